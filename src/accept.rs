@@ -0,0 +1,30 @@
+//! Keeping accept loops alive through accept errors.
+
+use std::io;
+use std::time::Duration;
+
+/// Pause after an accept error that isn't specific to one connection, such
+/// as running out of file descriptors, before accepting again.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Logs an accept error and waits before the next accept unless the error
+/// concerns only the connection being accepted.
+pub(crate) async fn recover(error: io::Error) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(%error, "accept failed");
+    if !is_connection_error(&error) {
+        tokio::time::sleep(ACCEPT_BACKOFF).await;
+    }
+}
+
+/// Returns true if an accept error concerns only the connection being
+/// accepted, so the listener can accept the next one at once.
+fn is_connection_error(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::Interrupted
+    )
+}
@@ -0,0 +1,61 @@
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::Instant;
+
+/// Wakes a parked OS thread.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Drives `future` to completion on the current thread, parking it between
+/// polls instead of spinning. No async runtime is required.
+///
+/// Returns `None` if `deadline` passes before the future completes.
+pub(crate) fn block_on<F: Future>(future: F, deadline: Option<Instant>) -> Option<F::Output> {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return Some(output);
+        }
+        // Spurious unparks simply lead to another poll.
+        match deadline {
+            None => thread::park(),
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return None;
+                }
+                thread::park_timeout(remaining);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_block_on_ready_and_deadline() {
+        assert_eq!(block_on(async { 42 }, None), Some(42));
+
+        let deadline = Instant::now() + Duration::from_millis(10);
+        assert_eq!(block_on(std::future::pending::<()>(), Some(deadline)), None);
+        assert!(Instant::now() >= deadline);
+    }
+}
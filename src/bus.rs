@@ -1,7 +1,17 @@
-use crate::{sub::Sub, topic::Topic};
-use ahash::AHasher;
+use crate::{
+    config::{NameHasher, TopicConfig, TopicConfigs},
+    error::BusError,
+    history::DeliveryMode,
+    lifecycle::Lifecycle,
+    multi::MultiSub,
+    pattern::{PatternRegistry, PatternSub, TopicPattern},
+    reply::ReplyRegistry,
+    snapshot::{BusSnapshot, TopicSnapshot},
+    stats::{BusCounters, BusStats},
+    sub::Sub,
+    topic::Topic,
+};
 use dashmap::DashMap;
-use std::hash::BuildHasherDefault;
 use std::sync::Arc;
 
 #[cfg(target_arch = "x86_64")]
@@ -9,7 +19,12 @@ use std::arch::x86_64::{_MM_HINT_T0, _mm_prefetch};
 
 /// High-performance publish-subscribe message broker with latest-only delivery.
 pub struct Bus<T> {
-    topics: DashMap<Arc<str>, Arc<Topic<T>>, BuildHasherDefault<AHasher>>,
+    topics: DashMap<Arc<str>, Arc<Topic<T>>, NameHasher>,
+    configs: TopicConfigs<T>,
+    patterns: Arc<PatternRegistry<T>>,
+    counters: BusCounters,
+    replies: Arc<ReplyRegistry<T>>,
+    lifecycle: Arc<Lifecycle>,
 }
 
 impl<T> Default for Bus<T>
@@ -29,9 +44,7 @@ where
     /// Creates a new Bus with default settings.
     #[inline]
     pub fn new() -> Self {
-        Self {
-            topics: DashMap::with_capacity_and_hasher(16, BuildHasherDefault::<AHasher>::default()),
-        }
+        Self::builder().build()
     }
 
     /// Creates a Bus with specified initial capacity.
@@ -47,16 +60,29 @@ where
     /// ```
     #[inline]
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::builder().capacity(capacity).build()
+    }
+
+    pub(crate) fn from_parts(
+        capacity: usize,
+        hasher: NameHasher,
+        configs: TopicConfigs<T>,
+    ) -> Self {
         Self {
-            topics: DashMap::with_capacity_and_hasher(
-                capacity,
-                BuildHasherDefault::<AHasher>::default(),
-            ),
+            topics: DashMap::with_capacity_and_hasher(capacity, hasher),
+            configs,
+            patterns: Arc::new(PatternRegistry::new()),
+            counters: BusCounters::default(),
+            replies: Arc::new(ReplyRegistry::new()),
+            lifecycle: Arc::default(),
         }
     }
 
     /// Gets existing topic or creates a new one.
     ///
+    /// A new topic gets the config the bus was built with for its name; see
+    /// [`Bus::builder`].
+    ///
     /// # Examples
     /// ```
     /// # use dropslot::Bus;
@@ -69,7 +95,30 @@ where
         if let Some(topic) = self.get_topic_with_prefetch(&key) {
             return topic;
         }
-        self.create_topic_with_race_protection(key, name.to_string())
+        self.create_topic_with_race_protection(key, name.to_string(), self.configs.resolve(name))
+    }
+
+    /// Gets existing topic or creates a new one with the given delivery mode.
+    ///
+    /// The mode only applies when the topic is created, overriding the mode
+    /// of its configured [`TopicConfig`]; an existing topic keeps the mode it
+    /// was created with.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::{Bus, DeliveryMode};
+    /// let bus = Bus::<String>::new();
+    /// let topic = bus.topic_with_mode("audit", DeliveryMode::history(64));
+    /// assert_eq!(topic.delivery_mode(), DeliveryMode::history(64));
+    /// ```
+    #[inline]
+    pub fn topic_with_mode(&self, name: &str, mode: DeliveryMode) -> Arc<Topic<T>> {
+        let key: Arc<str> = name.into();
+        if let Some(topic) = self.get_topic_with_prefetch(&key) {
+            return topic;
+        }
+        let config = self.configs.resolve(name).mode(mode);
+        self.create_topic_with_race_protection(key, name.to_string(), &config)
     }
 
     /// Publishes a message to the specified topic.
@@ -106,6 +155,16 @@ where
         topic.subscribe()
     }
 
+    /// Creates a subscriber for the specified topic, respecting its
+    /// admission limit. See [`Topic::try_subscribe`].
+    ///
+    /// # Errors
+    /// Returns [`BusError::CapacityExceeded`] if the topic is full.
+    #[inline]
+    pub fn try_subscribe(&self, topic_name: &str) -> Result<Sub<T>, BusError> {
+        self.topic(topic_name).try_subscribe()
+    }
+
     /// Returns the number of active topics.
     ///
     /// # Examples
@@ -144,9 +203,8 @@ where
     #[inline]
     pub fn remove_topic(&self, topic_name: &str) -> Option<usize> {
         let key: Arc<str> = topic_name.into();
-        self.topics
-            .remove(&key)
-            .map(|(_, topic)| topic.subscriber_count())
+        self.remove_topic_if(&key, |_| true)
+            .map(|topic| topic.subscriber_count())
     }
 
     /// Returns all topic names.
@@ -171,13 +229,16 @@ where
             .collect()
     }
 
-    /// Removes topics with no active subscribers.
+    /// Removes topics with no active subscribers, except pinned ones.
     ///
     /// This method helps prevent memory leaks by cleaning up unused topics.
-    /// It's safe to call periodically in long-running applications.
+    /// It's safe to call periodically in long-running applications. As with
+    /// [`remove_topic`](Self::remove_topic), the topics derived from a
+    /// removed topic are removed too.
     ///
     /// # Returns
-    /// The number of topics that were removed.
+    /// The number of topics that were removed, not counting derived topics
+    /// removed along with one of their sources.
     ///
     /// # Examples
     /// ```
@@ -194,16 +255,238 @@ where
     /// assert_eq!(bus.cleanup_unused_topics(), 1);
     /// ```
     pub fn cleanup_unused_topics(&self) -> usize {
-        let mut removed_count: usize = 0;
-        self.topics.retain(|_, topic| {
-            if topic.subscriber_count() == 0 {
-                removed_count = removed_count.saturating_add(1);
-                false
-            } else {
-                true
+        #[cfg(feature = "tracing")]
+        let _entered =
+            tracing::debug_span!("cleanup_unused_topics", topics = self.topics.len()).entered();
+        let removed_count = self
+            .topic_keys()
+            .iter()
+            .filter(|key| {
+                self.remove_topic_if(key, |topic| {
+                    topic.subscriber_count() == 0 && !topic.is_pinned()
+                })
+                .is_some()
+            })
+            .count();
+        #[cfg(feature = "tracing")]
+        tracing::debug!(removed = removed_count, "cleanup finished");
+        removed_count
+    }
+
+    /// Captures the latest value and version of every topic.
+    ///
+    /// Topics that never received a value are skipped. Each topic is read
+    /// consistently, but topics published to while the snapshot is taken may
+    /// be captured before or after that publish.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<u32>::new();
+    /// bus.publish("a", 1);
+    /// bus.publish("a", 2);
+    /// let _empty = bus.topic("b");
+    ///
+    /// let snapshot = bus.snapshot();
+    /// assert_eq!(snapshot.len(), 1);
+    /// let a = snapshot.get("a").unwrap();
+    /// assert_eq!((a.value, a.version), (2, 2));
+    /// ```
+    pub fn snapshot(&self) -> BusSnapshot<T> {
+        let mut topics: Vec<_> = self
+            .topics
+            .iter()
+            .filter_map(|entry| {
+                entry
+                    .value()
+                    .latest_with_version()
+                    .map(|(value, version)| TopicSnapshot {
+                        name: entry.key().to_string(),
+                        version,
+                        value,
+                    })
+            })
+            .collect();
+        topics.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        BusSnapshot { topics }
+    }
+
+    /// Returns the usage counters of the bus and every topic.
+    ///
+    /// Bus totals include the counters of topics removed since. Counters
+    /// are read one at a time while the bus keeps running, so the snapshot
+    /// is not taken at a single instant.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<u32>::new();
+    /// bus.publish("a", 1);
+    /// bus.publish("b", 2);
+    /// bus.remove_topic("b");
+    ///
+    /// let stats = bus.stats();
+    /// assert_eq!((stats.topics_created, stats.topics_removed), (2, 1));
+    /// assert_eq!(stats.publishes, 2);
+    /// assert_eq!(stats.topics.len(), 1);
+    /// ```
+    pub fn stats(&self) -> BusStats {
+        let topics = self
+            .topics
+            .iter()
+            .map(|entry| entry.value().stats())
+            .collect();
+        self.counters.collect(topics)
+    }
+
+    /// Restores the latest values and versions captured by
+    /// [`snapshot`](Bus::snapshot) and returns the number of topics restored.
+    ///
+    /// Missing topics are created as latest-only topics; existing ones keep
+    /// their delivery mode and derived-topic operators, so set those up
+    /// before restoring. Restoring does not run derived-topic operators.
+    /// Versions never move backwards: a topic that has already been
+    /// published past the captured version keeps its value and is not
+    /// counted.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<String>::new();
+    /// bus.publish("status", "online".to_string());
+    /// let snapshot = bus.snapshot();
+    ///
+    /// let restarted = Bus::<String>::new();
+    /// assert_eq!(restarted.restore(snapshot), 1);
+    ///
+    /// let mut subscriber = restarted.subscribe("status");
+    /// assert_eq!(subscriber.get_latest(), Some("online".to_string()));
+    ///
+    /// restarted.publish("status", "busy".to_string());
+    /// let delivery = subscriber.try_get_delivery().unwrap().unwrap();
+    /// assert_eq!(delivery.version, 2);
+    /// ```
+    pub fn restore(&self, snapshot: BusSnapshot<T>) -> usize {
+        let mut count = 0;
+        for entry in snapshot.topics {
+            if self.topic(&entry.name).restore(entry.value, entry.version) {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Gets existing topic or creates a new one with `config`.
+    pub(crate) fn topic_configured(&self, name: &str, config: &TopicConfig<T>) -> Arc<Topic<T>> {
+        let key: Arc<str> = name.into();
+        if let Some(topic) = self.get_topic_with_prefetch(&key) {
+            return topic;
+        }
+        self.create_topic_with_race_protection(key, name.to_string(), config)
+    }
+
+    /// Inserts a topic that must not exist yet, configured as its name
+    /// dictates apart from its delivery mode.
+    pub(crate) fn insert_new_topic(&self, topic: Topic<T>) -> Result<Arc<Topic<T>>, BusError> {
+        match self.topics.entry(topic.name().into()) {
+            dashmap::mapref::entry::Entry::Occupied(entry) => {
+                Err(BusError::topic_exists(&**entry.key()))
+            }
+            dashmap::mapref::entry::Entry::Vacant(entry) => {
+                let mut topic = topic;
+                self.configs.resolve(topic.name()).apply(&mut topic);
+                let topic = Arc::new(topic);
+                #[cfg(feature = "tracing")]
+                topic
+                    .span()
+                    .in_scope(|| tracing::debug!(sources = ?topic.sources(), "topic created"));
+                self.counters.record_created();
+                self.lifecycle.on_topic_created(&topic);
+                self.patterns.on_topic_created(&topic);
+                entry.insert(topic.clone());
+                Ok(topic)
             }
+        }
+    }
+
+    /// Removes the topic under `key` if `remove` approves of it, along with
+    /// the topics derived from it.
+    pub(crate) fn remove_topic_if(
+        &self,
+        key: &Arc<str>,
+        remove: impl FnOnce(&Topic<T>) -> bool,
+    ) -> Option<Arc<Topic<T>>> {
+        let (_, topic) = self.topics.remove_if(key, |_, topic| remove(topic))?;
+        #[cfg(feature = "tracing")]
+        topic.span().in_scope(|| {
+            tracing::debug!(
+                version = topic.get_current_version(),
+                subscribers = topic.subscriber_count(),
+                "topic removed"
+            );
         });
-        removed_count
+        self.counters.record_removed(topic.counters());
+        self.lifecycle.on_topic_removed(&topic);
+        self.remove_derived_topics(&topic);
+        Some(topic)
+    }
+
+    /// Returns the keys of every topic.
+    pub(crate) fn topic_keys(&self) -> Vec<Arc<str>> {
+        self.topics
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect()
+    }
+
+    /// Removes every topic derived from `source`, directly or through other
+    /// derived topics, and detaches them from their remaining sources.
+    fn remove_derived_topics(&self, source: &Topic<T>) {
+        // Every derived topic hooks all of its sources, so a topic without
+        // hooks feeds nothing and the scan below can be skipped.
+        if !source.clear_hooks() {
+            return;
+        }
+        let dependents: Vec<Arc<str>> = self
+            .topics
+            .iter()
+            .filter(|entry| {
+                entry
+                    .value()
+                    .source_names()
+                    .iter()
+                    .any(|name| &**name == source.name())
+            })
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        for key in dependents {
+            if let Some((_, derived)) = self.topics.remove(&key) {
+                #[cfg(feature = "tracing")]
+                derived.span().in_scope(|| {
+                    tracing::debug!(source = source.name(), "derived topic removed");
+                });
+                self.counters.record_removed(derived.counters());
+                self.lifecycle.on_topic_removed(&derived);
+                let target = Arc::downgrade(&derived);
+                for name in derived.source_names() {
+                    if let Some(other) = self.topics.get(name) {
+                        other.remove_hooks_for(&target);
+                    }
+                }
+                self.remove_derived_topics(&derived);
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn lifecycle(&self) -> &Arc<Lifecycle> {
+        &self.lifecycle
+    }
+
+    #[inline(always)]
+    pub(crate) fn replies(&self) -> &Arc<ReplyRegistry<T>> {
+        &self.replies
     }
 
     #[inline(always)]
@@ -220,8 +503,11 @@ where
         &self,
         key: Arc<str>,
         name: String,
+        config: &TopicConfig<T>,
     ) -> Arc<Topic<T>> {
-        let topic = Arc::new(Topic::new(name));
+        let mut topic = Topic::with_mode(name, config.delivery_mode());
+        config.apply(&mut topic);
+        let topic = Arc::new(topic);
         match self.topics.entry(key) {
             dashmap::mapref::entry::Entry::Occupied(entry) => {
                 let existing = entry.get();
@@ -229,6 +515,13 @@ where
                 existing.clone()
             }
             dashmap::mapref::entry::Entry::Vacant(entry) => {
+                #[cfg(feature = "tracing")]
+                topic
+                    .span()
+                    .in_scope(|| tracing::debug!(mode = ?topic.delivery_mode(), "topic created"));
+                self.counters.record_created();
+                self.lifecycle.on_topic_created(&topic);
+                self.patterns.on_topic_created(&topic);
                 entry.insert(topic.clone());
                 topic
             }
@@ -236,6 +529,104 @@ where
     }
 }
 
+impl<T> Bus<T>
+where
+    T: Clone + PartialEq,
+{
+    /// Sets whether publishes to `topic_name` skip messages equal to its
+    /// latest one, creating the topic if needed.
+    ///
+    /// The policy stays with the topic; a removed and recreated topic starts
+    /// without it. See [`Topic::publish_if_changed`] for one-off publishes.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<String>::new();
+    /// bus.set_dedup("config", true);
+    ///
+    /// bus.publish("config", "v1".to_string());
+    /// bus.publish("config", "v1".to_string());
+    /// assert_eq!(bus.topic("config").version(), 1);
+    /// ```
+    pub fn set_dedup(&self, topic_name: &str, enabled: bool) {
+        self.topic(topic_name).set_dedup(enabled);
+    }
+}
+
+impl<T> Bus<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    /// Creates a subscriber that waits on several topics at once.
+    ///
+    /// Topics that don't exist yet are created. More topics can be added to
+    /// or removed from the returned [`MultiSub`] later.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let bus = Bus::<String>::new();
+    /// let mut subscriber = bus.subscribe_many(&["orders", "trades"]);
+    /// assert_eq!(subscriber.len(), 2);
+    ///
+    /// bus.publish("orders", "buy".to_string());
+    /// let (topic, message) = subscriber.wait_for_message().await.unwrap();
+    /// assert_eq!(&*topic, "orders");
+    /// assert_eq!(message, "buy");
+    /// # }
+    /// ```
+    pub fn subscribe_many(&self, topic_names: &[&str]) -> MultiSub<T> {
+        let mut subscriber = MultiSub::new();
+        for topic_name in topic_names {
+            subscriber.add(self.subscribe(topic_name));
+        }
+        subscriber
+    }
+
+    /// Creates a subscriber for every topic matching a wildcard pattern.
+    ///
+    /// Topic names are split into levels on `/`; `+` matches exactly one
+    /// level and `#` matches all remaining levels. Matching topics that
+    /// already hold a value deliver it first, and topics created later are
+    /// picked up automatically.
+    ///
+    /// # Errors
+    /// Returns `Err(BusError::invalid_pattern(..))` if the pattern is malformed.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let bus = Bus::<f64>::new();
+    /// bus.publish("sensors/floor1/temp", 21.5);
+    ///
+    /// let mut temps = bus.subscribe_pattern("sensors/+/temp").unwrap();
+    /// let (topic, value) = temps.wait_for_message().await.unwrap();
+    /// assert_eq!(&*topic, "sensors/floor1/temp");
+    /// assert_eq!(value, 21.5);
+    ///
+    /// bus.publish("sensors/floor2/temp", 19.0);
+    /// let (topic, value) = temps.wait_for_message().await.unwrap();
+    /// assert_eq!(&*topic, "sensors/floor2/temp");
+    /// assert_eq!(value, 19.0);
+    /// # }
+    /// ```
+    pub fn subscribe_pattern(&self, pattern: &str) -> Result<PatternSub<T>, BusError> {
+        let pattern = TopicPattern::new(pattern)?;
+        let subscriber = PatternSub::new(pattern, &self.patterns);
+        for entry in self.topics.iter() {
+            if subscriber.pattern().matches(entry.key()) {
+                subscriber.attach_existing(entry.value());
+            }
+        }
+        Ok(subscriber)
+    }
+}
+
 #[inline(always)]
 pub(crate) fn prefetch_read<T>(data: *const T) {
     #[cfg(target_arch = "x86_64")]
@@ -332,14 +723,20 @@ mod tests {
         let bus = Bus::<String>::new();
 
         let vacant_key: Arc<str> = "new_topic".into();
-        let topic =
-            bus.create_topic_with_race_protection(vacant_key.clone(), "new_topic".to_string());
+        let topic = bus.create_topic_with_race_protection(
+            vacant_key.clone(),
+            "new_topic".to_string(),
+            &TopicConfig::new(),
+        );
         assert_eq!(topic.name(), "new_topic");
 
         let _existing_topic = bus.topic("existing");
         let occupied_key: Arc<str> = "existing".into();
-        let topic =
-            bus.create_topic_with_race_protection(occupied_key.clone(), "existing".to_string());
+        let topic = bus.create_topic_with_race_protection(
+            occupied_key.clone(),
+            "existing".to_string(),
+            &TopicConfig::new(),
+        );
         assert_eq!(topic.name(), "existing");
     }
 
@@ -0,0 +1,406 @@
+//! Building buses with per-topic configuration.
+//!
+//! [`Bus::builder`] returns a [`BusBuilder`] that sets the initial capacity,
+//! the hash function for topic names, and the [`TopicConfig`] topics get when
+//! they are created: a default, plus overrides for names matching wildcard
+//! patterns. [`Bus::topic_with`] creates a topic with an explicit config.
+//!
+//! ```rust
+//! use dropslot::prelude::*;
+//! use dropslot::{BusHasher, TopicConfig};
+//! use std::time::Duration;
+//!
+//! let bus = Bus::<f64>::builder()
+//!     .capacity(1024)
+//!     .hasher(BusHasher::SipHash)
+//!     .default_config(TopicConfig::new().ttl(Duration::from_secs(60)))
+//!     .pattern_config("audit/#", TopicConfig::new().mode(DeliveryMode::history(100)).pinned(true))
+//!     .unwrap()
+//!     .build();
+//!
+//! assert_eq!(bus.topic("sensors/temp").default_ttl(), Some(Duration::from_secs(60)));
+//! assert_eq!(bus.topic("audit/login").delivery_mode(), DeliveryMode::history(100));
+//! ```
+//!
+//! [`Bus::builder`]: crate::Bus::builder
+//! [`Bus::topic_with`]: crate::Bus::topic_with
+
+use crate::{
+    bus::Bus, error::BusError, history::DeliveryMode, pattern::TopicPattern, topic::Topic,
+};
+use ahash::AHasher;
+use std::collections::hash_map::{DefaultHasher, RandomState};
+use std::hash::{BuildHasher, BuildHasherDefault, Hasher};
+use std::time::Duration;
+
+/// How a topic behaves, applied when it is created.
+///
+/// The default is a latest-only topic without time-to-live, deduplication,
+/// admission limit or pinning.
+pub struct TopicConfig<T> {
+    mode: DeliveryMode,
+    ttl: Option<Duration>,
+    dedup: Option<fn(&T, &T) -> bool>,
+    admission_limit: Option<usize>,
+    pinned: bool,
+}
+
+impl<T> TopicConfig<T> {
+    /// Creates the default config.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            mode: DeliveryMode::Latest,
+            ttl: None,
+            dedup: None,
+            admission_limit: None,
+            pinned: false,
+        }
+    }
+
+    /// Sets the delivery mode. Derived topics are always latest-only.
+    #[inline]
+    pub fn mode(mut self, mode: DeliveryMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the default time-to-live of published values.
+    ///
+    /// See [`Topic::set_default_ttl`].
+    #[inline]
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Limits how many subscribers [`Topic::try_subscribe`] admits.
+    ///
+    /// See [`Topic::set_admission_limit`]: other ways of subscribing ignore
+    /// the limit.
+    #[inline]
+    pub fn admission_limit(mut self, limit: usize) -> Self {
+        self.admission_limit = Some(limit);
+        self
+    }
+
+    /// Sets whether the topic is kept by
+    /// [`Bus::cleanup_unused_topics`](crate::Bus::cleanup_unused_topics) and
+    /// [`Bus::reap`](crate::Bus::reap). See [`Topic::set_pinned`].
+    #[inline]
+    pub fn pinned(mut self, pinned: bool) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    /// Applies everything but the delivery mode, which is fixed when the
+    /// topic is constructed, and records the config on the topic.
+    pub(crate) fn apply(&self, topic: &mut Topic<T>)
+    where
+        T: Clone,
+    {
+        topic.set_default_ttl(self.ttl);
+        if let Some(eq) = self.dedup {
+            topic.enable_dedup(eq);
+        }
+        topic.set_admission_limit(self.admission_limit);
+        topic.set_pinned(self.pinned);
+        let mode = topic.delivery_mode();
+        topic.set_config(self.mode(mode));
+    }
+
+    /// Returns true if `topic` was created with this config.
+    ///
+    /// Compares against the recorded config rather than the topic's current
+    /// settings, which may have been changed since.
+    pub(crate) fn is_applied_to(&self, topic: &Topic<T>) -> bool
+    where
+        T: Clone,
+    {
+        let applied = topic.config();
+        applied.mode == self.mode
+            && applied.ttl == self.ttl
+            && applied.dedup.is_some() == self.dedup.is_some()
+            && applied.admission_limit == self.admission_limit
+            && applied.pinned == self.pinned
+    }
+
+    #[inline]
+    pub(crate) fn delivery_mode(&self) -> DeliveryMode {
+        self.mode
+    }
+}
+
+impl<T> TopicConfig<T>
+where
+    T: PartialEq,
+{
+    /// Sets whether publishes skip messages equal to the latest one.
+    ///
+    /// See [`Topic::set_dedup`].
+    #[inline]
+    pub fn dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup.then_some(T::eq as fn(&T, &T) -> bool);
+        self
+    }
+}
+
+impl<T> Default for TopicConfig<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for TopicConfig<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for TopicConfig<T> {}
+
+impl<T> std::fmt::Debug for TopicConfig<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TopicConfig")
+            .field("mode", &self.mode)
+            .field("ttl", &self.ttl)
+            .field("dedup", &self.dedup.is_some())
+            .field("admission_limit", &self.admission_limit)
+            .field("pinned", &self.pinned)
+            .finish()
+    }
+}
+
+/// Hash function for topic names.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BusHasher {
+    /// aHash with fixed keys: the fastest, for trusted topic names.
+    #[default]
+    AHash,
+    /// The standard library's randomly keyed SipHash, resistant to
+    /// collision attacks through untrusted topic names.
+    SipHash,
+}
+
+/// Builds the hashers of a bus's topic map.
+#[derive(Debug, Clone)]
+pub(crate) enum NameHasher {
+    AHash(BuildHasherDefault<AHasher>),
+    SipHash(RandomState),
+}
+
+impl From<BusHasher> for NameHasher {
+    fn from(hasher: BusHasher) -> Self {
+        match hasher {
+            BusHasher::AHash => NameHasher::AHash(BuildHasherDefault::default()),
+            BusHasher::SipHash => NameHasher::SipHash(RandomState::new()),
+        }
+    }
+}
+
+impl BuildHasher for NameHasher {
+    type Hasher = NameHashState;
+
+    #[inline]
+    fn build_hasher(&self) -> NameHashState {
+        match self {
+            NameHasher::AHash(build) => NameHashState::AHash(build.build_hasher()),
+            NameHasher::SipHash(build) => NameHashState::SipHash(build.build_hasher()),
+        }
+    }
+}
+
+pub(crate) enum NameHashState {
+    AHash(AHasher),
+    SipHash(DefaultHasher),
+}
+
+impl Hasher for NameHashState {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        match self {
+            NameHashState::AHash(hasher) => hasher.write(bytes),
+            NameHashState::SipHash(hasher) => hasher.write(bytes),
+        }
+    }
+
+    #[inline]
+    fn write_u8(&mut self, byte: u8) {
+        match self {
+            NameHashState::AHash(hasher) => hasher.write_u8(byte),
+            NameHashState::SipHash(hasher) => hasher.write_u8(byte),
+        }
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        match self {
+            NameHashState::AHash(hasher) => hasher.finish(),
+            NameHashState::SipHash(hasher) => hasher.finish(),
+        }
+    }
+}
+
+/// The configs new topics of a bus get.
+#[derive(Debug)]
+pub(crate) struct TopicConfigs<T> {
+    default: TopicConfig<T>,
+    patterns: Vec<(TopicPattern, TopicConfig<T>)>,
+}
+
+impl<T> TopicConfigs<T> {
+    /// Returns the config of the first pattern matching `name`, or the
+    /// default.
+    pub(crate) fn resolve(&self, name: &str) -> &TopicConfig<T> {
+        self.patterns
+            .iter()
+            .find(|(pattern, _)| pattern.matches(name))
+            .map_or(&self.default, |(_, config)| config)
+    }
+}
+
+impl<T> Default for TopicConfigs<T> {
+    fn default() -> Self {
+        Self {
+            default: TopicConfig::new(),
+            patterns: Vec::new(),
+        }
+    }
+}
+
+/// Builds a [`Bus`] with custom settings.
+///
+/// Created by [`Bus::builder`].
+#[derive(Debug)]
+pub struct BusBuilder<T> {
+    capacity: usize,
+    hasher: BusHasher,
+    configs: TopicConfigs<T>,
+}
+
+impl<T> BusBuilder<T>
+where
+    T: Clone,
+{
+    /// Creates a builder with the settings of [`Bus::new`].
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            capacity: 16,
+            hasher: BusHasher::default(),
+            configs: TopicConfigs::default(),
+        }
+    }
+
+    /// Sets the initial capacity of the topic map.
+    #[inline]
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets the hash function for topic names.
+    #[inline]
+    pub fn hasher(mut self, hasher: BusHasher) -> Self {
+        self.hasher = hasher;
+        self
+    }
+
+    /// Sets the config of topics no pattern config applies to.
+    #[inline]
+    pub fn default_config(mut self, config: TopicConfig<T>) -> Self {
+        self.configs.default = config;
+        self
+    }
+
+    /// Sets the config of topics whose names match `pattern`, an exact name
+    /// or a wildcard pattern as in [`Bus::subscribe_pattern`]. The first
+    /// matching pattern wins.
+    ///
+    /// # Errors
+    /// Returns [`BusError::InvalidPattern`] if `pattern` is malformed.
+    ///
+    /// [`Bus::subscribe_pattern`]: crate::Bus::subscribe_pattern
+    pub fn pattern_config(
+        mut self,
+        pattern: &str,
+        config: TopicConfig<T>,
+    ) -> Result<Self, BusError> {
+        let pattern = TopicPattern::new(pattern)?;
+        self.configs.patterns.push((pattern, config));
+        Ok(self)
+    }
+
+    /// Builds the bus.
+    pub fn build(self) -> Bus<T> {
+        Bus::from_parts(self.capacity, self.hasher.into(), self.configs)
+    }
+}
+
+impl<T> Default for BusBuilder<T>
+where
+    T: Clone,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Bus<T>
+where
+    T: Clone,
+{
+    /// Returns a builder for a bus with custom settings.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::{Bus, TopicConfig};
+    /// let bus = Bus::<u32>::builder()
+    ///     .default_config(TopicConfig::new().admission_limit(1))
+    ///     .build();
+    ///
+    /// let _first = bus.try_subscribe("jobs").unwrap();
+    /// assert!(bus.try_subscribe("jobs").is_err());
+    /// ```
+    #[inline]
+    pub fn builder() -> BusBuilder<T> {
+        BusBuilder::new()
+    }
+
+    /// Gets the topic `name` or creates it with `config`.
+    ///
+    /// A topic that exists is returned if it was created with the same
+    /// config, even if its settings were changed at runtime since, for
+    /// example with [`Topic::set_pinned`].
+    ///
+    /// # Errors
+    /// Returns [`BusError::ConfigConflict`] if the topic exists but was
+    /// created with another config.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::{Bus, DeliveryMode, TopicConfig};
+    /// let bus = Bus::<String>::new();
+    /// let config = TopicConfig::new().mode(DeliveryMode::history(8)).dedup(true);
+    ///
+    /// let topic = bus.topic_with("audit", config).unwrap();
+    /// assert!(topic.dedups());
+    /// assert!(bus.topic_with("audit", config).is_ok());
+    /// assert!(bus.topic_with("audit", TopicConfig::new()).unwrap_err().is_config_conflict());
+    /// ```
+    pub fn topic_with(
+        &self,
+        name: &str,
+        config: TopicConfig<T>,
+    ) -> Result<std::sync::Arc<Topic<T>>, BusError> {
+        let topic = self.topic_configured(name, &config);
+        if config.is_applied_to(&topic) {
+            Ok(topic)
+        } else {
+            Err(BusError::config_conflict(name))
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Derived topics that are recomputed whenever their sources publish.
+//!
+//! Operators such as [`Bus::map`] create a new topic on the bus and attach a
+//! hook to each source topic. The hook runs synchronously on every publish,
+//! so a derived topic is updated before the source's `publish` returns.
+//! Removing any source with [`Bus::remove_topic`] removes the topics derived
+//! from it as well.
+//!
+//! ```rust
+//! use dropslot::prelude::*;
+//!
+//! let bus = Bus::<i32>::new();
+//! let doubled = bus.map("values", "values/doubled", |v| v * 2).unwrap();
+//! let subscriber = doubled.subscribe();
+//!
+//! bus.publish("values", 21);
+//! assert_eq!(subscriber.get_latest(), Some(42));
+//! ```
+
+use crate::{bus::Bus, error::BusError, topic::Topic};
+use std::sync::{Arc, Weak};
+
+type Compute<T> = Arc<dyn Fn(&T) -> Option<T> + Send + Sync>;
+
+/// Updates a derived topic from a message published to one of its sources.
+pub(crate) struct DerivedHook<T> {
+    target: Weak<Topic<T>>,
+    compute: Compute<T>,
+    emit: fn(&Topic<T>, T),
+}
+
+impl<T> DerivedHook<T>
+where
+    T: Clone,
+{
+    /// Runs the hook, returning `false` if the derived topic is gone.
+    #[inline]
+    pub(crate) fn run(&self, message: &T) -> bool {
+        match self.target.upgrade() {
+            Some(target) => {
+                if let Some(output) = (self.compute)(message) {
+                    (self.emit)(&target, output);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<T> DerivedHook<T> {
+    #[inline]
+    pub(crate) fn feeds(&self, target: &Weak<Topic<T>>) -> bool {
+        self.target.ptr_eq(target)
+    }
+
+    #[inline]
+    pub(crate) fn is_alive(&self) -> bool {
+        self.target.strong_count() > 0
+    }
+}
+
+impl<T> std::fmt::Debug for DerivedHook<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DerivedHook")
+            .field("alive", &self.is_alive())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Bus<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    /// Creates a topic named `name` that holds `f` applied to every message
+    /// published to `source`.
+    ///
+    /// The source topic is created if it doesn't exist; if it already holds
+    /// a value, the derived topic starts with the mapped value.
+    ///
+    /// # Errors
+    /// Returns `Err(BusError::topic_exists(..))` if a topic named `name`
+    /// already exists.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<String>::new();
+    /// let upper = bus.map("names", "names/upper", |name| name.to_uppercase()).unwrap();
+    ///
+    /// bus.publish("names", "ada".to_string());
+    /// assert_eq!(upper.subscribe().get_latest(), Some("ADA".to_string()));
+    /// ```
+    pub fn map<F>(&self, source: &str, name: &str, f: F) -> Result<Arc<Topic<T>>, BusError>
+    where
+        F: Fn(&T) -> T + Send + Sync + 'static,
+    {
+        self.derive(&[source], name, publish, |_| {
+            move |message: &T| Some(f(message))
+        })
+    }
+
+    /// Creates a topic named `name` that republishes the messages from
+    /// `source` for which `predicate` returns true.
+    ///
+    /// # Errors
+    /// Returns `Err(BusError::topic_exists(..))` if a topic named `name`
+    /// already exists.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<i32>::new();
+    /// let positive = bus.filter("readings", "readings/positive", |v| *v > 0).unwrap();
+    /// let subscriber = positive.subscribe();
+    ///
+    /// bus.publish("readings", 5);
+    /// bus.publish("readings", -3);
+    /// assert_eq!(subscriber.get_latest(), Some(5));
+    /// ```
+    pub fn filter<P>(
+        &self,
+        source: &str,
+        name: &str,
+        predicate: P,
+    ) -> Result<Arc<Topic<T>>, BusError>
+    where
+        P: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        self.derive(&[source], name, publish, |_| {
+            move |message: &T| predicate(message).then(|| message.clone())
+        })
+    }
+
+    /// Creates a topic named `name` that republishes messages from `source`
+    /// only when they differ from the previous one.
+    ///
+    /// # Errors
+    /// Returns `Err(BusError::topic_exists(..))` if a topic named `name`
+    /// already exists.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<u8>::new();
+    /// let changes = bus.distinct_until_changed("state", "state/changes").unwrap();
+    /// let mut subscriber = changes.subscribe();
+    ///
+    /// bus.publish("state", 1);
+    /// assert_eq!(subscriber.try_get_message().unwrap(), Some(1));
+    ///
+    /// bus.publish("state", 1);
+    /// assert!(subscriber.try_get_message().unwrap_err().is_empty());
+    /// ```
+    pub fn distinct_until_changed(
+        &self,
+        source: &str,
+        name: &str,
+    ) -> Result<Arc<Topic<T>>, BusError>
+    where
+        T: PartialEq,
+    {
+        self.derive(
+            &[source],
+            name,
+            |target, message| {
+                target.publish_if_changed(message);
+            },
+            |_| |message: &T| Some(message.clone()),
+        )
+    }
+
+    /// Creates a topic named `name` that holds `f` applied to the latest
+    /// message of every topic in `sources`.
+    ///
+    /// `f` receives the messages in the order of `sources` and runs whenever
+    /// any source publishes, once all of them hold a value.
+    ///
+    /// # Errors
+    /// Returns `Err(BusError::topic_exists(..))` if a topic named `name`
+    /// already exists, or `Err(BusError::no_sources(..))` if `sources` is
+    /// empty.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<f64>::new();
+    /// let spread = bus
+    ///     .combine_latest(&["ask", "bid"], "spread", |prices| prices[0] - prices[1])
+    ///     .unwrap();
+    /// let subscriber = spread.subscribe();
+    ///
+    /// bus.publish("ask", 101.0);
+    /// assert_eq!(subscriber.get_latest(), None);
+    ///
+    /// bus.publish("bid", 100.5);
+    /// assert_eq!(subscriber.get_latest(), Some(0.5));
+    /// ```
+    pub fn combine_latest<F>(
+        &self,
+        sources: &[&str],
+        name: &str,
+        f: F,
+    ) -> Result<Arc<Topic<T>>, BusError>
+    where
+        F: Fn(&[T]) -> T + Send + Sync + 'static,
+    {
+        self.derive(sources, name, publish, |sources| {
+            let inputs: Vec<Weak<Topic<T>>> = sources.iter().map(Arc::downgrade).collect();
+            move |_: &T| {
+                let latest: Option<Vec<T>> = inputs
+                    .iter()
+                    .map(|input| input.upgrade().and_then(|input| input.latest()))
+                    .collect();
+                latest.map(|latest| f(&latest))
+            }
+        })
+    }
+
+    /// Creates the derived topic `name`, then its sources, and hooks the
+    /// function `make` builds from the sources to every one of them. `emit`
+    /// publishes the function's results to the derived topic.
+    fn derive<C>(
+        &self,
+        sources: &[&str],
+        name: &str,
+        emit: fn(&Topic<T>, T),
+        make: impl FnOnce(&[Arc<Topic<T>>]) -> C,
+    ) -> Result<Arc<Topic<T>>, BusError>
+    where
+        C: Fn(&T) -> Option<T> + Send + Sync + 'static,
+    {
+        if sources.is_empty() {
+            return Err(BusError::no_sources(name));
+        }
+        if sources.contains(&name) {
+            return Err(BusError::topic_exists(name));
+        }
+        // The target goes first so that a name clash leaves the bus as it was.
+        let source_names = sources
+            .iter()
+            .map(|&source| Arc::<str>::from(source))
+            .collect();
+        let target = self.insert_new_topic(Topic::derived(name.to_string(), source_names))?;
+        let sources: Vec<Arc<Topic<T>>> = sources.iter().map(|source| self.topic(source)).collect();
+
+        let compute: Compute<T> = Arc::new(make(&sources));
+        for (index, source) in sources.iter().enumerate() {
+            if sources[..index]
+                .iter()
+                .any(|seen| Arc::ptr_eq(seen, source))
+            {
+                continue;
+            }
+            source.add_hook(DerivedHook {
+                target: Arc::downgrade(&target),
+                compute: compute.clone(),
+                emit,
+            });
+        }
+
+        // A hook that ran since the hooks were added saw a value at least as
+        // new as this one, so the seed only goes in if none did.
+        if let Some(seed) = sources[0].latest().and_then(|latest| compute(&latest)) {
+            let _ = target.publish_if_version(0, seed);
+        }
+        Ok(target)
+    }
+}
+
+fn publish<T>(target: &Topic<T>, message: T)
+where
+    T: Clone,
+{
+    target.publish(message);
+}
@@ -0,0 +1,167 @@
+//! Messages together with when, at which version and with what headers they
+//! were published.
+//!
+//! [`Topic::publish_with_meta`] attaches a small map of [`Headers`] to a
+//! message, and [`Sub::get_latest_envelope`] / [`Sub::wait_for_envelope`]
+//! return an [`Envelope`] with the message, its version, publish time and
+//! headers. Messages published without headers come with empty ones; the
+//! plain message APIs are unaffected.
+//!
+//! ```rust
+//! use dropslot::prelude::*;
+//! use dropslot::Headers;
+//!
+//! let bus = Bus::<String>::new();
+//! let topic = bus.topic("config");
+//! let subscriber = topic.subscribe();
+//!
+//! topic.publish_with_meta("v2".to_string(), Headers::new().with("origin", "deployer"));
+//!
+//! let envelope = subscriber.get_latest_envelope().unwrap();
+//! assert_eq!(envelope.message, "v2");
+//! assert_eq!(envelope.version, 1);
+//! assert_eq!(envelope.headers.get("origin"), Some("deployer"));
+//! ```
+//!
+//! [`Topic::publish_with_meta`]: crate::Topic::publish_with_meta
+//! [`Sub::get_latest_envelope`]: crate::Sub::get_latest_envelope
+//! [`Sub::wait_for_envelope`]: crate::Sub::wait_for_envelope
+
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+
+/// A small map of user-defined string headers.
+///
+/// Kept as a list, so lookups are linear; meant for a handful of entries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Headers {
+    entries: Vec<(String, String)>,
+}
+
+impl Headers {
+    /// Creates an empty header map.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the map with `name` set to `value`.
+    #[inline]
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    /// Sets `name` to `value`, returning the previous value.
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        let name = name.into();
+        let value = value.into();
+        match self.entries.iter_mut().find(|(key, _)| *key == name) {
+            Some((_, old)) => Some(std::mem::replace(old, value)),
+            None => {
+                self.entries.push((name, value));
+                None
+            }
+        }
+    }
+
+    /// Returns the value of `name`.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Iterates over the headers in insertion order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Returns the number of headers.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if there are no headers.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for Headers
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut headers = Self::new();
+        for (name, value) in iter {
+            headers.insert(name, value);
+        }
+        headers
+    }
+}
+
+/// A message with the details of its publish.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Envelope<T> {
+    /// The published message.
+    pub message: T,
+    /// Topic version the message was published at.
+    pub version: u64,
+    /// When the message was published, on the monotonic clock.
+    pub published_at: Instant,
+    /// When the message was published, on the wall clock.
+    ///
+    /// Recorded by [`Topic::publish_with_meta`](crate::Topic::publish_with_meta);
+    /// for other publishes it is derived from `published_at`.
+    pub timestamp: SystemTime,
+    /// Headers given to [`Topic::publish_with_meta`](crate::Topic::publish_with_meta),
+    /// empty for other publishes.
+    pub headers: Arc<Headers>,
+}
+
+/// What [`Topic::publish_with_meta`](crate::Topic::publish_with_meta)
+/// records besides the message.
+#[derive(Debug)]
+pub(crate) struct Meta {
+    pub(crate) version: u64,
+    pub(crate) timestamp: SystemTime,
+    pub(crate) headers: Arc<Headers>,
+}
+
+impl<T> Envelope<T> {
+    /// Wraps a message read at `version`, taking the recorded metadata if it
+    /// belongs to that version.
+    pub(crate) fn new(
+        message: T,
+        version: u64,
+        published_at: Instant,
+        meta: Option<Arc<Meta>>,
+    ) -> Self {
+        match meta.filter(|meta| meta.version == version) {
+            Some(meta) => Self {
+                message,
+                version,
+                published_at,
+                timestamp: meta.timestamp,
+                headers: meta.headers.clone(),
+            },
+            None => Self {
+                message,
+                version,
+                published_at,
+                timestamp: SystemTime::now() - published_at.elapsed(),
+                headers: Arc::default(),
+            },
+        }
+    }
+}
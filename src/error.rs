@@ -1,79 +1,512 @@
-/// Error type for subscriber operations.
+use std::sync::Arc;
+
+/// Error type for bus operations.
+///
+/// Every variant but [`InvalidPattern`](BusError::InvalidPattern) names the
+/// topic it is about; see [`topic`](BusError::topic). An invalid pattern is
+/// rejected before it is matched against any topic, so it carries the
+/// pattern instead.
+///
+/// [`Timeout`](BusError::Timeout), [`Closed`](BusError::Closed) and
+/// [`PermissionDenied`](BusError::PermissionDenied) can carry an underlying
+/// cause from a runtime, transport or the operating system, returned by
+/// [`Error::source`](std::error::Error::source). The other variants report
+/// conditions the bus detects itself and have no source.
+///
+/// New variants may be added as the bus grows, so matches need a wildcard
+/// arm.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum BusError {
-    /// Try receive-related errors.
-    TryRecv {
-        /// No new message is available.
-        empty: bool,
-        /// The topic has been dropped and no more messages will be sent.
-        disconnected: bool,
+    /// No new message is available.
+    Empty {
+        /// Name of the topic.
+        topic: String,
+    },
+    /// The topic has been dropped and no more messages will be sent.
+    Disconnected {
+        /// Name of the topic.
+        topic: String,
+    },
+    /// A topic that must exist does not.
+    TopicNotFound {
+        /// Name of the missing topic.
+        topic: String,
+    },
+    /// A wildcard topic pattern is malformed.
+    InvalidPattern {
+        /// The rejected pattern.
+        pattern: String,
+    },
+    /// A topic is registered with a different message type.
+    TypeMismatch {
+        /// Name of the topic.
+        topic: String,
+        /// Message type that was requested.
+        expected: &'static str,
+        /// Message type the topic was registered with.
+        found: &'static str,
+    },
+    /// A topic that must be new already exists.
+    TopicExists {
+        /// Name of the existing topic.
+        topic: String,
+    },
+    /// A topic exists, but was created with another config.
+    ConfigConflict {
+        /// Name of the topic.
+        topic: String,
+    },
+    /// A derived topic was given no source topics.
+    NoSources {
+        /// Name of the derived topic.
+        topic: String,
+    },
+    /// A request found no responder, or was dropped without a reply.
+    NoResponder {
+        /// Name of the request topic.
+        topic: String,
+    },
+    /// An operation on a topic did not finish in time.
+    Timeout {
+        /// Name of the topic.
+        topic: String,
+        /// What timed out, if known.
+        source: Option<ErrorSource>,
+    },
+    /// A topic or the channel carrying it was closed.
+    Closed {
+        /// Name of the topic.
+        topic: String,
+        /// Why it was closed, if known.
+        source: Option<ErrorSource>,
+    },
+    /// The latest message expired before it was read.
+    Stale {
+        /// Name of the topic.
+        topic: String,
+    },
+    /// A conditional publish found the topic at another version.
+    Conflict {
+        /// Name of the topic.
+        topic: String,
+        /// Version the publish expected.
+        expected: u64,
+        /// Version the topic was at.
+        found: u64,
+    },
+    /// A topic has as many subscribers as it admits.
+    CapacityExceeded {
+        /// Name of the topic.
+        topic: String,
+        /// The topic's admission limit.
+        limit: usize,
+    },
+    /// The caller may not access a topic.
+    PermissionDenied {
+        /// Name of the topic.
+        topic: String,
+        /// The underlying refusal, if any.
+        source: Option<ErrorSource>,
     },
 }
 
 impl BusError {
-    /// Creates an error for when the message queue is empty.
-    pub fn message_queue_empty() -> Self {
-        BusError::TryRecv {
-            empty: true,
-            disconnected: false,
+    /// Creates an error for when no new message is available on `topic`.
+    pub fn empty(topic: impl Into<String>) -> Self {
+        BusError::Empty {
+            topic: topic.into(),
         }
     }
 
-    /// Creates an error for when the topic has been disconnected.
+    /// Creates an error for when `topic` has been dropped.
+    pub fn disconnected(topic: impl Into<String>) -> Self {
+        BusError::Disconnected {
+            topic: topic.into(),
+        }
+    }
+
+    /// Creates an [`Empty`](BusError::Empty) error without a topic name.
+    #[deprecated(since = "0.3.0", note = "use `BusError::empty(topic)`")]
+    pub fn message_queue_empty() -> Self {
+        Self::empty(String::new())
+    }
+
+    /// Creates a [`Disconnected`](BusError::Disconnected) error without a
+    /// topic name.
+    #[deprecated(since = "0.3.0", note = "use `BusError::disconnected(topic)`")]
     pub fn topic_disconnected() -> Self {
-        BusError::TryRecv {
-            empty: false,
-            disconnected: true,
+        Self::disconnected(String::new())
+    }
+
+    /// Creates an error for a topic that does not exist.
+    pub fn topic_not_found(topic: impl Into<String>) -> Self {
+        BusError::TopicNotFound {
+            topic: topic.into(),
+        }
+    }
+
+    /// Creates an error for a malformed wildcard topic pattern.
+    pub fn invalid_pattern(pattern: impl Into<String>) -> Self {
+        BusError::InvalidPattern {
+            pattern: pattern.into(),
+        }
+    }
+
+    /// Creates an error for a topic registered with a different message type.
+    pub fn type_mismatch(
+        topic: impl Into<String>,
+        expected: &'static str,
+        found: &'static str,
+    ) -> Self {
+        BusError::TypeMismatch {
+            topic: topic.into(),
+            expected,
+            found,
+        }
+    }
+
+    /// Creates an error for when a topic with this name already exists.
+    pub fn topic_exists(topic: impl Into<String>) -> Self {
+        BusError::TopicExists {
+            topic: topic.into(),
+        }
+    }
+
+    /// Creates an error for a topic created with another config.
+    pub fn config_conflict(topic: impl Into<String>) -> Self {
+        BusError::ConfigConflict {
+            topic: topic.into(),
+        }
+    }
+
+    /// Creates an error for a derived topic without source topics.
+    pub fn no_sources(topic: impl Into<String>) -> Self {
+        BusError::NoSources {
+            topic: topic.into(),
+        }
+    }
+
+    /// Creates an error for a request that no responder answered.
+    pub fn no_responder(topic: impl Into<String>) -> Self {
+        BusError::NoResponder {
+            topic: topic.into(),
+        }
+    }
+
+    /// Creates an error for an operation that timed out.
+    pub fn timeout(topic: impl Into<String>) -> Self {
+        BusError::Timeout {
+            topic: topic.into(),
+            source: None,
+        }
+    }
+
+    /// Creates an error for a closed topic.
+    pub fn closed(topic: impl Into<String>) -> Self {
+        BusError::Closed {
+            topic: topic.into(),
+            source: None,
+        }
+    }
+
+    /// Creates an error for a message that expired before it was read.
+    pub fn stale(topic: impl Into<String>) -> Self {
+        BusError::Stale {
+            topic: topic.into(),
+        }
+    }
+
+    /// Creates an error for a conditional publish that lost a race.
+    pub fn conflict(topic: impl Into<String>, expected: u64, found: u64) -> Self {
+        BusError::Conflict {
+            topic: topic.into(),
+            expected,
+            found,
+        }
+    }
+
+    /// Creates an error for a topic that admits no more subscribers.
+    pub fn capacity_exceeded(topic: impl Into<String>, limit: usize) -> Self {
+        BusError::CapacityExceeded {
+            topic: topic.into(),
+            limit,
+        }
+    }
+
+    /// Creates an error for a topic the caller may not access.
+    pub fn permission_denied(topic: impl Into<String>) -> Self {
+        BusError::PermissionDenied {
+            topic: topic.into(),
+            source: None,
+        }
+    }
+
+    /// Attaches the underlying cause to a [`Timeout`](BusError::Timeout),
+    /// [`Closed`](BusError::Closed) or
+    /// [`PermissionDenied`](BusError::PermissionDenied) error, to be
+    /// returned by [`Error::source`](std::error::Error::source). Other
+    /// errors are returned unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::BusError;
+    /// use std::error::Error;
+    /// use std::io;
+    ///
+    /// let cause = io::Error::new(io::ErrorKind::PermissionDenied, "read-only segment");
+    /// let error = BusError::permission_denied("shm/prices").with_source(cause);
+    /// assert_eq!(error.source().unwrap().to_string(), "read-only segment");
+    /// ```
+    pub fn with_source(mut self, cause: impl std::error::Error + Send + Sync + 'static) -> Self {
+        if let BusError::Timeout { source, .. }
+        | BusError::Closed { source, .. }
+        | BusError::PermissionDenied { source, .. } = &mut self
+        {
+            *source = Some(ErrorSource::new(cause));
+        }
+        self
+    }
+
+    /// Returns the name of the topic the error is about, or `None` for
+    /// [`InvalidPattern`](BusError::InvalidPattern).
+    ///
+    /// Errors of subscribers to many topics name all of them, separated by
+    /// commas, and errors of pattern subscribers name the pattern.
+    pub fn topic(&self) -> Option<&str> {
+        match self {
+            BusError::InvalidPattern { .. } => None,
+            BusError::Empty { topic }
+            | BusError::Disconnected { topic }
+            | BusError::TopicNotFound { topic }
+            | BusError::TypeMismatch { topic, .. }
+            | BusError::TopicExists { topic }
+            | BusError::ConfigConflict { topic }
+            | BusError::NoSources { topic }
+            | BusError::NoResponder { topic }
+            | BusError::Timeout { topic, .. }
+            | BusError::Closed { topic, .. }
+            | BusError::Stale { topic }
+            | BusError::Conflict { topic, .. }
+            | BusError::CapacityExceeded { topic, .. }
+            | BusError::PermissionDenied { topic, .. } => Some(topic),
         }
     }
 
     /// Returns true if the error is due to disconnection.
     pub fn is_disconnected(&self) -> bool {
-        matches!(
-            self,
-            BusError::TryRecv {
-                disconnected: true,
-                ..
-            }
-        )
+        matches!(self, BusError::Disconnected { .. })
     }
 
     /// Returns true if the error is due to empty state.
     pub fn is_empty(&self) -> bool {
-        matches!(self, BusError::TryRecv { empty: true, .. })
+        matches!(self, BusError::Empty { .. })
+    }
+
+    /// Returns true if a topic that must exist does not.
+    pub fn is_topic_not_found(&self) -> bool {
+        matches!(self, BusError::TopicNotFound { .. })
+    }
+
+    /// Returns true if the error is due to a malformed topic pattern.
+    pub fn is_invalid_pattern(&self) -> bool {
+        matches!(self, BusError::InvalidPattern { .. })
+    }
+
+    /// Returns true if the error is due to a topic holding another message type.
+    pub fn is_type_mismatch(&self) -> bool {
+        matches!(self, BusError::TypeMismatch { .. })
+    }
+
+    /// Returns true if the error is due to a topic name already in use.
+    pub fn is_topic_exists(&self) -> bool {
+        matches!(self, BusError::TopicExists { .. })
+    }
+
+    /// Returns true if a topic was created with another config.
+    pub fn is_config_conflict(&self) -> bool {
+        matches!(self, BusError::ConfigConflict { .. })
+    }
+
+    /// Returns true if a derived topic was given no source topics.
+    pub fn is_no_sources(&self) -> bool {
+        matches!(self, BusError::NoSources { .. })
+    }
+
+    /// Returns true if a request had no responder to answer it.
+    pub fn is_no_responder(&self) -> bool {
+        matches!(self, BusError::NoResponder { .. })
+    }
+
+    /// Returns true if an operation timed out.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, BusError::Timeout { .. })
+    }
+
+    /// Returns true if a topic was closed.
+    pub fn is_closed(&self) -> bool {
+        matches!(self, BusError::Closed { .. })
+    }
+
+    /// Returns true if the latest message expired before it was read.
+    pub fn is_stale(&self) -> bool {
+        matches!(self, BusError::Stale { .. })
+    }
+
+    /// Returns true if a conditional publish found another version.
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, BusError::Conflict { .. })
+    }
+
+    /// Returns true if a topic's admission limit was reached.
+    pub fn is_capacity_exceeded(&self) -> bool {
+        matches!(self, BusError::CapacityExceeded { .. })
+    }
+
+    /// Returns true if access to a topic was refused.
+    pub fn is_permission_denied(&self) -> bool {
+        matches!(self, BusError::PermissionDenied { .. })
     }
 }
 
 impl std::fmt::Display for BusError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            BusError::TryRecv { empty: true, .. } => {
-                write!(f, "TryRecv error: No message available")
+            BusError::Empty { topic } => {
+                write!(f, "Receive error: No message available on topic '{topic}'")
+            }
+            BusError::Disconnected { topic } => {
+                write!(f, "Receive error: Topic '{topic}' disconnected")
+            }
+            BusError::TopicNotFound { topic } => {
+                write!(f, "Topic error: Topic '{topic}' does not exist")
+            }
+            BusError::InvalidPattern { pattern } => {
+                write!(f, "Pattern error: Invalid topic pattern '{pattern}'")
+            }
+            BusError::TypeMismatch {
+                topic,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Type error: Topic '{topic}' carries `{found}`, not `{expected}`"
+            ),
+            BusError::TopicExists { topic } => {
+                write!(f, "Topic error: Topic '{topic}' already exists")
+            }
+            BusError::ConfigConflict { topic } => {
+                write!(f, "Topic error: Topic '{topic}' exists with another config")
+            }
+            BusError::NoSources { topic } => {
+                write!(f, "Derive error: Topic '{topic}' needs at least one source")
+            }
+            BusError::NoResponder { topic } => {
+                write!(f, "Request error: No responder for topic '{topic}'")
+            }
+            BusError::Timeout { topic, .. } => {
+                write!(f, "Timeout error: Operation on topic '{topic}' timed out")
+            }
+            BusError::Closed { topic, .. } => {
+                write!(f, "Topic error: Topic '{topic}' is closed")
+            }
+            BusError::Stale { topic } => {
+                write!(
+                    f,
+                    "Receive error: Latest message on topic '{topic}' expired"
+                )
+            }
+            BusError::Conflict {
+                topic,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Publish error: Topic '{topic}' is at version {found}, not {expected}"
+            ),
+            BusError::CapacityExceeded { topic, limit } => write!(
+                f,
+                "Subscribe error: Topic '{topic}' admits at most {limit} subscribers"
+            ),
+            BusError::PermissionDenied { topic, .. } => {
+                write!(f, "Access error: Permission denied for topic '{topic}'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BusError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BusError::Timeout { source, .. }
+            | BusError::Closed { source, .. }
+            | BusError::PermissionDenied { source, .. } => {
+                source.as_ref().map(|source| source.get() as _)
             }
-            BusError::TryRecv {
-                disconnected: true, ..
-            } => write!(f, "TryRecv error: Topic disconnected"),
-            _ => write!(f, "Unknown bus error"),
+            _ => None,
         }
     }
 }
 
-impl std::error::Error for BusError {}
+/// The underlying cause of a [`BusError`].
+///
+/// Shared, so errors stay cheap to clone. Two sources are equal only if they
+/// are the same error.
+#[derive(Clone)]
+pub struct ErrorSource(Arc<dyn std::error::Error + Send + Sync + 'static>);
+
+impl ErrorSource {
+    /// Wraps `error`.
+    pub fn new(error: impl std::error::Error + Send + Sync + 'static) -> Self {
+        ErrorSource(Arc::new(error))
+    }
+
+    /// Returns the wrapped error.
+    #[inline]
+    pub fn get(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        &*self.0
+    }
+}
+
+impl std::fmt::Debug for ErrorSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl std::fmt::Display for ErrorSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl PartialEq for ErrorSource {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ErrorSource {}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::error::Error;
+    use std::io;
 
     #[test]
-    fn test_display_unreachable_case() {
-        // This should never happen in practice, but we need to test the _ arm
-        // We'll use unsafe to create an invalid variant for testing
-        let error = BusError::TryRecv {
-            empty: false,
-            disconnected: false,
-        };
-
-        let display_str = format!("{error}");
-        assert_eq!(display_str, "Unknown bus error");
+    fn test_source_chaining() {
+        let cause = io::Error::new(io::ErrorKind::BrokenPipe, "peer hung up");
+        let error = BusError::closed("feed").with_source(cause);
+        assert_eq!(error.source().unwrap().to_string(), "peer hung up");
+        assert_eq!(error.clone(), error);
+        assert_ne!(
+            error,
+            BusError::closed("feed").with_source(io::Error::from(io::ErrorKind::BrokenPipe))
+        );
+
+        let plain = BusError::empty("feed").with_source(io::Error::other("ignored"));
+        assert_eq!(plain, BusError::empty("feed"));
+        assert!(plain.source().is_none());
     }
 }
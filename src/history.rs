@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+use std::num::NonZeroUsize;
+
+/// How a topic retains published messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DeliveryMode {
+    /// Keep only the most recent message.
+    #[default]
+    Latest,
+    /// Keep the last `n` messages so subscribers can drain what they missed.
+    ///
+    /// A slow subscriber loses at most the oldest messages and is told how
+    /// many were dropped.
+    History(NonZeroUsize),
+}
+
+impl DeliveryMode {
+    /// Creates a history mode retaining the last `capacity` messages.
+    ///
+    /// Returns `DeliveryMode::Latest` if `capacity` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::DeliveryMode;
+    /// assert!(matches!(DeliveryMode::history(8), DeliveryMode::History(n) if n.get() == 8));
+    /// assert_eq!(DeliveryMode::history(0), DeliveryMode::Latest);
+    /// ```
+    #[inline]
+    pub fn history(capacity: usize) -> Self {
+        NonZeroUsize::new(capacity).map_or(DeliveryMode::Latest, DeliveryMode::History)
+    }
+
+    /// Returns the number of messages retained per topic.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        match self {
+            DeliveryMode::Latest => 1,
+            DeliveryMode::History(capacity) => capacity.get(),
+        }
+    }
+}
+
+/// A message tagged with its sequence number within a topic.
+///
+/// Sequence numbers are the topic version right after the message was
+/// published, so consecutive publishes have consecutive sequence numbers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sequenced<T> {
+    /// Position of the message in the topic's publish order.
+    pub seq: u64,
+    /// The published message.
+    pub message: T,
+}
+
+/// A message together with how it was delivered to a subscriber.
+///
+/// Returned by [`Sub::try_get_delivery`](crate::Sub::try_get_delivery) and
+/// [`Sub::wait_for_delivery`](crate::Sub::wait_for_delivery).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delivery<T> {
+    /// The delivered message.
+    pub message: T,
+    /// Topic version the message was published at.
+    pub version: u64,
+    /// Number of updates coalesced into this one since the previous read.
+    pub skipped: u64,
+}
+
+/// Messages drained from a topic since the previous read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Drained<T> {
+    /// Retained messages in publish order, oldest first.
+    pub messages: Vec<Sequenced<T>>,
+    /// Number of messages published since the previous read that were no
+    /// longer retained.
+    pub dropped: u64,
+}
+
+impl<T> Drained<T> {
+    /// Returns the most recent drained message.
+    #[inline]
+    pub fn latest(&self) -> Option<&Sequenced<T>> {
+        self.messages.last()
+    }
+}
+
+/// Ring buffer backing [`DeliveryMode::History`] topics.
+#[derive(Debug)]
+pub(crate) struct HistoryBuffer<T> {
+    messages: VecDeque<Sequenced<T>>,
+    capacity: usize,
+}
+
+impl<T> HistoryBuffer<T>
+where
+    T: Clone,
+{
+    pub(crate) fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            messages: VecDeque::with_capacity(capacity.get()),
+            capacity: capacity.get(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn push(&mut self, seq: u64, message: T) {
+        if self.messages.len() == self.capacity {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(Sequenced { seq, message });
+    }
+
+    /// Returns retained messages published after `last_seen` up to `latest`,
+    /// counting the ones that have already been evicted.
+    pub(crate) fn drain_since(&self, last_seen: u64, latest: u64) -> Drained<T> {
+        let messages: Vec<_> = self
+            .messages
+            .iter()
+            .filter(|entry| entry.seq > last_seen && entry.seq <= latest)
+            .cloned()
+            .collect();
+        let first = messages.first().map_or(latest, |entry| entry.seq - 1);
+        Drained {
+            dropped: first.saturating_sub(last_seen),
+            messages,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_buffer_evicts_oldest() {
+        let mut buffer = HistoryBuffer::new(NonZeroUsize::new(3).unwrap());
+        for seq in 1..=5 {
+            buffer.push(seq, seq * 10);
+        }
+
+        let drained = buffer.drain_since(0, 5);
+        assert_eq!(drained.dropped, 2);
+        let seqs: Vec<_> = drained.messages.iter().map(|entry| entry.seq).collect();
+        assert_eq!(seqs, [3, 4, 5]);
+
+        let drained = buffer.drain_since(4, 5);
+        assert_eq!(drained.dropped, 0);
+        assert_eq!(drained.latest().map(|entry| entry.message), Some(50));
+
+        let drained = buffer.drain_since(5, 5);
+        assert!(drained.messages.is_empty());
+        assert_eq!(drained.dropped, 0);
+    }
+}
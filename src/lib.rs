@@ -7,8 +7,27 @@
 //! - **Latest-only delivery**: Subscribers receive the most recent message only
 //! - **Zero-copy operations**: Optimized for `bytes::Bytes` and other types
 //! - **String-keyed topics**: Simple string-based topic naming
+//! - **Typed topics**: `TypedBus` mixes message types, keyed by `TopicKey<T>`
+//! - **Bounded history**: Opt-in per-topic ring buffers for "last N" delivery
+//! - **Multi-topic subscribers**: Wait on many topics at once with fair scheduling
+//! - **Wildcard subscriptions**: MQTT-style `+` and `#` patterns over `/`-separated names
+//! - **Snapshots**: Capture and restore every topic's latest value, serializable with `serde`
+//! - **Write-ahead log**: Opt-in durable topics with a segmented, compactable log
+//! - **Network bridge**: Mirror a bus to other processes over TCP with the `net` feature
+//! - **Same-host IPC**: Unix domain sockets for `Bytes` (`ipc`) and seqlocked shared memory (`shm`)
+//! - **WebSocket gateway**: Live, conflated JSON updates for browser dashboards with the `ws` feature
+//! - **Usage stats**: Per-topic and per-bus counters, exportable through `metrics` with the `metrics` feature
+//! - **Tracing**: Per-topic spans and lifecycle events with the `tracing` feature, compiled out otherwise
+//! - **Expiry**: Per-publish or per-topic time-to-live, after which values read as stale
+//! - **Publish metadata**: Wall-clock timestamps and user headers delivered in an `Envelope`
+//! - **Lifecycle events**: Be told when topics are created or removed and gain or lose subscribers
+//! - **Topic reaping**: Remove idle or unsubscribed topics by policy, keeping pinned or retained ones
+//! - **Per-topic configuration**: Build buses whose topics get delivery mode, TTL, dedup and limits by name pattern
+//! - **Request/reply**: Ask a responder on a named endpoint and await its correlated reply
+//! - **Derived topics**: `map`, `filter`, `distinct_until_changed` and `combine_latest` operators
 //! - **High performance**: Optimized data structures and memory layout
-//! - **Async/sync APIs**: Both `async` and non-blocking sync operations
+//! - **Async/sync APIs**: `async`, non-blocking and thread-blocking receive operations
+//! - **Runtime-neutral**: Works under tokio, smol or any other executor, or none at all
 //!
 //! ## Quick Start
 //!
@@ -54,13 +73,54 @@
 //! topic.publish(Bytes::from("zero-copy message"));
 //! ```
 
+#[cfg(any(feature = "net", feature = "ws"))]
+mod accept;
+mod blocking;
 pub mod bus;
+pub mod config;
+pub mod derived;
+pub mod envelope;
 pub mod error;
+pub mod history;
+pub mod lifecycle;
+pub mod multi;
+#[cfg(feature = "net")]
+pub mod net;
+pub mod pattern;
 pub mod prelude;
+pub mod reaper;
+pub mod reply;
+#[cfg(feature = "shm")]
+pub mod shm;
+mod slot;
+pub mod snapshot;
+pub mod stats;
+#[cfg(feature = "stream")]
+pub mod stream;
 pub mod sub;
+#[cfg(feature = "tokio")]
+pub mod timeout;
 pub mod topic;
+mod ttl;
+pub mod typed;
+pub mod wal;
+#[cfg(feature = "ws")]
+pub mod ws;
 
 pub use bus::Bus;
-pub use error::BusError;
+pub use config::{BusBuilder, BusHasher, TopicConfig};
+pub use envelope::{Envelope, Headers};
+pub use error::{BusError, ErrorSource};
+pub use history::{Delivery, DeliveryMode, Drained, Sequenced};
+pub use lifecycle::{LifecycleEvent, LifecycleEvents};
+pub use multi::MultiSub;
+pub use pattern::{PatternSub, TopicPattern};
+pub use reaper::{ReapPolicy, ReapReason, ReapReport, Reaped, Reaper};
+pub use reply::{Request, Responder};
+pub use snapshot::{BusSnapshot, TopicSnapshot};
+pub use stats::{BusStats, TopicStats};
+#[cfg(feature = "stream")]
+pub use stream::{SubStream, SubStreamMap};
 pub use sub::Sub;
 pub use topic::Topic;
+pub use typed::{TopicKey, TypedBus};
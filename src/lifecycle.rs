@@ -0,0 +1,224 @@
+//! Notifications about topics appearing, disappearing and gaining or losing
+//! their subscribers.
+//!
+//! [`Bus::lifecycle_events`] returns a [`LifecycleEvents`] receiver. Unlike
+//! topic messages, events are queued rather than replaced, so every
+//! receiver sees each event that happened after it was created, in order.
+//! A receiver that falls [`EVENT_CAPACITY`] events behind loses the oldest
+//! ones and counts them in [`dropped_count`](LifecycleEvents::dropped_count).
+//!
+//! ```rust
+//! use dropslot::prelude::*;
+//! use dropslot::LifecycleEvent;
+//!
+//! let bus = Bus::<u32>::new();
+//! let mut events = bus.lifecycle_events();
+//!
+//! let subscriber = bus.subscribe("market/eur");
+//! drop(subscriber);
+//! bus.remove_topic("market/eur");
+//!
+//! let seen: Vec<_> = std::iter::from_fn(|| events.try_get_event()).collect();
+//! assert_eq!(
+//!     seen,
+//!     [
+//!         LifecycleEvent::TopicCreated("market/eur".into()),
+//!         LifecycleEvent::FirstSubscriber("market/eur".into()),
+//!         LifecycleEvent::LastSubscriberGone("market/eur".into()),
+//!         LifecycleEvent::TopicRemoved("market/eur".into()),
+//!     ]
+//! );
+//! ```
+//!
+//! [`Bus::lifecycle_events`]: crate::Bus::lifecycle_events
+
+use crate::{bus::Bus, slot, topic::Topic};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Something that happened to a topic of a bus.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LifecycleEvent {
+    /// The topic was created.
+    TopicCreated(Arc<str>),
+    /// The topic was removed from the bus.
+    TopicRemoved(Arc<str>),
+    /// The topic got a subscriber after having none.
+    FirstSubscriber(Arc<str>),
+    /// The last subscriber of the topic was dropped.
+    LastSubscriberGone(Arc<str>),
+}
+
+impl LifecycleEvent {
+    /// Returns the name of the topic the event is about.
+    #[inline]
+    pub fn topic(&self) -> &str {
+        match self {
+            LifecycleEvent::TopicCreated(topic)
+            | LifecycleEvent::TopicRemoved(topic)
+            | LifecycleEvent::FirstSubscriber(topic)
+            | LifecycleEvent::LastSubscriberGone(topic) => topic,
+        }
+    }
+}
+
+/// Most events a [`LifecycleEvents`] receiver queues; older ones are dropped
+/// to make room.
+pub const EVENT_CAPACITY: usize = 1024;
+
+/// Events waiting for one receiver.
+#[derive(Debug, Default)]
+struct Pending {
+    events: VecDeque<LifecycleEvent>,
+    dropped: u64,
+}
+
+impl Pending {
+    fn push(&mut self, event: LifecycleEvent) {
+        if self.events.len() == EVENT_CAPACITY {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(event);
+    }
+}
+
+type Queue = Arc<Mutex<Pending>>;
+
+/// Delivers lifecycle events to every receiver of a bus.
+#[derive(Debug, Default)]
+pub(crate) struct Lifecycle {
+    next_id: AtomicU64,
+    len: AtomicUsize,
+    listeners: Mutex<Vec<(u64, Queue, slot::Sender<()>)>>,
+}
+
+impl Lifecycle {
+    /// Queues the event built by `event` for every receiver, if there are any.
+    pub(crate) fn emit(&self, event: impl FnOnce() -> LifecycleEvent) {
+        if self.len.load(Ordering::Acquire) == 0 {
+            return;
+        }
+        let event = event();
+        for (_, queue, doorbell) in lock(&self.listeners).iter() {
+            lock(queue).push(event.clone());
+            doorbell.send_modify(|_| {});
+        }
+    }
+
+    /// Attaches a topic that was just added to the bus and reports it.
+    pub(crate) fn on_topic_created<T: Clone>(self: &Arc<Self>, topic: &Topic<T>) {
+        topic.attach_lifecycle(self.clone());
+        self.emit(|| LifecycleEvent::TopicCreated(topic.name().into()));
+    }
+
+    /// Detaches a topic that was just taken off the bus and reports it.
+    /// Subscribers of the topic that are still around no longer report.
+    pub(crate) fn on_topic_removed<T: Clone>(&self, topic: &Topic<T>) {
+        topic.detach_lifecycle();
+        self.emit(|| LifecycleEvent::TopicRemoved(topic.name().into()));
+    }
+
+    fn remove(&self, id: u64) {
+        let mut listeners = lock(&self.listeners);
+        listeners.retain(|(listener, _, _)| *listener != id);
+        self.len.store(listeners.len(), Ordering::Release);
+    }
+}
+
+/// Receives the lifecycle events of a bus.
+///
+/// Created by [`Bus::lifecycle_events`]. Up to [`EVENT_CAPACITY`] events are
+/// queued until read.
+#[derive(Debug)]
+pub struct LifecycleEvents {
+    id: u64,
+    queue: Queue,
+    doorbell: slot::Receiver<()>,
+    lifecycle: Weak<Lifecycle>,
+}
+
+impl LifecycleEvents {
+    /// Takes the oldest queued event without waiting.
+    #[inline]
+    pub fn try_get_event(&mut self) -> Option<LifecycleEvent> {
+        lock(&self.queue).events.pop_front()
+    }
+
+    /// Returns how many events were dropped because the queue was full.
+    ///
+    /// Once it grows, the events read no longer tell the full story; use
+    /// [`Bus::topic_names`] to catch up.
+    #[inline]
+    pub fn dropped_count(&self) -> u64 {
+        lock(&self.queue).dropped
+    }
+
+    /// Waits for the next event.
+    ///
+    /// Returns `None` once the bus is dropped and every queued event was read.
+    pub async fn wait_for_event(&mut self) -> Option<LifecycleEvent> {
+        loop {
+            if let Some(event) = self.try_get_event() {
+                return Some(event);
+            }
+            if self.doorbell.changed().await.is_err() {
+                return self.try_get_event();
+            }
+        }
+    }
+}
+
+impl Drop for LifecycleEvents {
+    fn drop(&mut self) {
+        if let Some(lifecycle) = self.lifecycle.upgrade() {
+            lifecycle.remove(self.id);
+        }
+    }
+}
+
+impl<T> Bus<T>
+where
+    T: Clone,
+{
+    /// Returns a receiver of the topic lifecycle events of this bus.
+    ///
+    /// It sees every [`LifecycleEvent`] from now on: topics being created,
+    /// removed (including by [`cleanup_unused_topics`](Bus::cleanup_unused_topics)),
+    /// getting their first subscriber and losing their last one.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::{Bus, LifecycleEvent};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let bus = Bus::<f64>::new();
+    /// let mut events = bus.lifecycle_events();
+    ///
+    /// bus.publish("market/usd", 1.0);
+    /// let event = events.wait_for_event().await.unwrap();
+    /// assert_eq!(event, LifecycleEvent::TopicCreated("market/usd".into()));
+    /// # }
+    /// ```
+    pub fn lifecycle_events(&self) -> LifecycleEvents {
+        let lifecycle = self.lifecycle();
+        let id = lifecycle.next_id.fetch_add(1, Ordering::Relaxed);
+        let queue = Queue::default();
+        let doorbell = slot::Sender::new(());
+        let receiver = doorbell.subscribe();
+        let mut listeners = lock(&lifecycle.listeners);
+        listeners.push((id, queue.clone(), doorbell));
+        lifecycle.len.store(listeners.len(), Ordering::Release);
+        LifecycleEvents {
+            id,
+            queue,
+            doorbell: receiver,
+            lifecycle: Arc::downgrade(lifecycle),
+        }
+    }
+}
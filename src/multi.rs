@@ -0,0 +1,318 @@
+use crate::{
+    error::BusError,
+    sub::{PollSub, Sub},
+    topic::Topic,
+};
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+/// Topic name reported by the errors of a [`MultiSub`], which would otherwise
+/// have to join every name on each miss.
+const ERROR_LABEL: &str = "*";
+
+struct Entry<T> {
+    topic_name: Arc<str>,
+    inner: PollSub<T>,
+}
+
+/// Receives the latest messages from many topics at once.
+///
+/// Each topic keeps latest-only semantics on its own, and every delivery
+/// carries the name of the topic it came from. Topics are polled in
+/// round-robin order so a busy topic cannot starve the others, and can be
+/// added or removed at any time.
+///
+/// # Examples
+/// ```
+/// # use dropslot::Bus;
+/// # #[tokio::main]
+/// # async fn main() {
+/// let bus = Bus::<String>::new();
+/// let mut subscriber = bus.subscribe_many(&["orders", "trades"]);
+///
+/// bus.publish("trades", "BTC 42000".to_string());
+///
+/// let (topic, message) = subscriber.wait_for_message().await.unwrap();
+/// assert_eq!(&*topic, "trades");
+/// assert_eq!(message, "BTC 42000");
+/// # }
+/// ```
+pub struct MultiSub<T> {
+    entries: Vec<Entry<T>>,
+    cursor: usize,
+}
+
+impl<T> Default for MultiSub<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> MultiSub<T> {
+    /// Returns true if a topic with this name is part of the set.
+    #[inline]
+    pub fn contains(&self, topic_name: &str) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| &*entry.topic_name == topic_name)
+    }
+
+    /// Returns the names of all topics in the set.
+    #[inline]
+    pub fn topic_names(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|entry| entry.topic_name.to_string())
+            .collect()
+    }
+
+    /// Returns the number of topics in the set.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the set contains no topics.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<T> MultiSub<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    /// Creates an empty multi-topic subscriber.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            cursor: 0,
+        }
+    }
+
+    /// Adds a subscriber to the set.
+    ///
+    /// Returns `false` if this topic is already part of the set. A subscriber
+    /// for a different topic instance with the same name replaces the old one.
+    ///
+    /// The subscriber does not keep its topic alive; once the topic is
+    /// dropped it is removed from the set.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::{Bus, MultiSub};
+    /// let bus = Bus::<String>::new();
+    /// let mut subscriber = MultiSub::new();
+    ///
+    /// assert!(subscriber.add(bus.subscribe("orders")));
+    /// assert!(!subscriber.add(bus.subscribe("orders")));
+    /// assert_eq!(subscriber.len(), 1);
+    /// ```
+    pub fn add(&mut self, sub: Sub<T>) -> bool {
+        let existing = self
+            .entries
+            .iter()
+            .position(|entry| &*entry.topic_name == sub.topic_name());
+        if let Some(index) = existing {
+            if self.entries[index].inner.is_same_topic(sub.topic_ref()) {
+                return false;
+            }
+        }
+
+        let entry = Entry {
+            topic_name: sub.topic_name().into(),
+            inner: PollSub::new(sub),
+        };
+        match existing {
+            Some(index) => self.entries[index] = entry,
+            None => self.entries.push(entry),
+        }
+        true
+    }
+
+    /// Subscribes to `topic` and adds it to the set.
+    ///
+    /// Returns `false` if this topic is already part of the set.
+    #[inline]
+    pub fn add_topic(&mut self, topic: &Arc<Topic<T>>) -> bool {
+        self.add(topic.subscribe())
+    }
+
+    /// Removes a topic from the set.
+    ///
+    /// Returns `false` if no topic with this name was part of the set.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<String>::new();
+    /// let mut subscriber = bus.subscribe_many(&["orders", "trades"]);
+    ///
+    /// assert!(subscriber.remove("orders"));
+    /// assert!(!subscriber.remove("orders"));
+    /// assert!(!subscriber.contains("orders"));
+    /// assert!(subscriber.contains("trades"));
+    /// ```
+    pub fn remove(&mut self, topic_name: &str) -> bool {
+        match self
+            .entries
+            .iter()
+            .position(|entry| &*entry.topic_name == topic_name)
+        {
+            Some(index) => {
+                self.entries.remove(index);
+                if self.cursor > index {
+                    self.cursor -= 1;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Waits for the next message from any topic in the set.
+    ///
+    /// Returns the name of the topic that published along with its latest
+    /// message, or `None` once the set is empty.
+    pub async fn wait_for_message(&mut self) -> Option<(Arc<str>, T)> {
+        std::future::poll_fn(|cx| self.poll_recv_with(cx, |_, message| message.clone())).await
+    }
+
+    /// Waits for a message from any topic and applies a transformation.
+    ///
+    /// The function receives the topic name and a reference to the message,
+    /// avoiding a clone of the message.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let bus = Bus::<String>::new();
+    /// let mut subscriber = bus.subscribe_many(&["a", "b"]);
+    ///
+    /// bus.publish("b", "hello".to_string());
+    ///
+    /// let length = subscriber.wait_for_message_and_apply(|_, msg| msg.len()).await;
+    /// assert_eq!(length, Some(5));
+    /// # }
+    /// ```
+    pub async fn wait_for_message_and_apply<R>(
+        &mut self,
+        f: impl FnOnce(&str, &T) -> R,
+    ) -> Option<R> {
+        let mut f = Some(f);
+        std::future::poll_fn(|cx| {
+            self.poll_recv_with(cx, |topic_name, message| {
+                let f = f.take().expect("message handler is only invoked once");
+                f(topic_name, message)
+            })
+        })
+        .await
+        .map(|(_, result)| result)
+    }
+
+    /// Attempts to receive a message from any topic without blocking.
+    ///
+    /// Returns `Err(BusError::Empty { .. })` if no topic has published
+    /// since the last check, or `Err(BusError::Disconnected { .. })` once the
+    /// set is empty. Either error names the topic `"*"`; use
+    /// [`topic_names`](Self::topic_names) to see which topics were waited on.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<String>::new();
+    /// let mut subscriber = bus.subscribe_many(&["a", "b"]);
+    ///
+    /// assert!(subscriber.try_get_message().unwrap_err().is_empty());
+    ///
+    /// bus.publish("a", "hello".to_string());
+    /// let (topic, message) = subscriber.try_get_message().unwrap();
+    /// assert_eq!(&*topic, "a");
+    /// assert_eq!(message, "hello");
+    /// ```
+    pub fn try_get_message(&mut self) -> Result<(Arc<str>, T), BusError> {
+        let mut cx = Context::from_waker(Waker::noop());
+        match self.poll_recv_with(&mut cx, |_, message| message.clone()) {
+            Poll::Ready(Some(delivery)) => Ok(delivery),
+            Poll::Ready(None) => Err(BusError::disconnected(ERROR_LABEL)),
+            Poll::Pending => Err(BusError::empty(ERROR_LABEL)),
+        }
+    }
+
+    /// Polls every topic once, starting after the one that delivered last.
+    ///
+    /// Returns `Ready(None)` when the set is empty; topics that have been
+    /// dropped are removed along the way.
+    #[inline]
+    pub(crate) fn poll_recv_with<R>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut f: impl FnMut(&str, &T) -> R,
+    ) -> Poll<Option<(Arc<str>, R)>> {
+        self.poll_recv_versioned_with(cx, |topic_name, message, _| f(topic_name, message))
+    }
+
+    /// Like [`poll_recv_with`](Self::poll_recv_with), but also passes the
+    /// message's topic version to `f`.
+    pub(crate) fn poll_recv_versioned_with<R>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut f: impl FnMut(&str, &T, u64) -> R,
+    ) -> Poll<Option<(Arc<str>, R)>> {
+        let mut remaining = self.entries.len();
+        while remaining > 0 {
+            if self.cursor >= self.entries.len() {
+                self.cursor = 0;
+            }
+            let Entry { topic_name, inner } = &mut self.entries[self.cursor];
+            match inner.poll_versioned_with(cx, |message, version| f(topic_name, message, version))
+            {
+                Poll::Ready(Some(result)) => {
+                    let topic_name = topic_name.clone();
+                    self.cursor += 1;
+                    return Poll::Ready(Some((topic_name, result)));
+                }
+                Poll::Ready(None) => {
+                    self.entries.remove(self.cursor);
+                }
+                Poll::Pending => self.cursor += 1,
+            }
+            remaining -= 1;
+        }
+
+        if self.entries.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for MultiSub<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MultiSub")
+            .field("topics", &self.entries.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<T> futures_core::Stream for MultiSub<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    type Item = (Arc<str>, T);
+
+    fn poll_next(self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut()
+            .poll_recv_with(cx, |_, message| message.clone())
+    }
+}
@@ -0,0 +1,972 @@
+//! Mirroring a bus to other processes over TCP.
+//!
+//! Enabled with the `net` feature. A [`BusServer`] exposes a [`Bus`] on a TCP
+//! listener, and a [`RemoteBus`] connects to it and offers `topic`,
+//! `subscribe` and `publish` like a local bus. Messages are serialized as
+//! JSON in length-prefixed frames.
+//!
+//! For processes on the same host, the `ipc` feature adds
+//! [`BusServer::bind_unix`] and [`RemoteBus::connect_unix`], which carry
+//! `Bytes` payloads unencoded over a Unix domain socket.
+//!
+//! The client keeps a local mirror of every subscribed topic, so subscribers
+//! are ordinary [`Sub`]s with the server's versions. When the connection
+//! drops, the client reconnects in the background and immediately receives
+//! the latest value of every topic it subscribed to.
+//!
+//! ```rust
+//! use dropslot::prelude::*;
+//! use dropslot::net::{BusServer, RemoteBus};
+//! use std::sync::Arc;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> std::io::Result<()> {
+//! let bus = Arc::new(Bus::<String>::new());
+//! bus.publish("status", "online".to_string());
+//!
+//! let server = BusServer::bind("127.0.0.1:0", bus.clone()).await?;
+//! let addr = server.local_addr()?;
+//! tokio::spawn(server.serve());
+//!
+//! let remote = RemoteBus::<String>::connect(addr).await?;
+//! let mut status = remote.subscribe("status");
+//! assert_eq!(status.wait_for_message().await, Some("online".to_string()));
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{accept, bus::Bus, sub::Sub};
+#[cfg(all(unix, feature = "ipc"))]
+use bytes::{Buf, BufMut, Bytes};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, RandomState};
+use std::io;
+use std::net::SocketAddr;
+#[cfg(all(unix, feature = "ipc"))]
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+#[cfg(all(unix, feature = "ipc"))]
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::mpsc;
+use tokio::task::{AbortHandle, JoinHandle, JoinSet};
+
+/// Largest frame either side accepts, to bound memory use on bad input.
+const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// Frames queued per connection before subscriptions wait, conflating
+/// updates for slow peers.
+const OUTBOUND_CAPACITY: usize = 64;
+
+/// Delay between reconnection attempts.
+const RECONNECT_DELAY: Duration = Duration::from_millis(100);
+
+/// A frame sent from a [`RemoteBus`] to a [`BusServer`].
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Request<T> {
+    Subscribe { topic: String },
+    Unsubscribe { topic: String },
+    Publish { topic: String, value: T },
+}
+
+/// A frame sent from a [`BusServer`] to a [`RemoteBus`].
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Event<T> {
+    /// First frame of every connection, identifying the server instance.
+    Hello { server_id: u64 },
+    Update {
+        topic: String,
+        version: u64,
+        value: T,
+    },
+}
+
+/// Encodes protocol frames for one message type.
+trait Wire<T>: Send + Sync {
+    fn encode_request(&self, request: &Request<T>) -> io::Result<Vec<u8>>;
+    fn decode_request(&self, frame: Vec<u8>) -> io::Result<Request<T>>;
+    fn encode_event(&self, event: &Event<T>) -> io::Result<Vec<u8>>;
+    fn decode_event(&self, frame: Vec<u8>) -> io::Result<Event<T>>;
+}
+
+/// JSON frames, used over TCP.
+struct Json;
+
+impl<T: Serialize + DeserializeOwned> Wire<T> for Json {
+    fn encode_request(&self, request: &Request<T>) -> io::Result<Vec<u8>> {
+        Ok(serde_json::to_vec(request)?)
+    }
+
+    fn decode_request(&self, frame: Vec<u8>) -> io::Result<Request<T>> {
+        Ok(serde_json::from_slice(&frame)?)
+    }
+
+    fn encode_event(&self, event: &Event<T>) -> io::Result<Vec<u8>> {
+        Ok(serde_json::to_vec(event)?)
+    }
+
+    fn decode_event(&self, frame: Vec<u8>) -> io::Result<Event<T>> {
+        Ok(serde_json::from_slice(&frame)?)
+    }
+}
+
+/// Binary frames for `Bytes` messages, used over Unix domain sockets.
+///
+/// Requests are a tag byte, a `u32` topic length, the topic and, for
+/// publishes, the raw payload. Updates are a tag byte, the `u64` version,
+/// the topic in the same form and the payload; hellos a tag and the id.
+#[cfg(all(unix, feature = "ipc"))]
+struct Binary;
+
+#[cfg(all(unix, feature = "ipc"))]
+impl Binary {
+    const SUBSCRIBE: u8 = 0;
+    const UNSUBSCRIBE: u8 = 1;
+    const PUBLISH: u8 = 2;
+    const HELLO: u8 = 0;
+    const UPDATE: u8 = 1;
+
+    fn put_topic(buf: &mut Vec<u8>, topic: &str) -> io::Result<()> {
+        let len = u32::try_from(topic.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "topic name too long"))?;
+        buf.put_u32(len);
+        buf.put_slice(topic.as_bytes());
+        Ok(())
+    }
+
+    fn get_topic(frame: &mut Bytes) -> io::Result<String> {
+        let len = Self::get_u32(frame)? as usize;
+        if frame.remaining() < len {
+            return Err(Self::truncated());
+        }
+        String::from_utf8(frame.split_to(len).to_vec())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    fn get_u8(frame: &mut Bytes) -> io::Result<u8> {
+        frame.try_get_u8().map_err(|_| Self::truncated())
+    }
+
+    fn get_u32(frame: &mut Bytes) -> io::Result<u32> {
+        frame.try_get_u32().map_err(|_| Self::truncated())
+    }
+
+    fn get_u64(frame: &mut Bytes) -> io::Result<u64> {
+        frame.try_get_u64().map_err(|_| Self::truncated())
+    }
+
+    fn truncated() -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, "truncated frame")
+    }
+
+    fn unknown_tag(tag: u8) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unknown frame tag {tag}"),
+        )
+    }
+}
+
+#[cfg(all(unix, feature = "ipc"))]
+impl Wire<Bytes> for Binary {
+    fn encode_request(&self, request: &Request<Bytes>) -> io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        match request {
+            Request::Subscribe { topic } => {
+                buf.put_u8(Self::SUBSCRIBE);
+                Self::put_topic(&mut buf, topic)?;
+            }
+            Request::Unsubscribe { topic } => {
+                buf.put_u8(Self::UNSUBSCRIBE);
+                Self::put_topic(&mut buf, topic)?;
+            }
+            Request::Publish { topic, value } => {
+                buf.reserve(5 + topic.len() + value.len());
+                buf.put_u8(Self::PUBLISH);
+                Self::put_topic(&mut buf, topic)?;
+                buf.put_slice(value);
+            }
+        }
+        Ok(buf)
+    }
+
+    fn decode_request(&self, frame: Vec<u8>) -> io::Result<Request<Bytes>> {
+        let mut frame = Bytes::from(frame);
+        let tag = Self::get_u8(&mut frame)?;
+        let topic = Self::get_topic(&mut frame)?;
+        match tag {
+            Self::SUBSCRIBE => Ok(Request::Subscribe { topic }),
+            Self::UNSUBSCRIBE => Ok(Request::Unsubscribe { topic }),
+            Self::PUBLISH => Ok(Request::Publish {
+                topic,
+                value: frame,
+            }),
+            tag => Err(Self::unknown_tag(tag)),
+        }
+    }
+
+    fn encode_event(&self, event: &Event<Bytes>) -> io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        match event {
+            Event::Hello { server_id } => {
+                buf.put_u8(Self::HELLO);
+                buf.put_u64(*server_id);
+            }
+            Event::Update {
+                topic,
+                version,
+                value,
+            } => {
+                buf.reserve(13 + topic.len() + value.len());
+                buf.put_u8(Self::UPDATE);
+                buf.put_u64(*version);
+                Self::put_topic(&mut buf, topic)?;
+                buf.put_slice(value);
+            }
+        }
+        Ok(buf)
+    }
+
+    fn decode_event(&self, frame: Vec<u8>) -> io::Result<Event<Bytes>> {
+        let mut frame = Bytes::from(frame);
+        match Self::get_u8(&mut frame)? {
+            Self::HELLO => Ok(Event::Hello {
+                server_id: Self::get_u64(&mut frame)?,
+            }),
+            Self::UPDATE => {
+                let version = Self::get_u64(&mut frame)?;
+                let topic = Self::get_topic(&mut frame)?;
+                Ok(Event::Update {
+                    topic,
+                    version,
+                    value: frame,
+                })
+            }
+            tag => Err(Self::unknown_tag(tag)),
+        }
+    }
+}
+
+/// A byte stream to a peer, over any transport.
+trait Connection: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<S: AsyncRead + AsyncWrite + Send + Unpin> Connection for S {}
+
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(all(unix, feature = "ipc"))]
+    Unix(UnixListener, PathBuf),
+}
+
+impl Listener {
+    async fn accept(&self) -> io::Result<Box<dyn Connection>> {
+        match self {
+            Self::Tcp(listener) => {
+                let (stream, _) = listener.accept().await?;
+                stream.set_nodelay(true)?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(all(unix, feature = "ipc"))]
+            Self::Unix(listener, _) => Ok(Box::new(listener.accept().await?.0)),
+        }
+    }
+}
+
+#[cfg(all(unix, feature = "ipc"))]
+impl Drop for Listener {
+    fn drop(&mut self) {
+        if let Self::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Endpoint {
+    Tcp(SocketAddr),
+    #[cfg(all(unix, feature = "ipc"))]
+    Unix(PathBuf),
+}
+
+impl Endpoint {
+    async fn connect(&self) -> io::Result<Box<dyn Connection>> {
+        match self {
+            Self::Tcp(addr) => {
+                let stream = TcpStream::connect(addr).await?;
+                stream.set_nodelay(true)?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(all(unix, feature = "ipc"))]
+            Self::Unix(path) => Ok(Box::new(UnixStream::connect(path).await?)),
+        }
+    }
+}
+
+/// Serves a [`Bus`] to [`RemoteBus`] clients over TCP or, for `Bytes`
+/// messages, a Unix domain socket.
+pub struct BusServer<T> {
+    bus: Arc<Bus<T>>,
+    listener: Listener,
+    wire: Arc<dyn Wire<T>>,
+    id: u64,
+}
+
+impl<T> BusServer<T>
+where
+    T: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    /// Binds a server for `bus` to a TCP address. Messages are sent as JSON.
+    ///
+    /// # Errors
+    /// Returns any error from binding the listener.
+    pub async fn bind(addr: impl ToSocketAddrs, bus: Arc<Bus<T>>) -> io::Result<Self> {
+        let listener = Listener::Tcp(TcpListener::bind(addr).await?);
+        Ok(Self::new(bus, listener, Arc::new(Json)))
+    }
+}
+
+#[cfg(all(unix, feature = "ipc"))]
+impl BusServer<Bytes> {
+    /// Binds a server for `bus` to a Unix domain socket at `path`.
+    ///
+    /// Payloads are sent as raw bytes, without any encoding, which keeps
+    /// latency low for processes on the same host. A stale socket file left
+    /// at `path` by a server that is no longer running is replaced, and the
+    /// file is removed when the server is dropped. Must be called within a
+    /// tokio runtime.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`AddrInUse`](io::ErrorKind::AddrInUse) if
+    /// a server is still listening at `path`, or any error from binding the
+    /// socket.
+    pub async fn bind_unix(path: impl AsRef<Path>, bus: Arc<Bus<Bytes>>) -> io::Result<Self> {
+        use std::os::unix::fs::FileTypeExt;
+
+        let path = path.as_ref();
+        if std::fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+            // Only a socket nobody accepts on is stale.
+            match UnixStream::connect(path).await {
+                Ok(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AddrInUse,
+                        format!("a server is listening at {}", path.display()),
+                    ));
+                }
+                Err(error) if error.kind() == io::ErrorKind::ConnectionRefused => {
+                    std::fs::remove_file(path)?;
+                }
+                Err(_) => {}
+            }
+        }
+        let listener = Listener::Unix(UnixListener::bind(path)?, path.to_path_buf());
+        Ok(Self::new(bus, listener, Arc::new(Binary)))
+    }
+}
+
+impl<T> BusServer<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    fn new(bus: Arc<Bus<T>>, listener: Listener, wire: Arc<dyn Wire<T>>) -> Self {
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Self {
+            bus,
+            listener,
+            wire,
+            id: RandomState::new().hash_one((std::process::id(), since_epoch)),
+        }
+    }
+
+    /// Returns the TCP address the server is listening on.
+    ///
+    /// # Errors
+    /// Returns any error from the underlying socket, or
+    /// `io::ErrorKind::Unsupported` for a Unix domain socket server.
+    #[inline]
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        match &self.listener {
+            Listener::Tcp(listener) => listener.local_addr(),
+            #[cfg(all(unix, feature = "ipc"))]
+            Listener::Unix(..) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "not a TCP server",
+            )),
+        }
+    }
+
+    /// Returns the served bus.
+    #[inline]
+    pub fn bus(&self) -> &Arc<Bus<T>> {
+        &self.bus
+    }
+
+    /// Accepts and serves clients until the returned future is dropped.
+    ///
+    /// Every client is handled on its own task. Dropping the returned future
+    /// closes all client connections.
+    ///
+    /// Accept errors don't stop the server: errors of a single connection
+    /// are skipped, and others, such as running out of file descriptors,
+    /// pause accepting briefly. With the `tracing` feature they are logged.
+    pub async fn serve(self) {
+        let mut connections = JoinSet::new();
+        loop {
+            let stream = match self.listener.accept().await {
+                Ok(stream) => stream,
+                Err(error) => {
+                    accept::recover(error).await;
+                    continue;
+                }
+            };
+            while connections.try_join_next().is_some() {}
+            connections.spawn(serve_connection(
+                self.bus.clone(),
+                self.wire.clone(),
+                self.id,
+                stream,
+            ));
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for BusServer<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("BusServer");
+        match &self.listener {
+            Listener::Tcp(listener) => debug.field("local_addr", &listener.local_addr().ok()),
+            #[cfg(all(unix, feature = "ipc"))]
+            Listener::Unix(_, path) => debug.field("path", path),
+        };
+        debug.finish_non_exhaustive()
+    }
+}
+
+async fn serve_connection<T>(
+    bus: Arc<Bus<T>>,
+    wire: Arc<dyn Wire<T>>,
+    server_id: u64,
+    stream: Box<dyn Connection>,
+) where
+    T: Clone + Send + Sync + 'static,
+{
+    let (mut reader, writer) = tokio::io::split(stream);
+    let (outbound, frames) = mpsc::channel(OUTBOUND_CAPACITY);
+    let Ok(hello) = wire.encode_event(&Event::Hello { server_id }) else {
+        return;
+    };
+    let _ = outbound.try_send(hello);
+    let mut tasks = JoinSet::new();
+    tasks.spawn(write_frames(writer, frames));
+    let mut subscriptions: HashMap<String, AbortHandle> = HashMap::new();
+
+    // Any read or decode error ends the connection; dropping `tasks` then
+    // stops the writer and every subscription.
+    while let Ok(Some(frame)) = read_frame(&mut reader).await {
+        let Ok(request) = wire.decode_request(frame) else {
+            break;
+        };
+        match request {
+            Request::Subscribe { topic } => {
+                if let Entry::Vacant(entry) = subscriptions.entry(topic) {
+                    let sub = bus.subscribe(entry.key());
+                    let forward = forward_updates(sub, wire.clone(), outbound.clone());
+                    entry.insert(tasks.spawn(forward));
+                }
+            }
+            Request::Unsubscribe { topic } => {
+                if let Some(handle) = subscriptions.remove(&topic) {
+                    handle.abort();
+                }
+            }
+            Request::Publish { topic, value } => bus.publish(&topic, value),
+        }
+    }
+}
+
+/// Sends the current value of a topic and then every update, letting the
+/// subscriber coalesce updates while the connection is busy. Updates that
+/// fail to encode are skipped and, with the `tracing` feature, logged.
+async fn forward_updates<T: Clone>(
+    mut sub: Sub<T>,
+    wire: Arc<dyn Wire<T>>,
+    outbound: mpsc::Sender<Vec<u8>>,
+) {
+    sub.mark_unseen();
+    while let Some(delivery) = sub.wait_for_delivery().await {
+        let event = Event::Update {
+            topic: sub.topic_name().to_string(),
+            version: delivery.version,
+            value: delivery.message,
+        };
+        let frame = match wire.encode_event(&event) {
+            Ok(frame) => frame,
+            Err(_error) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(topic = sub.topic_name(), error = %_error, "update not encoded");
+                continue;
+            }
+        };
+        if outbound.send(frame).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// A request waiting for the writer of a [`RemoteBus`] connection.
+///
+/// Subscription changes happen at most once per call, so the queue only
+/// grows with publishes, and those are conflated: a queued publish carries
+/// only the topic name, and the writer sends the latest pending value.
+#[derive(Debug)]
+enum Queued {
+    Subscribe(String),
+    Unsubscribe(String),
+    Publish(String),
+}
+
+struct ClientState<T> {
+    subscriptions: HashSet<String>,
+    /// Requests for the current connection, if any.
+    outbound: Option<mpsc::UnboundedSender<Queued>>,
+    /// Latest unsent publish per topic.
+    pending: HashMap<String, T>,
+}
+
+struct Shared<T> {
+    mirror: Bus<T>,
+    wire: Arc<dyn Wire<T>>,
+    state: Mutex<ClientState<T>>,
+}
+
+impl<T> Shared<T> {
+    #[inline]
+    fn state(&self) -> MutexGuard<'_, ClientState<T>> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A client that mirrors topics of a [`BusServer`].
+///
+/// Dropping the client closes its connection; subscribers it handed out
+/// then stop receiving updates.
+pub struct RemoteBus<T> {
+    shared: Arc<Shared<T>>,
+    task: JoinHandle<()>,
+}
+
+impl<T> RemoteBus<T>
+where
+    T: Clone + Send + Sync + Serialize + DeserializeOwned + 'static,
+{
+    /// Connects to a [`BusServer`] bound with [`BusServer::bind`].
+    ///
+    /// The first connection must succeed. Afterwards the client reconnects
+    /// in the background whenever the connection drops, and resubscribes to
+    /// all its topics. Must be called within a tokio runtime.
+    ///
+    /// # Errors
+    /// Returns any error from the initial connection.
+    pub async fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let addr = tokio::net::lookup_host(addr).await?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "no address to connect to")
+        })?;
+        Self::connect_to(Endpoint::Tcp(addr), Arc::new(Json)).await
+    }
+}
+
+#[cfg(all(unix, feature = "ipc"))]
+impl RemoteBus<Bytes> {
+    /// Connects to a [`BusServer`] bound with [`BusServer::bind_unix`].
+    ///
+    /// Behaves like [`RemoteBus::connect`], including reconnection.
+    ///
+    /// # Errors
+    /// Returns any error from the initial connection.
+    pub async fn connect_unix(path: impl AsRef<Path>) -> io::Result<Self> {
+        let endpoint = Endpoint::Unix(path.as_ref().to_path_buf());
+        Self::connect_to(endpoint, Arc::new(Binary)).await
+    }
+}
+
+impl<T> RemoteBus<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    async fn connect_to(endpoint: Endpoint, wire: Arc<dyn Wire<T>>) -> io::Result<Self> {
+        let stream = endpoint.connect().await?;
+        let shared = Arc::new(Shared {
+            mirror: Bus::new(),
+            wire,
+            state: Mutex::new(ClientState {
+                subscriptions: HashSet::new(),
+                outbound: None,
+                pending: HashMap::new(),
+            }),
+        });
+        let task = tokio::spawn(run_client(shared.clone(), endpoint, stream));
+        Ok(Self { shared, task })
+    }
+
+    /// Returns a handle to a remote topic.
+    #[inline]
+    pub fn topic(&self, name: &str) -> RemoteTopic<'_, T> {
+        RemoteTopic {
+            bus: self,
+            name: name.into(),
+        }
+    }
+
+    /// Publishes a message to a topic on the server.
+    ///
+    /// Subscribers see the message once the server sends it back. While the
+    /// connection is busy, a newer publish to the same topic replaces one
+    /// that hasn't been sent yet, so a slow connection can't make messages
+    /// pile up.
+    ///
+    /// # Errors
+    /// Returns `io::ErrorKind::NotConnected` while the client is reconnecting.
+    pub fn publish(&self, topic_name: &str, message: T) -> io::Result<()> {
+        let mut state = self.shared.state();
+        let ClientState {
+            outbound, pending, ..
+        } = &mut *state;
+        let outbound = outbound
+            .as_ref()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotConnected))?;
+        if pending.insert(topic_name.to_string(), message).is_none() {
+            outbound
+                .send(Queued::Publish(topic_name.to_string()))
+                .map_err(|_| io::Error::from(io::ErrorKind::NotConnected))?;
+        }
+        Ok(())
+    }
+
+    /// Subscribes to a topic on the server.
+    ///
+    /// The returned subscriber reads from the local mirror of the topic,
+    /// which receives the server's current value right away and every update
+    /// after it, with the server's versions. Subscribing again to the same
+    /// topic just adds another local subscriber.
+    pub fn subscribe(&self, topic_name: &str) -> Sub<T> {
+        let sub = self.shared.mirror.subscribe(topic_name);
+        let mut state = self.shared.state();
+        if state.subscriptions.insert(topic_name.to_string()) {
+            if let Some(outbound) = &state.outbound {
+                let _ = outbound.send(Queued::Subscribe(topic_name.to_string()));
+            }
+        }
+        sub
+    }
+
+    /// Stops receiving updates for a topic.
+    ///
+    /// Returns `false` if the topic was not subscribed. Existing subscribers
+    /// keep the last value they saw.
+    pub fn unsubscribe(&self, topic_name: &str) -> bool {
+        let mut state = self.shared.state();
+        if !state.subscriptions.remove(topic_name) {
+            return false;
+        }
+        if let Some(outbound) = &state.outbound {
+            let _ = outbound.send(Queued::Unsubscribe(topic_name.to_string()));
+        }
+        true
+    }
+
+    /// Returns the names of all subscribed topics.
+    pub fn subscribed_topics(&self) -> Vec<String> {
+        self.shared.state().subscriptions.iter().cloned().collect()
+    }
+
+    /// Returns true if the client is currently connected.
+    #[inline]
+    pub fn is_connected(&self) -> bool {
+        self.shared.state().outbound.is_some()
+    }
+}
+
+impl<T> std::fmt::Debug for RemoteBus<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.shared.state();
+        f.debug_struct("RemoteBus")
+            .field("connected", &state.outbound.is_some())
+            .field("subscriptions", &state.subscriptions)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for RemoteBus<T> {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// A topic on a [`BusServer`], created by [`RemoteBus::topic`].
+#[derive(Debug)]
+pub struct RemoteTopic<'a, T> {
+    bus: &'a RemoteBus<T>,
+    name: Box<str>,
+}
+
+impl<T> RemoteTopic<'_, T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    /// Returns the topic name.
+    #[inline(always)]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Publishes a message to this topic. See [`RemoteBus::publish`].
+    ///
+    /// # Errors
+    /// Returns `io::ErrorKind::NotConnected` while the client is reconnecting.
+    #[inline]
+    pub fn publish(&self, message: T) -> io::Result<()> {
+        self.bus.publish(&self.name, message)
+    }
+
+    /// Subscribes to this topic. See [`RemoteBus::subscribe`].
+    #[inline]
+    pub fn subscribe(&self) -> Sub<T> {
+        self.bus.subscribe(&self.name)
+    }
+}
+
+async fn run_client<T>(shared: Arc<Shared<T>>, endpoint: Endpoint, mut stream: Box<dyn Connection>)
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let mut server_id = None;
+    loop {
+        let (mut reader, writer) = tokio::io::split(stream);
+        let (outbound, requests) = mpsc::unbounded_channel();
+        {
+            let mut state = shared.state();
+            for topic in &state.subscriptions {
+                let _ = outbound.send(Queued::Subscribe(topic.clone()));
+            }
+            state.outbound = Some(outbound);
+        }
+
+        let writer = tokio::spawn(write_requests(writer, shared.clone(), requests));
+        // Topics whose mirrored versions came from a previous server instance.
+        let mut stale = HashSet::new();
+        while let Ok(Some(frame)) = read_frame(&mut reader).await {
+            match shared.wire.decode_event(frame) {
+                Ok(Event::Hello { server_id: id }) => {
+                    if server_id.is_some_and(|previous| previous != id) {
+                        stale = shared.state().subscriptions.clone();
+                    }
+                    server_id = Some(id);
+                }
+                Ok(Event::Update {
+                    topic,
+                    version,
+                    value,
+                }) => {
+                    let stale = stale.remove(&topic);
+                    apply_update(&shared.mirror, &topic, version, value, stale);
+                }
+                Err(_) => break,
+            }
+        }
+        {
+            let mut state = shared.state();
+            state.outbound = None;
+            state.pending.clear();
+        }
+        writer.abort();
+
+        stream = loop {
+            tokio::time::sleep(RECONNECT_DELAY).await;
+            if let Ok(stream) = endpoint.connect().await {
+                break stream;
+            }
+        };
+    }
+}
+
+/// Stores a server update in the mirror, keeping the server's version.
+///
+/// A version the mirror already holds is skipped unless it is `stale`, that
+/// is, it came from a server that has since restarted. Versions that can't be
+/// kept because they went backwards are published as new local versions, so
+/// existing subscribers still see them.
+fn apply_update<T: Clone>(mirror: &Bus<T>, topic_name: &str, version: u64, value: T, stale: bool) {
+    let topic = mirror.topic(topic_name);
+    match version.cmp(&topic.get_current_version()) {
+        Ordering::Greater => {
+            topic.restore(value, version);
+        }
+        Ordering::Equal if !stale => {}
+        _ => topic.publish(value),
+    }
+}
+
+async fn write_requests<T, W>(
+    mut writer: W,
+    shared: Arc<Shared<T>>,
+    mut requests: mpsc::UnboundedReceiver<Queued>,
+) where
+    W: AsyncWrite + Unpin,
+{
+    while let Some(queued) = requests.recv().await {
+        let request = match queued {
+            Queued::Subscribe(topic) => Request::Subscribe { topic },
+            Queued::Unsubscribe(topic) => Request::Unsubscribe { topic },
+            Queued::Publish(topic) => match shared.state().pending.remove(&topic) {
+                Some(value) => Request::Publish { topic, value },
+                None => continue,
+            },
+        };
+        let Ok(frame) = shared.wire.encode_request(&request) else {
+            continue;
+        };
+        if write_frame(&mut writer, &frame).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn write_frames<W>(mut writer: W, mut frames: mpsc::Receiver<Vec<u8>>)
+where
+    W: AsyncWrite + Unpin,
+{
+    while let Some(frame) = frames.recv().await {
+        if write_frame(&mut writer, &frame).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Writes a frame as a big-endian `u32` length followed by the payload.
+async fn write_frame<W>(writer: &mut W, payload: &[u8]) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    if payload.len() > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "frame too large",
+        ));
+    }
+    writer.write_u32(payload.len() as u32).await?;
+    writer.write_all(payload).await?;
+    writer.flush().await
+}
+
+/// Reads one frame, returning `None` if the peer closed the connection
+/// between frames.
+async fn read_frame<R>(reader: &mut R) -> io::Result<Option<Vec<u8>>>
+where
+    R: AsyncRead + Unpin,
+{
+    let len = match reader.read_u32().await {
+        Ok(len) => len as usize,
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error),
+    };
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "frame too large",
+        ));
+    }
+    let mut payload = vec![0; len];
+    reader.read_exact(&mut payload).await?;
+    Ok(Some(payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_frame_round_trip_and_limits() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+        write_frame(&mut client, b"hello").await.unwrap();
+        write_frame(&mut client, b"").await.unwrap();
+        client.write_u32(MAX_FRAME_LEN as u32 + 1).await.unwrap();
+        drop(client);
+
+        assert_eq!(
+            read_frame(&mut server).await.unwrap(),
+            Some(b"hello".to_vec())
+        );
+        assert_eq!(read_frame(&mut server).await.unwrap(), Some(Vec::new()));
+        let error = read_frame(&mut server).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(read_frame(&mut server).await.unwrap(), None);
+    }
+
+    #[cfg(all(unix, feature = "ipc"))]
+    #[test]
+    fn test_binary_wire_round_trip() {
+        let frame = Binary
+            .encode_request(&Request::Publish {
+                topic: "raw".to_string(),
+                value: Bytes::from_static(b"\x00\x01payload"),
+            })
+            .unwrap();
+        let Request::Publish { topic, value } = Binary.decode_request(frame).unwrap() else {
+            panic!("expected a publish");
+        };
+        assert_eq!(
+            (topic.as_str(), &value[..]),
+            ("raw", &b"\x00\x01payload"[..])
+        );
+
+        let frame = Binary
+            .encode_event(&Event::Update {
+                topic: "raw".to_string(),
+                version: 7,
+                value: Bytes::new(),
+            })
+            .unwrap();
+        let truncated = frame[..frame.len() - 2].to_vec();
+        let Event::Update {
+            topic,
+            version,
+            value,
+        } = Binary.decode_event(frame).unwrap()
+        else {
+            panic!("expected an update");
+        };
+        assert_eq!((topic.as_str(), version, value.len()), ("raw", 7, 0));
+
+        let error = Binary.decode_event(truncated).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(Binary.decode_request(vec![9, 0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_apply_update_follows_server_versions() {
+        let mirror = Bus::<u32>::new();
+        let sub = mirror.subscribe("t");
+
+        apply_update(&mirror, "t", 5, 50, false);
+        assert_eq!(mirror.topic("t").get_current_version(), 5);
+        apply_update(&mirror, "t", 5, 99, false);
+        assert_eq!(sub.get_latest(), Some(50));
+
+        // The server restarted and counts from scratch again.
+        apply_update(&mirror, "t", 1, 10, true);
+        assert_eq!(mirror.topic("t").get_current_version(), 6);
+        assert_eq!(sub.get_latest(), Some(10));
+        apply_update(&mirror, "t", 6, 60, true);
+        assert_eq!(mirror.topic("t").get_current_version(), 7);
+        assert_eq!(sub.get_latest(), Some(60));
+    }
+}
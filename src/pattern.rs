@@ -0,0 +1,567 @@
+//! MQTT-style wildcard subscriptions over hierarchical topic names.
+//!
+//! Topic names are split into levels on `/`. In a pattern, `+` matches
+//! exactly one level and `#` matches all remaining levels (including none);
+//! `#` may only appear as the last level.
+//!
+//! ```rust
+//! use dropslot::prelude::*;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let bus = Bus::<f64>::new();
+//! let mut temps = bus.subscribe_pattern("sensors/+/temp").unwrap();
+//!
+//! bus.publish("sensors/floor1/temp", 21.5);
+//!
+//! let (topic, value) = temps.wait_for_message().await.unwrap();
+//! assert_eq!(&*topic, "sensors/floor1/temp");
+//! assert_eq!(value, 21.5);
+//! # }
+//! ```
+
+use crate::{error::BusError, multi::MultiSub, sub::Sub, topic::Topic};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, RwLock, Weak};
+use std::task::{Context, Poll, Waker};
+
+const LEVEL_SEPARATOR: char = '/';
+const SINGLE_LEVEL_WILDCARD: &str = "+";
+const MULTI_LEVEL_WILDCARD: &str = "#";
+
+/// A validated wildcard pattern for matching hierarchical topic names.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TopicPattern {
+    pattern: Box<str>,
+}
+
+impl TopicPattern {
+    /// Parses and validates a pattern.
+    ///
+    /// Returns `Err(BusError::invalid_pattern(..))` if a wildcard shares a
+    /// level with other characters or `#` is not the last level.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::pattern::TopicPattern;
+    /// assert!(TopicPattern::new("sensors/+/temp").is_ok());
+    /// assert!(TopicPattern::new("sensors/#").is_ok());
+    /// assert!(TopicPattern::new("sensors/#/temp").is_err());
+    /// assert!(TopicPattern::new("sensors/floor+").is_err());
+    /// ```
+    pub fn new(pattern: &str) -> Result<Self, BusError> {
+        let mut levels = pattern.split(LEVEL_SEPARATOR).peekable();
+        while let Some(level) = levels.next() {
+            let is_wildcard = level == SINGLE_LEVEL_WILDCARD || level == MULTI_LEVEL_WILDCARD;
+            let has_wildcard_chars = level.contains(['+', '#']);
+            if (has_wildcard_chars && !is_wildcard)
+                || (level == MULTI_LEVEL_WILDCARD && levels.peek().is_some())
+            {
+                return Err(BusError::invalid_pattern(pattern));
+            }
+        }
+        Ok(Self {
+            pattern: pattern.into(),
+        })
+    }
+
+    /// Returns the pattern as a string slice.
+    #[inline(always)]
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns true if the pattern contains `+` or `#` wildcards.
+    #[inline]
+    pub fn is_wildcard(&self) -> bool {
+        self.levels()
+            .any(|level| level == SINGLE_LEVEL_WILDCARD || level == MULTI_LEVEL_WILDCARD)
+    }
+
+    /// Returns true if `topic_name` matches this pattern.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::pattern::TopicPattern;
+    /// let pattern = TopicPattern::new("sensors/#").unwrap();
+    /// assert!(pattern.matches("sensors"));
+    /// assert!(pattern.matches("sensors/floor1/temp"));
+    /// assert!(!pattern.matches("actuators/floor1"));
+    /// ```
+    pub fn matches(&self, topic_name: &str) -> bool {
+        let mut pattern = self.levels();
+        let mut topic = topic_name.split(LEVEL_SEPARATOR);
+        loop {
+            match (pattern.next(), topic.next()) {
+                (Some(MULTI_LEVEL_WILDCARD), _) => return true,
+                (Some(SINGLE_LEVEL_WILDCARD), Some(_)) => {}
+                (Some(expected), Some(level)) if expected == level => {}
+                (None, None) => return true,
+                _ => return false,
+            }
+        }
+    }
+
+    #[inline]
+    fn levels(&self) -> std::str::Split<'_, char> {
+        self.pattern.split(LEVEL_SEPARATOR)
+    }
+}
+
+impl std::fmt::Display for TopicPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+impl std::str::FromStr for TopicPattern {
+    type Err = BusError;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        Self::new(pattern)
+    }
+}
+
+struct Node<V> {
+    children: HashMap<Box<str>, Node<V>>,
+    single_level: Option<Box<Node<V>>>,
+    multi_level: Vec<V>,
+    values: Vec<V>,
+}
+
+impl<V> Default for Node<V> {
+    fn default() -> Self {
+        Self {
+            children: HashMap::new(),
+            single_level: None,
+            multi_level: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+}
+
+impl<V: Clone> Node<V> {
+    fn collect(&self, levels: &[&str], out: &mut Vec<V>) {
+        out.extend(self.multi_level.iter().cloned());
+        let Some((level, rest)) = levels.split_first() else {
+            out.extend(self.values.iter().cloned());
+            return;
+        };
+        if let Some(child) = self.children.get(*level) {
+            child.collect(rest, out);
+        }
+        if let Some(child) = &self.single_level {
+            child.collect(rest, out);
+        }
+    }
+
+    fn retain(&mut self, f: &mut impl FnMut(&V) -> bool) -> usize {
+        self.multi_level.retain(|value| f(value));
+        self.values.retain(|value| f(value));
+        let mut len = self.multi_level.len() + self.values.len();
+
+        self.children.retain(|_, child| {
+            let child_len = child.retain(f);
+            len += child_len;
+            child_len > 0
+        });
+        if let Some(child) = &mut self.single_level {
+            let child_len = child.retain(f);
+            len += child_len;
+            if child_len == 0 {
+                self.single_level = None;
+            }
+        }
+        len
+    }
+}
+
+/// A trie of patterns, each level keyed by a literal or wildcard.
+pub(crate) struct PatternIndex<V> {
+    root: Node<V>,
+    len: usize,
+}
+
+impl<V: Clone> PatternIndex<V> {
+    pub(crate) fn new() -> Self {
+        Self {
+            root: Node::default(),
+            len: 0,
+        }
+    }
+
+    pub(crate) fn insert(&mut self, pattern: &TopicPattern, value: V) {
+        let mut node = &mut self.root;
+        for level in pattern.levels() {
+            match level {
+                MULTI_LEVEL_WILDCARD => {
+                    node.multi_level.push(value);
+                    self.len += 1;
+                    return;
+                }
+                SINGLE_LEVEL_WILDCARD => {
+                    node = node.single_level.get_or_insert_with(Default::default);
+                }
+                literal => {
+                    node = node.children.entry(literal.into()).or_default();
+                }
+            }
+        }
+        node.values.push(value);
+        self.len += 1;
+    }
+
+    /// Collects the values of every pattern matching `topic_name`.
+    pub(crate) fn collect(&self, topic_name: &str, out: &mut Vec<V>) {
+        let levels: Vec<&str> = topic_name.split(LEVEL_SEPARATOR).collect();
+        self.root.collect(&levels, out);
+    }
+
+    /// Keeps only the values for which `f` returns true, pruning empty levels.
+    pub(crate) fn retain(&mut self, mut f: impl FnMut(&V) -> bool) {
+        self.len = self.root.retain(&mut f);
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// Shared state between a [`PatternSub`] and the bus that feeds it topics.
+pub(crate) struct PatternShared<T> {
+    pending: Mutex<Vec<Sub<T>>>,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl<T> PatternShared<T> {
+    fn new() -> Self {
+        Self {
+            pending: Mutex::new(Vec::new()),
+            waker: Mutex::new(None),
+        }
+    }
+
+    fn attach(&self, sub: Sub<T>) {
+        self.pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(sub);
+        self.wake();
+    }
+
+    fn wake(&self) {
+        let waker = self
+            .waker
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// Wildcard subscriptions registered on a bus.
+pub(crate) struct PatternRegistry<T> {
+    index: RwLock<PatternIndex<Weak<PatternShared<T>>>>,
+    registered: AtomicUsize,
+}
+
+impl<T> PatternRegistry<T>
+where
+    T: Clone,
+{
+    pub(crate) fn new() -> Self {
+        Self {
+            index: RwLock::new(PatternIndex::new()),
+            registered: AtomicUsize::new(0),
+        }
+    }
+
+    /// Subscribes every matching wildcard subscription to a newly created topic.
+    ///
+    /// Must run before the topic becomes visible to other threads so that no
+    /// publish can slip in between creation and subscription.
+    #[inline]
+    pub(crate) fn on_topic_created(&self, topic: &Arc<Topic<T>>) {
+        if self.registered.load(Ordering::Acquire) == 0 {
+            return;
+        }
+        let mut matched = Vec::new();
+        self.index
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .collect(topic.name(), &mut matched);
+        for shared in matched.iter().filter_map(Weak::upgrade) {
+            shared.attach(topic.subscribe());
+        }
+    }
+
+    fn register(&self, pattern: &TopicPattern, shared: &Arc<PatternShared<T>>) {
+        let mut index = self.index.write().unwrap_or_else(PoisonError::into_inner);
+        index.retain(|entry| entry.strong_count() > 0);
+        index.insert(pattern, Arc::downgrade(shared));
+        self.registered.store(index.len(), Ordering::Release);
+    }
+}
+
+impl<T> PatternRegistry<T> {
+    /// Removes a dropped subscription so that topics created later skip it.
+    fn deregister(&self, shared: &Arc<PatternShared<T>>) {
+        let mut index = self.index.write().unwrap_or_else(PoisonError::into_inner);
+        index.retain(|entry| {
+            entry.strong_count() > 0 && !std::ptr::eq(entry.as_ptr(), Arc::as_ptr(shared))
+        });
+        self.registered.store(index.len(), Ordering::Release);
+    }
+}
+
+impl<T> Drop for PatternRegistry<T> {
+    fn drop(&mut self) {
+        let index = self.index.get_mut().unwrap_or_else(PoisonError::into_inner);
+        index.retain(|entry| {
+            if let Some(shared) = entry.upgrade() {
+                shared.wake();
+            }
+            false
+        });
+    }
+}
+
+/// Receives the latest messages from every topic matching a wildcard pattern.
+///
+/// Created by [`Bus::subscribe_pattern`](crate::Bus::subscribe_pattern).
+/// Topics created after subscribing are picked up automatically, and each
+/// delivery carries the name of the concrete topic it came from, as with
+/// [`MultiSub`].
+pub struct PatternSub<T> {
+    pattern: TopicPattern,
+    shared: Arc<PatternShared<T>>,
+    registry: Weak<PatternRegistry<T>>,
+    inner: MultiSub<T>,
+}
+
+impl<T> PatternSub<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    pub(crate) fn new(pattern: TopicPattern, registry: &Arc<PatternRegistry<T>>) -> Self {
+        let shared = Arc::new(PatternShared::new());
+        registry.register(&pattern, &shared);
+        Self {
+            pattern,
+            shared,
+            registry: Arc::downgrade(registry),
+            inner: MultiSub::new(),
+        }
+    }
+
+    /// Adds an already existing topic, delivering its current value first.
+    pub(crate) fn attach_existing(&self, topic: &Arc<Topic<T>>) {
+        let mut sub = topic.subscribe();
+        if sub.has_latest() {
+            sub.mark_unseen();
+        }
+        self.shared.attach(sub);
+    }
+
+    /// Waits for the next message from any matching topic.
+    ///
+    /// Returns the name of the topic that published along with its latest
+    /// message, or `None` once the bus and every matched topic are gone.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let bus = Bus::<String>::new();
+    /// let mut subscriber = bus.subscribe_pattern("logs/#").unwrap();
+    ///
+    /// bus.publish("logs/app/error", "disk full".to_string());
+    ///
+    /// let (topic, message) = subscriber.wait_for_message().await.unwrap();
+    /// assert_eq!(&*topic, "logs/app/error");
+    /// assert_eq!(message, "disk full");
+    /// # }
+    /// ```
+    pub async fn wait_for_message(&mut self) -> Option<(Arc<str>, T)> {
+        std::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Attempts to receive a message from any matching topic without blocking.
+    ///
+    /// Returns `Err(BusError::Empty { .. })` if no matching topic has
+    /// published since the last check, or `Err(BusError::Disconnected { .. })`
+    /// once the bus and every matched topic are gone.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<String>::new();
+    /// let mut subscriber = bus.subscribe_pattern("logs/+").unwrap();
+    ///
+    /// assert!(subscriber.try_get_message().is_err());
+    ///
+    /// bus.publish("logs/app", "started".to_string());
+    /// let (topic, message) = subscriber.try_get_message().unwrap();
+    /// assert_eq!(&*topic, "logs/app");
+    /// assert_eq!(message, "started");
+    /// ```
+    pub fn try_get_message(&mut self) -> Result<(Arc<str>, T), BusError> {
+        match self.poll_recv(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(Some(delivery)) => Ok(delivery),
+            Poll::Ready(None) => Err(BusError::disconnected(self.pattern.as_str())),
+            Poll::Pending => Err(BusError::empty(self.pattern.as_str())),
+        }
+    }
+
+    /// Returns the pattern this subscriber was created with.
+    #[inline(always)]
+    pub fn pattern(&self) -> &TopicPattern {
+        &self.pattern
+    }
+
+    /// Returns the number of topics currently matched by this subscriber.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<String>::new();
+    /// let _topic = bus.topic("logs/app");
+    /// let mut subscriber = bus.subscribe_pattern("logs/+").unwrap();
+    /// let _other = bus.topic("logs/db");
+    ///
+    /// assert_eq!(subscriber.topic_count(), 2);
+    /// ```
+    pub fn topic_count(&mut self) -> usize {
+        self.attach_pending();
+        self.inner.len()
+    }
+
+    #[inline]
+    pub(crate) fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<(Arc<str>, T)>> {
+        self.poll_recv_versioned_with(cx, |_, message, _| message.clone())
+    }
+
+    /// Polls every matched topic, passing the message and its topic version
+    /// to `f`.
+    pub(crate) fn poll_recv_versioned_with<R>(
+        &mut self,
+        cx: &mut Context<'_>,
+        f: impl FnMut(&str, &T, u64) -> R,
+    ) -> Poll<Option<(Arc<str>, R)>> {
+        *self
+            .shared
+            .waker
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(cx.waker().clone());
+        self.attach_pending();
+
+        match self.inner.poll_recv_versioned_with(cx, f) {
+            Poll::Ready(None) if self.registry.strong_count() > 0 => Poll::Pending,
+            poll => poll,
+        }
+    }
+
+    fn attach_pending(&mut self) {
+        let pending = std::mem::take(
+            &mut *self
+                .shared
+                .pending
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        for sub in pending {
+            self.inner.add(sub);
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for PatternSub<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PatternSub")
+            .field("pattern", &self.pattern)
+            .field("topics", &self.inner.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for PatternSub<T> {
+    fn drop(&mut self) {
+        if let Some(registry) = self.registry.upgrade() {
+            registry.deregister(&self.shared);
+        }
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<T> futures_core::Stream for PatternSub<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    type Item = (Arc<str>, T);
+
+    fn poll_next(self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(index: &PatternIndex<&'static str>, topic_name: &str) -> Vec<&'static str> {
+        let mut out = Vec::new();
+        index.collect(topic_name, &mut out);
+        out.sort_unstable();
+        out
+    }
+
+    #[test]
+    fn test_pattern_index_matching() {
+        let mut index = PatternIndex::new();
+        for pattern in ["a/b/c", "a/+/c", "a/#", "#", "+/b/+", "a/b"] {
+            index.insert(&TopicPattern::new(pattern).unwrap(), pattern);
+        }
+        assert_eq!(index.len(), 6);
+
+        assert_eq!(
+            collect(&index, "a/b/c"),
+            ["#", "+/b/+", "a/#", "a/+/c", "a/b/c"]
+        );
+        assert_eq!(collect(&index, "a/b"), ["#", "a/#", "a/b"]);
+        assert_eq!(collect(&index, "a"), ["#", "a/#"]);
+        assert_eq!(collect(&index, "x/y"), ["#"]);
+    }
+
+    #[test]
+    fn test_pattern_index_retain_prunes() {
+        let mut index = PatternIndex::new();
+        index.insert(&TopicPattern::new("a/+/c").unwrap(), 1);
+        index.insert(&TopicPattern::new("a/b/#").unwrap(), 2);
+
+        index.retain(|value| *value == 2);
+        assert_eq!(index.len(), 1);
+        assert!(index.root.children["a"].single_level.is_none());
+
+        index.retain(|_| false);
+        assert_eq!(index.len(), 0);
+        assert!(index.root.children.is_empty());
+    }
+
+    #[test]
+    fn test_dropped_pattern_sub_deregisters() {
+        let registry = Arc::new(PatternRegistry::<u8>::new());
+        let first = PatternSub::new(TopicPattern::new("a/+").unwrap(), &registry);
+        let second = PatternSub::new(TopicPattern::new("a/+").unwrap(), &registry);
+        assert_eq!(registry.registered.load(Ordering::Acquire), 2);
+
+        drop(first);
+        assert_eq!(registry.registered.load(Ordering::Acquire), 1);
+        drop(second);
+        assert_eq!(registry.registered.load(Ordering::Acquire), 0);
+    }
+}
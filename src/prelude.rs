@@ -6,10 +6,13 @@
 //! ```rust
 //! use dropslot::prelude::*;
 //!
-//! // Now you can use Bus, Topic, Sub, and BusError directly
+//! // Now you can use Bus, Topic, Sub, BusError and friends directly
 //! let bus = Bus::<String>::new();
 //! let topic = bus.topic("events");
 //! let mut subscriber = topic.subscribe();
 //! ```
 
-pub use crate::{Bus, BusError, Sub, Topic};
+pub use crate::{
+    Bus, BusError, BusSnapshot, BusStats, DeliveryMode, MultiSub, PatternSub, Sub, Topic, TopicKey,
+    TypedBus,
+};
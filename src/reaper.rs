@@ -0,0 +1,247 @@
+//! Policy-driven removal of unused topics.
+//!
+//! [`Bus::cleanup_unused_topics`] removes every topic without subscribers,
+//! including ones holding a value worth keeping. [`Bus::reap`] instead
+//! removes the topics a [`ReapPolicy`] selects: topics not published to for
+//! a while, or without subscribers for a while. Pinned topics, and with
+//! [`ReapPolicy::keep_retained`] topics holding an unexpired value, are
+//! always kept. Every sweep returns a [`ReapReport`] of what it removed.
+//!
+//! Call [`Bus::reap`] from your own loop, or with the `tokio` feature spawn
+//! a [`Reaper`] that sweeps at a fixed interval for as long as the bus lives.
+//!
+//! ```rust
+//! use dropslot::prelude::*;
+//! use dropslot::ReapPolicy;
+//! use std::time::Duration;
+//!
+//! let bus = Bus::<String>::new();
+//! bus.topic("session/42");
+//! bus.publish("config", "v1".to_string());
+//!
+//! let policy = ReapPolicy::new()
+//!     .unsubscribed_after(Duration::ZERO)
+//!     .keep_retained(true);
+//! let report = bus.reap(&policy);
+//! assert_eq!(report.removed_names(), ["session/42"]);
+//! assert_eq!(bus.topic_names(), ["config"]);
+//! ```
+//!
+//! [`Bus::cleanup_unused_topics`]: crate::Bus::cleanup_unused_topics
+
+use crate::{bus::Bus, topic::Topic};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+/// Which topics [`Bus::reap`] removes.
+///
+/// A topic is removed once any enabled timeout has passed, unless it is
+/// kept alive. The default policy removes nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReapPolicy {
+    idle_after: Option<Duration>,
+    unsubscribed_after: Option<Duration>,
+    keep_retained: bool,
+}
+
+impl ReapPolicy {
+    /// Creates a policy that removes nothing.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Removes topics not published to for `timeout`, counting from their
+    /// creation if they never were. Their subscribers are disconnected.
+    #[inline]
+    pub fn idle_after(mut self, timeout: Duration) -> Self {
+        self.idle_after = Some(timeout);
+        self
+    }
+
+    /// Removes topics that have had no subscribers for `timeout`.
+    #[inline]
+    pub fn unsubscribed_after(mut self, timeout: Duration) -> Self {
+        self.unsubscribed_after = Some(timeout);
+        self
+    }
+
+    /// Keeps topics holding an unexpired value, however long they were idle
+    /// or without subscribers.
+    #[inline]
+    pub fn keep_retained(mut self, keep: bool) -> Self {
+        self.keep_retained = keep;
+        self
+    }
+
+    /// Returns why `topic` should be removed, if it should.
+    fn verdict<T: Clone>(&self, topic: &Topic<T>) -> Option<ReapReason> {
+        if topic.is_pinned() || (self.keep_retained && topic.retains_value()) {
+            return None;
+        }
+        if let Some(timeout) = self.idle_after {
+            let idle = topic.idle_for();
+            if idle >= timeout {
+                return Some(ReapReason::Idle(idle));
+            }
+        }
+        let timeout = self.unsubscribed_after?;
+        let unsubscribed = topic.unsubscribed_for()?;
+        (unsubscribed >= timeout).then_some(ReapReason::Unsubscribed(unsubscribed))
+    }
+}
+
+/// Why a topic was reaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReapReason {
+    /// Nothing was published for this long.
+    Idle(Duration),
+    /// The topic had no subscribers for this long.
+    Unsubscribed(Duration),
+}
+
+/// A topic removed by a sweep.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reaped {
+    /// Name of the topic.
+    pub name: String,
+    /// Why it was removed.
+    pub reason: ReapReason,
+}
+
+/// What one sweep removed.
+///
+/// Topics derived from a reaped topic are removed with it, but only the
+/// reaped topic is listed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReapReport {
+    /// The removed topics.
+    pub removed: Vec<Reaped>,
+}
+
+impl ReapReport {
+    /// Returns the names of the removed topics.
+    pub fn removed_names(&self) -> Vec<&str> {
+        self.removed
+            .iter()
+            .map(|reaped| reaped.name.as_str())
+            .collect()
+    }
+
+    /// Returns true if nothing was removed.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty()
+    }
+}
+
+impl<T> Bus<T>
+where
+    T: Clone,
+{
+    /// Removes the topics `policy` selects and reports them.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::{Bus, ReapPolicy, ReapReason};
+    /// use std::time::Duration;
+    ///
+    /// let bus = Bus::<u32>::new();
+    /// bus.publish("ticks", 1);
+    /// bus.topic("pinned").set_pinned(true);
+    ///
+    /// let report = bus.reap(&ReapPolicy::new().idle_after(Duration::ZERO));
+    /// assert_eq!(report.removed_names(), ["ticks"]);
+    /// assert!(matches!(report.removed[0].reason, ReapReason::Idle(_)));
+    /// assert_eq!(bus.topic_names(), ["pinned"]);
+    /// ```
+    pub fn reap(&self, policy: &ReapPolicy) -> ReapReport {
+        #[cfg(feature = "tracing")]
+        let _entered = tracing::debug_span!("reap", topics = self.topic_count()).entered();
+        let mut report = ReapReport::default();
+        for key in self.topic_keys() {
+            let mut reason = None;
+            let removed = self.remove_topic_if(&key, |topic| {
+                reason = policy.verdict(topic);
+                reason.is_some()
+            });
+            if let (Some(_), Some(reason)) = (removed, reason) {
+                report.removed.push(Reaped {
+                    name: key.to_string(),
+                    reason,
+                });
+            }
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(removed = report.removed.len(), "reap finished");
+        report
+    }
+}
+
+/// Sweeps a bus with a [`ReapPolicy`] at a fixed interval.
+///
+/// Holds the bus weakly, so it stops once the bus is dropped. Run it by
+/// spawning [`run`](Reaper::run) on a tokio runtime (requires the `tokio`
+/// feature), or drive it with [`tick`](Reaper::tick).
+#[derive(Debug)]
+pub struct Reaper<T> {
+    bus: Weak<Bus<T>>,
+    policy: ReapPolicy,
+}
+
+impl<T> Reaper<T>
+where
+    T: Clone,
+{
+    /// Creates a reaper for `bus`.
+    pub fn new(bus: &Arc<Bus<T>>, policy: ReapPolicy) -> Self {
+        Self {
+            bus: Arc::downgrade(bus),
+            policy,
+        }
+    }
+
+    /// Returns the policy this reaper applies.
+    #[inline]
+    pub fn policy(&self) -> &ReapPolicy {
+        &self.policy
+    }
+
+    /// Runs one sweep, or returns `None` if the bus was dropped.
+    pub fn tick(&self) -> Option<ReapReport> {
+        let bus = self.bus.upgrade()?;
+        Some(bus.reap(&self.policy))
+    }
+
+    /// Sweeps every `interval` until the bus is dropped, passing each
+    /// report that removed something to `on_sweep`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::{Bus, ReapPolicy, Reaper};
+    /// # use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let bus = Arc::new(Bus::<u32>::new());
+    /// let policy = ReapPolicy::new().unsubscribed_after(Duration::from_secs(300));
+    /// tokio::spawn(Reaper::new(&bus, policy).run(Duration::from_secs(30), |report| {
+    ///     println!("reaped {:?}", report.removed_names());
+    /// }));
+    /// # }
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub async fn run(self, interval: Duration, mut on_sweep: impl FnMut(ReapReport)) {
+        let mut ticks = tokio::time::interval(interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticks.tick().await;
+            match self.tick() {
+                Some(report) if report.is_empty() => {}
+                Some(report) => on_sweep(report),
+                None => return,
+            }
+        }
+    }
+}
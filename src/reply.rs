@@ -0,0 +1,364 @@
+//! Request/reply over named endpoints of a bus.
+//!
+//! A service takes requests for a topic name with [`Bus::responder`], and
+//! clients ask it with [`Bus::request`], which waits for the matching reply.
+//! Every request carries an ID, so any number of requests can be in flight
+//! at once and each gets its own answer. Unlike topic messages, requests are
+//! queued rather than replaced, and every one is handed to exactly one
+//! responder.
+//!
+//! ```rust
+//! use dropslot::prelude::*;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let bus = Bus::<String>::new();
+//! let responder = bus.responder("config");
+//! tokio::spawn(responder.serve(|key| format!("value of {key}")));
+//!
+//! let reply = bus.request("config", "timeout".to_string()).await;
+//! assert_eq!(reply.unwrap(), "value of timeout");
+//! # }
+//! ```
+//!
+//! Requests and replies don't travel through [`Topic`]s. A topic keeps only
+//! its latest message and shows it to every subscriber, whereas a request
+//! must wait in a queue until exactly one responder takes it, and its reply
+//! must reach only the requester. Inboxes and reply slots are therefore
+//! built on the notification primitive underneath topics instead. As a
+//! consequence, request topics don't appear in [`Bus::topic_names`], stats,
+//! lifecycle events or tracing spans, and time-to-live doesn't apply to
+//! them.
+//!
+//! [`Bus::request`] waits for as long as it takes. With the `tokio` feature,
+//! `Bus::request_timeout` gives up after a deadline; under other runtimes,
+//! race the request against the runtime's timer, as dropping it withdraws
+//! the request.
+//!
+//! [`Topic`]: crate::Topic
+
+use crate::{bus::Bus, error::BusError, slot};
+use std::collections::VecDeque;
+use std::collections::hash_map::{Entry, HashMap};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Requests waiting for any responder of one topic.
+#[derive(Debug)]
+struct Inbox<T> {
+    queue: Mutex<VecDeque<Request<T>>>,
+    doorbell: slot::Sender<()>,
+    responders: AtomicUsize,
+}
+
+/// Routes requests to responders and replies back to the requesters.
+#[derive(Debug)]
+pub(crate) struct ReplyRegistry<T> {
+    next_id: AtomicU64,
+    inboxes: Mutex<HashMap<Arc<str>, Arc<Inbox<T>>>>,
+    pending: Mutex<HashMap<u64, slot::Sender<Option<T>>>>,
+}
+
+impl<T> ReplyRegistry<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            next_id: AtomicU64::new(0),
+            inboxes: Mutex::new(HashMap::new()),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Queues a request for `topic` and returns the handle to its reply.
+    fn send(self: &Arc<Self>, topic: &str, message: T) -> Result<PendingReply<T>, BusError> {
+        let inboxes = lock(&self.inboxes);
+        let Some((topic, inbox)) = inboxes.get_key_value(topic) else {
+            return Err(BusError::no_responder(topic));
+        };
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let reply = slot::Sender::new(None);
+        let receiver = reply.subscribe();
+        lock(&self.pending).insert(id, reply);
+        lock(&inbox.queue).push_back(Request {
+            id,
+            topic: topic.clone(),
+            message,
+            registry: Arc::downgrade(self),
+        });
+        inbox.doorbell.send_modify(|_| {});
+        Ok(PendingReply {
+            id,
+            topic: topic.clone(),
+            receiver,
+            registry: self.clone(),
+        })
+    }
+
+    /// Stops waiting for reply `id`; its requester sees the slot close.
+    #[inline]
+    fn abandon(&self, id: u64) {
+        let reply = lock(&self.pending).remove(&id);
+        drop(reply);
+    }
+}
+
+/// A request handed to a [`Responder`].
+///
+/// Dropping it without calling [`reply`](Request::reply) fails the request
+/// with [`BusError::NoResponder`].
+#[derive(Debug)]
+pub struct Request<T> {
+    id: u64,
+    topic: Arc<str>,
+    message: T,
+    registry: Weak<ReplyRegistry<T>>,
+}
+
+impl<T> Request<T> {
+    /// Returns the ID that correlates this request with its reply.
+    #[inline(always)]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the topic the request was sent to.
+    #[inline(always)]
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Returns the request message.
+    #[inline(always)]
+    pub fn message(&self) -> &T {
+        &self.message
+    }
+
+    /// Sends the reply to the requester.
+    ///
+    /// Does nothing if the requester stopped waiting.
+    pub fn reply(self, message: T) {
+        let Some(registry) = self.registry.upgrade() else {
+            return;
+        };
+        let reply = lock(&registry.pending).remove(&self.id);
+        if let Some(reply) = reply {
+            reply.send_modify(|slot| *slot = Some(message));
+        }
+    }
+}
+
+impl<T> Drop for Request<T> {
+    fn drop(&mut self) {
+        if let Some(registry) = self.registry.upgrade() {
+            registry.abandon(self.id);
+        }
+    }
+}
+
+/// Receives the requests sent to one topic.
+///
+/// Created by [`Bus::responder`]. Several responders of the same topic
+/// share its requests, each going to whichever asks first. Requests still
+/// queued when the last responder is dropped fail with
+/// [`BusError::NoResponder`].
+#[derive(Debug)]
+pub struct Responder<T> {
+    topic: Arc<str>,
+    inbox: Weak<Inbox<T>>,
+    doorbell: slot::Receiver<()>,
+    registry: Weak<ReplyRegistry<T>>,
+}
+
+impl<T> Responder<T> {
+    /// Returns the topic this responder answers.
+    #[inline(always)]
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Takes the oldest queued request without waiting.
+    pub fn try_get_request(&mut self) -> Option<Request<T>> {
+        let inbox = self.inbox.upgrade()?;
+        lock(&inbox.queue).pop_front()
+    }
+
+    /// Waits for the next request.
+    ///
+    /// Returns `None` once the bus is dropped.
+    pub async fn wait_for_request(&mut self) -> Option<Request<T>> {
+        loop {
+            if let Some(request) = self.try_get_request() {
+                return Some(request);
+            }
+            self.doorbell.changed().await.ok()?;
+        }
+    }
+
+    /// Answers every request with `handler` until the bus is dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let bus = Bus::<u32>::new();
+    /// tokio::spawn(bus.responder("square").serve(|n| n * n));
+    ///
+    /// assert_eq!(bus.request("square", 12).await, Ok(144));
+    /// # }
+    /// ```
+    pub async fn serve(mut self, mut handler: impl FnMut(&T) -> T) {
+        while let Some(request) = self.wait_for_request().await {
+            let reply = handler(request.message());
+            request.reply(reply);
+        }
+    }
+}
+
+impl<T> Drop for Responder<T> {
+    fn drop(&mut self) {
+        let Some(registry) = self.registry.upgrade() else {
+            return;
+        };
+        let mut inboxes = lock(&registry.inboxes);
+        let Entry::Occupied(entry) = inboxes.entry(self.topic.clone()) else {
+            return;
+        };
+        if entry.get().responders.fetch_sub(1, Ordering::Relaxed) > 1 {
+            return;
+        }
+        let inbox = entry.remove();
+        drop(inboxes);
+        // Dropping the requests fails them.
+        let orphans = std::mem::take(&mut *lock(&inbox.queue));
+        drop(orphans);
+    }
+}
+
+/// A request waiting for its reply.
+struct PendingReply<T> {
+    id: u64,
+    topic: Arc<str>,
+    receiver: slot::Receiver<Option<T>>,
+    registry: Arc<ReplyRegistry<T>>,
+}
+
+impl<T> PendingReply<T>
+where
+    T: Clone,
+{
+    async fn wait(mut self) -> Result<T, BusError> {
+        loop {
+            let closed = self.receiver.changed().await.is_err();
+            if let Some(reply) = self.receiver.borrow().clone() {
+                return Ok(reply);
+            }
+            if closed {
+                return Err(BusError::no_responder(&*self.topic));
+            }
+        }
+    }
+}
+
+impl<T> Drop for PendingReply<T> {
+    fn drop(&mut self) {
+        self.registry.abandon(self.id);
+    }
+}
+
+impl<T> Bus<T>
+where
+    T: Clone,
+{
+    /// Sends `message` to the responders of `topic` and waits for the reply.
+    ///
+    /// Request topics are separate from the bus's regular topics. There is
+    /// no deadline: use `request_timeout` with the `tokio` feature, or drop
+    /// the returned future, which withdraws the request.
+    ///
+    /// # Errors
+    /// Returns [`BusError::NoResponder`] if no responder is registered for
+    /// `topic`, or if the request is dropped without a reply.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let bus = Bus::<String>::new();
+    /// let result = bus.request("nobody", "ping".to_string()).await;
+    /// assert!(result.unwrap_err().is_no_responder());
+    /// # }
+    /// ```
+    pub async fn request(&self, topic: &str, message: T) -> Result<T, BusError> {
+        self.replies().send(topic, message)?.wait().await
+    }
+
+    /// Registers a responder for requests sent to `topic`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let bus = Bus::<u32>::new();
+    /// let mut responder = bus.responder("double");
+    ///
+    /// let server = async {
+    ///     let request = responder.wait_for_request().await.unwrap();
+    ///     let doubled = request.message() * 2;
+    ///     request.reply(doubled);
+    /// };
+    /// let (reply, ()) = tokio::join!(bus.request("double", 21), server);
+    /// assert_eq!(reply, Ok(42));
+    /// # }
+    /// ```
+    pub fn responder(&self, topic: &str) -> Responder<T> {
+        let registry = self.replies();
+        let mut inboxes = lock(&registry.inboxes);
+        let (topic, inbox) = match inboxes.get_key_value(topic) {
+            Some((topic, inbox)) => (topic.clone(), inbox.clone()),
+            None => {
+                let topic: Arc<str> = topic.into();
+                let inbox = Arc::new(Inbox {
+                    queue: Mutex::new(VecDeque::new()),
+                    doorbell: slot::Sender::new(()),
+                    responders: AtomicUsize::new(0),
+                });
+                inboxes.insert(topic.clone(), inbox.clone());
+                (topic, inbox)
+            }
+        };
+        inbox.responders.fetch_add(1, Ordering::Relaxed);
+        Responder {
+            topic,
+            doorbell: inbox.doorbell.subscribe(),
+            inbox: Arc::downgrade(&inbox),
+            registry: Arc::downgrade(registry),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abandoned_requests_leave_nothing_pending() {
+        let bus = Bus::<u32>::new();
+        let mut responder = bus.responder("t");
+
+        let pending = bus.replies().send("t", 1).unwrap();
+        assert_eq!(lock(&bus.replies().pending).len(), 1);
+        drop(pending);
+        assert!(lock(&bus.replies().pending).is_empty());
+
+        let request = responder.try_get_request().unwrap();
+        request.reply(2);
+        drop(responder);
+        assert!(lock(&bus.replies().inboxes).is_empty());
+    }
+}
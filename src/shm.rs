@@ -0,0 +1,329 @@
+//! Latest values shared through memory-mapped files.
+//!
+//! Enabled with the `shm` feature. A [`ShmTopic`] holds one fixed-size
+//! [`Pod`] value in a file mapped into memory, typically under `/dev/shm`,
+//! and any number of [`ShmSub`]s in other processes read it without system
+//! calls. Writes are guarded by a seqlock: readers retry instead of ever
+//! seeing a half-written value, and poll the version number to find out
+//! whether something new was published, like [`Sub::try_get_message`] does.
+//!
+//! Each file has a single writer. A writer that reopens an existing file
+//! continues its version numbers, so readers never see them go backwards.
+//!
+//! ```rust
+//! use dropslot::shm::{ShmSub, ShmTopic};
+//!
+//! let path = std::env::temp_dir().join("dropslot-doc-shm");
+//! let mut topic = ShmTopic::<[u32; 4]>::create(&path).unwrap();
+//! let mut subscriber = ShmSub::<[u32; 4]>::open(&path).unwrap();
+//!
+//! topic.publish([1, 2, 3, 4]);
+//! assert_eq!(subscriber.try_get_message().unwrap(), Some([1, 2, 3, 4]));
+//! assert!(subscriber.try_get_message().is_err());
+//! # drop(topic);
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+//!
+//! [`Sub::try_get_message`]: crate::Sub::try_get_message
+
+use crate::error::BusError;
+use memmap2::{Mmap, MmapMut};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::marker::PhantomData;
+use std::mem::{align_of, size_of};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering, fence};
+
+pub use bytemuck::Pod;
+
+const MAGIC: u64 = u64::from_le_bytes(*b"DROPSHM1");
+
+/// Bytes reserved for the header; the value starts right after it.
+const HEADER_LEN: usize = 64;
+
+// Header fields, as `u64` offsets into the mapping.
+const MAGIC_OFFSET: usize = 0;
+const SIZE_OFFSET: usize = 1;
+const SEQ_OFFSET: usize = 2;
+const CLOSED_OFFSET: usize = 3;
+
+/// Returns the header field at `index` of a mapping.
+///
+/// # Safety
+/// `base` must point to a live, page-aligned mapping of at least
+/// [`HEADER_LEN`] bytes.
+#[inline(always)]
+unsafe fn field<'a>(base: *const u8, index: usize) -> &'a AtomicU64 {
+    unsafe { &*base.cast::<AtomicU64>().add(index) }
+}
+
+fn check_layout<T>() -> io::Result<()> {
+    if align_of::<T>() > HEADER_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "value alignment exceeds 64 bytes",
+        ));
+    }
+    Ok(())
+}
+
+/// The writing side of a shared-memory topic.
+///
+/// Readers see the topic as disconnected once it is dropped.
+#[derive(Debug)]
+pub struct ShmTopic<T: Pod> {
+    map: MmapMut,
+    path: PathBuf,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> ShmTopic<T> {
+    /// Creates the topic file at `path`, or takes over an existing one
+    /// holding the same value size.
+    ///
+    /// # Errors
+    /// Returns any error from the filesystem or from mapping the file.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        check_layout::<T>()?;
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let len = (HEADER_LEN + size_of::<T>()) as u64;
+        if file.metadata()?.len() < len {
+            file.set_len(len)?;
+        }
+        // SAFETY: the file is at least `len` bytes long; concurrent access is
+        // limited to the seqlock protocol below.
+        let map = unsafe { MmapMut::map_mut(&file)? };
+
+        let topic = Self {
+            map,
+            path: path.to_path_buf(),
+            _marker: PhantomData,
+        };
+        let (magic, size, seq) = (
+            topic.field(MAGIC_OFFSET),
+            topic.field(SIZE_OFFSET),
+            topic.field(SEQ_OFFSET),
+        );
+        if magic.load(Ordering::Acquire) == MAGIC
+            && size.load(Ordering::Relaxed) == size_of::<T>() as u64
+        {
+            // A writer that died mid-publish leaves the sequence odd.
+            if seq.load(Ordering::Relaxed) & 1 == 1 {
+                seq.fetch_add(1, Ordering::Release);
+            }
+        } else {
+            magic.store(0, Ordering::Relaxed);
+            size.store(size_of::<T>() as u64, Ordering::Relaxed);
+            seq.store(0, Ordering::Relaxed);
+            magic.store(MAGIC, Ordering::Release);
+        }
+        topic.field(CLOSED_OFFSET).store(0, Ordering::Release);
+        Ok(topic)
+    }
+
+    /// Publishes a value, replacing the previous one.
+    #[inline]
+    pub fn publish(&mut self, message: T) {
+        // SAFETY: the value area is in bounds and aligned (see `check_layout`).
+        let value = unsafe { self.map.as_mut_ptr().add(HEADER_LEN).cast::<T>() };
+        let seq = self.field(SEQ_OFFSET);
+        let start = seq.load(Ordering::Relaxed);
+        seq.store(start.wrapping_add(1), Ordering::Relaxed);
+        fence(Ordering::Release);
+        // SAFETY: readers discard whatever they read while the sequence is odd.
+        unsafe { value.write_volatile(message) };
+        seq.store(start.wrapping_add(2), Ordering::Release);
+    }
+
+    /// Returns the version of the latest published value, 0 if none.
+    #[inline]
+    pub fn get_current_version(&self) -> u64 {
+        self.field(SEQ_OFFSET).load(Ordering::Acquire) / 2
+    }
+
+    /// Returns the path of the topic file.
+    #[inline(always)]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    #[inline(always)]
+    fn field(&self, index: usize) -> &AtomicU64 {
+        // SAFETY: the mapping outlives `self` and holds the whole header.
+        unsafe { field(self.map.as_ptr(), index) }
+    }
+}
+
+impl<T: Pod> Drop for ShmTopic<T> {
+    fn drop(&mut self) {
+        self.field(CLOSED_OFFSET).store(1, Ordering::Release);
+    }
+}
+
+/// The reading side of a shared-memory topic.
+#[derive(Debug)]
+pub struct ShmSub<T: Pod> {
+    map: Mmap,
+    path: PathBuf,
+    last_seen_version: u64,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> ShmSub<T> {
+    /// Opens a topic file created by [`ShmTopic::create`].
+    ///
+    /// Like a new [`Sub`](crate::Sub), the subscriber starts out having seen
+    /// the current value.
+    ///
+    /// # Errors
+    /// Returns `io::ErrorKind::InvalidData` if the file is not an initialized
+    /// topic of this value size, or any error from opening or mapping it.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        check_layout::<T>()?;
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        if file.metadata()?.len() < (HEADER_LEN + size_of::<T>()) as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a shared-memory topic",
+            ));
+        }
+        // SAFETY: the file is large enough and only read through the seqlock.
+        let map = unsafe { Mmap::map(&file)? };
+
+        let mut sub = Self {
+            map,
+            path: path.to_path_buf(),
+            last_seen_version: 0,
+            _marker: PhantomData,
+        };
+        if sub.field(MAGIC_OFFSET).load(Ordering::Acquire) != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a shared-memory topic",
+            ));
+        }
+        if sub.field(SIZE_OFFSET).load(Ordering::Relaxed) != size_of::<T>() as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "shared-memory topic holds a different value size",
+            ));
+        }
+        sub.last_seen_version = sub.get_current_version();
+        Ok(sub)
+    }
+
+    /// Attempts to receive a value without blocking.
+    ///
+    /// Returns `Ok(Some(message))` if a value newer than the last one read
+    /// is available, `Err(BusError::Empty { .. })` if not, and
+    /// `Err(BusError::Disconnected { .. })` if nothing is new and the
+    /// writer is gone.
+    pub fn try_get_message(&mut self) -> Result<Option<T>, BusError> {
+        if self.get_current_version() == self.last_seen_version {
+            return Err(if self.is_closed() {
+                BusError::disconnected(self.path.display().to_string())
+            } else {
+                BusError::empty(self.path.display().to_string())
+            });
+        }
+        let (message, version) = self.read();
+        self.last_seen_version = version;
+        Ok(Some(message))
+    }
+
+    /// Returns the latest value without marking it as read.
+    pub fn get_latest(&self) -> Option<T> {
+        match self.read() {
+            (_, 0) => None,
+            (message, _) => Some(message),
+        }
+    }
+
+    /// Returns the version of the latest published value, 0 if none.
+    #[inline]
+    pub fn get_current_version(&self) -> u64 {
+        self.field(SEQ_OFFSET).load(Ordering::Acquire) / 2
+    }
+
+    /// Returns true if a value has been published.
+    #[inline]
+    pub fn has_latest(&self) -> bool {
+        self.get_current_version() > 0
+    }
+
+    /// Returns true if the writer has dropped its [`ShmTopic`].
+    #[inline]
+    pub fn is_closed(&self) -> bool {
+        self.field(CLOSED_OFFSET).load(Ordering::Acquire) != 0
+    }
+
+    /// Reads a consistent value and its version, retrying while a write is in
+    /// progress.
+    fn read(&self) -> (T, u64) {
+        let seq = self.field(SEQ_OFFSET);
+        loop {
+            let start = seq.load(Ordering::Acquire);
+            if start & 1 == 0 {
+                // SAFETY: in bounds and aligned; `T: Pod` accepts any bytes,
+                // and a torn read is detected and discarded below.
+                let message = unsafe {
+                    self.map
+                        .as_ptr()
+                        .add(HEADER_LEN)
+                        .cast::<T>()
+                        .read_volatile()
+                };
+                fence(Ordering::Acquire);
+                if seq.load(Ordering::Relaxed) == start {
+                    return (message, start / 2);
+                }
+            }
+            std::hint::spin_loop();
+        }
+    }
+
+    #[inline(always)]
+    fn field(&self, index: usize) -> &AtomicU64 {
+        // SAFETY: the mapping outlives `self` and holds the whole header.
+        unsafe { field(self.map.as_ptr(), index) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_readers_never_see_torn_values() {
+        let path = std::env::temp_dir().join(format!("dropslot-shm-torn-{}", std::process::id()));
+        let mut topic = ShmTopic::<[u64; 16]>::create(&path).unwrap();
+        let mut sub = ShmSub::<[u64; 16]>::open(&path).unwrap();
+
+        let reader = thread::spawn(move || {
+            let mut last = 0;
+            while last < 10_000 {
+                if let Ok(Some(value)) = sub.try_get_message() {
+                    assert!(value.iter().all(|&word| word == value[0]));
+                    assert!(value[0] > last);
+                    last = value[0];
+                }
+            }
+        });
+        for i in 1..=10_000 {
+            topic.publish([i; 16]);
+        }
+        reader.join().unwrap();
+
+        drop(topic);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,312 @@
+//! Runtime-neutral latest-value slot with change notification.
+//!
+//! A [`Sender`] owns a value that any number of [`Receiver`]s can read.
+//! Receivers wait for changes through plain [`Waker`]s, so the slot works
+//! under any executor, or none at all.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering, fence};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard};
+use std::task::{Context, Poll, Waker};
+
+/// Returned by [`Receiver::changed`] once the sender is gone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Closed;
+
+#[derive(Debug, Default)]
+struct Waiters {
+    next_key: u64,
+    wakers: HashMap<u64, Waker>,
+}
+
+#[derive(Debug)]
+struct Shared<T> {
+    value: RwLock<T>,
+    version: AtomicU64,
+    closed: AtomicBool,
+    receiver_count: AtomicUsize,
+    /// Number of registered wakers, so that sends nobody waits for skip the
+    /// lock.
+    waiting: AtomicUsize,
+    waiters: Mutex<Waiters>,
+}
+
+impl<T> Shared<T> {
+    #[inline]
+    fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.value.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[inline]
+    fn waiters(&self) -> MutexGuard<'_, Waiters> {
+        self.waiters.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn notify(&self) {
+        // Pairs with the fence in `Changed::poll`: either this sees the
+        // waker registered there, or the receiver sees the new version.
+        fence(Ordering::SeqCst);
+        if self.waiting.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let wakers: Vec<Waker> = {
+            let mut waiters = self.waiters();
+            self.waiting.store(0, Ordering::Relaxed);
+            waiters.wakers.drain().map(|(_, waker)| waker).collect()
+        };
+        // Woken outside the lock: dropping a waker can drop a task that
+        // holds a `Changed` on this slot.
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+/// The writing half of a slot. Receivers see the slot as closed once it is
+/// dropped.
+#[derive(Debug)]
+pub(crate) struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                value: RwLock::new(value),
+                version: AtomicU64::new(0),
+                closed: AtomicBool::new(false),
+                receiver_count: AtomicUsize::new(0),
+                waiting: AtomicUsize::new(0),
+                waiters: Mutex::new(Waiters::default()),
+            }),
+        }
+    }
+
+    /// Modifies the value in place and wakes every waiting receiver.
+    pub(crate) fn send_modify(&self, modify: impl FnOnce(&mut T)) {
+        self.send_if_modified(|value| {
+            modify(value);
+            true
+        });
+    }
+
+    /// Like [`send_modify`](Self::send_modify), but receivers are only told
+    /// about the change if `modify` returns true.
+    pub(crate) fn send_if_modified(&self, modify: impl FnOnce(&mut T) -> bool) -> bool {
+        {
+            let mut value = self
+                .shared
+                .value
+                .write()
+                .unwrap_or_else(PoisonError::into_inner);
+            if !modify(&mut value) {
+                return false;
+            }
+            self.shared.version.fetch_add(1, Ordering::Release);
+        }
+        self.shared.notify();
+        true
+    }
+
+    #[inline]
+    pub(crate) fn borrow(&self) -> RwLockReadGuard<'_, T> {
+        self.shared.read()
+    }
+
+    /// Creates a receiver that has already seen the current value.
+    pub(crate) fn subscribe(&self) -> Receiver<T> {
+        self.shared.receiver_count.fetch_add(1, Ordering::Relaxed);
+        Receiver {
+            seen: self.shared.version.load(Ordering::Acquire),
+            shared: self.shared.clone(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn receiver_count(&self) -> usize {
+        self.shared.receiver_count.load(Ordering::Relaxed)
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Release);
+        self.shared.notify();
+    }
+}
+
+/// The reading half of a slot.
+#[derive(Debug)]
+pub(crate) struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+    seen: u64,
+}
+
+impl<T> Receiver<T> {
+    /// Returns the current value without marking it as seen.
+    #[inline]
+    pub(crate) fn borrow(&self) -> RwLockReadGuard<'_, T> {
+        self.shared.read()
+    }
+
+    /// Returns the current value and marks it as seen.
+    #[inline]
+    pub(crate) fn borrow_and_update(&mut self) -> RwLockReadGuard<'_, T> {
+        let value = self.shared.read();
+        self.seen = self.shared.version.load(Ordering::Acquire);
+        value
+    }
+
+    /// Marks the current value as unseen so the next [`changed`](Self::changed)
+    /// completes at once.
+    #[inline]
+    pub(crate) fn mark_changed(&mut self) {
+        self.seen = self.shared.version.load(Ordering::Acquire).wrapping_sub(1);
+    }
+
+    /// Waits until a value newer than the last seen one is sent, then marks
+    /// it as seen. Fails once the sender is dropped and no unseen value is
+    /// left.
+    #[inline]
+    pub(crate) fn changed(&mut self) -> Changed<'_, T> {
+        Changed {
+            receiver: self,
+            key: None,
+        }
+    }
+
+    fn poll_changed(&mut self) -> Poll<Result<(), Closed>> {
+        let version = self.shared.version.load(Ordering::Acquire);
+        if version != self.seen {
+            self.seen = version;
+            Poll::Ready(Ok(()))
+        } else if self.shared.closed.load(Ordering::Acquire) {
+            Poll::Ready(Err(Closed))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.receiver_count.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Future returned by [`Receiver::changed`].
+pub(crate) struct Changed<'a, T> {
+    receiver: &'a mut Receiver<T>,
+    key: Option<u64>,
+}
+
+impl<T> Future for Changed<'_, T> {
+    type Output = Result<(), Closed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if let Poll::Ready(result) = this.receiver.poll_changed() {
+            return Poll::Ready(result);
+        }
+
+        // Register before checking again: a sender that changes the value
+        // after the check above either sees this waker or is seen below.
+        let mut waiters = this.receiver.shared.waiters();
+        let key = *this.key.get_or_insert_with(|| {
+            waiters.next_key += 1;
+            waiters.next_key
+        });
+        waiters.wakers.insert(key, cx.waker().clone());
+        this.receiver
+            .shared
+            .waiting
+            .store(waiters.wakers.len(), Ordering::Relaxed);
+        drop(waiters);
+
+        fence(Ordering::SeqCst);
+        this.receiver.poll_changed()
+    }
+}
+
+impl<T> Drop for Changed<'_, T> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            let shared = &self.receiver.shared;
+            let mut waiters = shared.waiters();
+            waiters.wakers.remove(&key);
+            shared
+                .waiting
+                .store(waiters.wakers.len(), Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocking::block_on;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_changed_wakes_on_send_and_close() {
+        let sender = Sender::new(0);
+        let mut receiver = sender.subscribe();
+        assert_eq!(sender.receiver_count(), 1);
+
+        let deadline = Instant::now() + Duration::from_millis(10);
+        assert_eq!(block_on(receiver.changed(), Some(deadline)), None);
+        assert!(receiver.shared.waiters().wakers.is_empty());
+        assert_eq!(receiver.shared.waiting.load(Ordering::Relaxed), 0);
+        let shared = receiver.shared.clone();
+
+        let handle = thread::spawn(move || {
+            let changed = block_on(receiver.changed(), None);
+            let value = *receiver.borrow();
+            let closed = block_on(receiver.changed(), None);
+            (changed, value, closed)
+        });
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(shared.waiting.load(Ordering::Relaxed), 1);
+        sender.send_modify(|value| *value = 7);
+        assert_eq!(shared.waiting.load(Ordering::Relaxed), 0);
+        thread::sleep(Duration::from_millis(10));
+        drop(sender);
+
+        assert_eq!(handle.join().unwrap(), (Some(Ok(())), 7, Some(Err(Closed))));
+    }
+
+    #[test]
+    fn test_mark_changed_and_borrow_and_update() {
+        let sender = Sender::new("a");
+        let mut receiver = sender.subscribe();
+
+        receiver.mark_changed();
+        assert_eq!(block_on(receiver.changed(), None), Some(Ok(())));
+
+        sender.send_modify(|value| *value = "b");
+        assert_eq!(*receiver.borrow_and_update(), "b");
+
+        drop(sender);
+        assert_eq!(block_on(receiver.changed(), None), Some(Err(Closed)));
+    }
+    #[test]
+    fn test_send_if_modified_skips_unchanged() {
+        let sender = Sender::new(1);
+        let mut receiver = sender.subscribe();
+
+        assert!(!sender.send_if_modified(|_| false));
+        let deadline = Instant::now() + Duration::from_millis(10);
+        assert_eq!(block_on(receiver.changed(), Some(deadline)), None);
+
+        assert!(sender.send_if_modified(|value| {
+            *value = 2;
+            true
+        }));
+        assert_eq!(block_on(receiver.changed(), None), Some(Ok(())));
+        assert_eq!(*receiver.borrow(), 2);
+    }
+}
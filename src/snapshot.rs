@@ -0,0 +1,150 @@
+//! Point-in-time copies of a bus's latest values.
+//!
+//! A [`BusSnapshot`] captures the name, latest value and version of every
+//! topic that holds a value, and [`Bus::restore`](crate::Bus::restore) puts
+//! them back, so consumers see warm state right after a restart. With the
+//! `serde` feature both types implement `Serialize` and `Deserialize`.
+//!
+//! ```rust
+//! use dropslot::prelude::*;
+//!
+//! let bus = Bus::<String>::new();
+//! bus.publish("status", "online".to_string());
+//! let snapshot = bus.snapshot();
+//!
+//! let restarted = Bus::<String>::new();
+//! restarted.restore(snapshot);
+//! let subscriber = restarted.subscribe("status");
+//! assert_eq!(subscriber.get_latest(), Some("online".to_string()));
+//! ```
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// The latest value of a single topic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TopicSnapshot<T> {
+    /// Name of the topic.
+    pub name: String,
+    /// Topic version the value was published at.
+    pub version: u64,
+    /// The latest published value.
+    pub value: T,
+}
+
+/// The latest values of every topic on a bus.
+///
+/// Created by [`Bus::snapshot`](crate::Bus::snapshot). Topics that never
+/// received a value are not included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BusSnapshot<T> {
+    /// Captured topics, sorted by name.
+    pub topics: Vec<TopicSnapshot<T>>,
+}
+
+impl<T> Default for BusSnapshot<T> {
+    #[inline]
+    fn default() -> Self {
+        Self { topics: Vec::new() }
+    }
+}
+
+impl<T> BusSnapshot<T> {
+    /// Returns the captured topic with this name.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&TopicSnapshot<T>> {
+        self.topics.iter().find(|topic| topic.name == name)
+    }
+
+    /// Returns the number of captured topics.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.topics.len()
+    }
+
+    /// Returns true if no topic was captured.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.topics.is_empty()
+    }
+
+    /// Writes the snapshot to `path` atomically.
+    ///
+    /// `encode` serializes the snapshot into a temporary file next to `path`,
+    /// which is synced to disk and then renamed over `path`. Readers therefore
+    /// see either the previous file or the complete new one, never a partial
+    /// write. The encoding is up to the caller, so any serde format works.
+    ///
+    /// # Errors
+    /// Returns any error from `encode` or the filesystem. The temporary file
+    /// is removed on failure.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// use std::io::Write;
+    ///
+    /// let bus = Bus::<String>::new();
+    /// bus.publish("status", "online".to_string());
+    ///
+    /// let path = std::env::temp_dir().join("dropslot-doc-snapshot.txt");
+    /// bus.snapshot()
+    ///     .write_to_file(&path, |snapshot, writer| {
+    ///         for topic in &snapshot.topics {
+    ///             writeln!(writer, "{} {} {}", topic.name, topic.version, topic.value)?;
+    ///         }
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    ///
+    /// assert_eq!(std::fs::read_to_string(&path).unwrap(), "status 1 online\n");
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn write_to_file<P, E>(&self, path: P, encode: E) -> io::Result<()>
+    where
+        P: AsRef<Path>,
+        E: FnOnce(&Self, &mut dyn Write) -> io::Result<()>,
+    {
+        write_atomic(path.as_ref(), |writer| encode(self, writer))
+    }
+}
+
+/// Writes a file through a synced temporary sibling and an atomic rename.
+pub(crate) fn write_atomic(
+    path: &Path,
+    write: impl FnOnce(&mut dyn Write) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut tmp_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no file name"))?
+        .to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = (|| {
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        write(&mut writer)?;
+        writer
+            .into_inner()
+            .map_err(|error| error.into_error())?
+            .sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+        return result;
+    }
+
+    // Persist the rename itself; directories can't be opened on every platform.
+    #[cfg(unix)]
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        File::open(parent)?.sync_all()?;
+    }
+    Ok(())
+}
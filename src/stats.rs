@@ -0,0 +1,236 @@
+//! Usage counters for buses and topics.
+//!
+//! Every topic counts its publishes and what its subscribers read, and every
+//! bus counts the topics it created and removed. [`Bus::stats`] collects them
+//! into a [`BusStats`] snapshot. With the `metrics` feature,
+//! [`BusStats::export_metrics`] hands the snapshot to whatever recorder is
+//! installed for the [`metrics`](https://docs.rs/metrics) facade.
+//!
+//! ```rust
+//! use dropslot::prelude::*;
+//!
+//! let bus = Bus::<u32>::new();
+//! let mut subscriber = bus.subscribe("ticks");
+//! bus.publish("ticks", 1);
+//! bus.publish("ticks", 2);
+//! assert_eq!(subscriber.try_get_message().unwrap(), Some(2));
+//! assert!(subscriber.try_get_message().is_err());
+//!
+//! let stats = bus.stats();
+//! let ticks = stats.get("ticks").unwrap();
+//! assert_eq!((ticks.publishes, ticks.reads), (2, 1));
+//! assert_eq!((ticks.coalesced, ticks.empty_polls), (1, 1));
+//! assert_eq!(ticks.subscribers, 1);
+//! ```
+//!
+//! [`Bus::stats`]: crate::Bus::stats
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Live counters of a single topic, shared with its subscribers.
+#[repr(align(64))]
+#[derive(Debug, Default)]
+pub(crate) struct TopicCounters {
+    publishes: AtomicU64,
+    reads: AtomicU64,
+    empty_polls: AtomicU64,
+    coalesced: AtomicU64,
+}
+
+impl TopicCounters {
+    #[inline(always)]
+    pub(crate) fn record_publish(&self) {
+        self.publishes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records `reads` messages read, after `coalesced` updates were
+    /// replaced before anyone read them.
+    #[inline(always)]
+    pub(crate) fn record_reads(&self, reads: u64, coalesced: u64) {
+        self.reads.fetch_add(reads, Ordering::Relaxed);
+        if coalesced > 0 {
+            self.coalesced.fetch_add(coalesced, Ordering::Relaxed);
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn record_empty_poll(&self) {
+        self.empty_polls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Adds every counter of `other` to this one.
+    fn absorb(&self, other: &TopicCounters) {
+        let pairs = [
+            (&self.publishes, &other.publishes),
+            (&self.reads, &other.reads),
+            (&self.empty_polls, &other.empty_polls),
+            (&self.coalesced, &other.coalesced),
+        ];
+        for (total, counter) in pairs {
+            total.fetch_add(counter.load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+    }
+}
+
+/// Bus-wide counters that outlive individual topics.
+#[derive(Debug, Default)]
+pub(crate) struct BusCounters {
+    topics_created: AtomicU64,
+    topics_removed: AtomicU64,
+    /// Totals of removed topics, so bus totals never go backwards.
+    retired: TopicCounters,
+}
+
+impl BusCounters {
+    #[inline]
+    pub(crate) fn record_created(&self) {
+        self.topics_created.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn record_removed(&self, counters: &TopicCounters) {
+        self.topics_removed.fetch_add(1, Ordering::Relaxed);
+        self.retired.absorb(counters);
+    }
+
+    /// Sums the counters of the live `topics` with those of removed ones.
+    pub(crate) fn collect(&self, mut topics: Vec<TopicStats>) -> BusStats {
+        topics.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        let mut stats = BusStats {
+            topics_created: self.topics_created.load(Ordering::Relaxed),
+            topics_removed: self.topics_removed.load(Ordering::Relaxed),
+            publishes: self.retired.publishes.load(Ordering::Relaxed),
+            reads: self.retired.reads.load(Ordering::Relaxed),
+            empty_polls: self.retired.empty_polls.load(Ordering::Relaxed),
+            coalesced: self.retired.coalesced.load(Ordering::Relaxed),
+            subscribers: 0,
+            topics: Vec::new(),
+        };
+        for topic in &topics {
+            stats.publishes += topic.publishes;
+            stats.reads += topic.reads;
+            stats.empty_polls += topic.empty_polls;
+            stats.coalesced += topic.coalesced;
+            stats.subscribers += topic.subscribers;
+        }
+        stats.topics = topics;
+        stats
+    }
+}
+
+/// Counters of a single topic.
+///
+/// Created by [`Topic::stats`](crate::Topic::stats) and
+/// [`Bus::stats`](crate::Bus::stats).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TopicStats {
+    /// Name of the topic.
+    pub name: String,
+    /// Current topic version.
+    pub version: u64,
+    /// Number of live subscribers.
+    pub subscribers: usize,
+    /// Messages published, not counting restored or replayed values.
+    pub publishes: u64,
+    /// Messages handed to subscribers.
+    pub reads: u64,
+    /// Non-blocking reads that found nothing new.
+    pub empty_polls: u64,
+    /// Updates replaced by a newer one before a subscriber read them,
+    /// summed over subscribers.
+    pub coalesced: u64,
+}
+
+impl TopicStats {
+    #[inline]
+    pub(crate) fn new(
+        name: &str,
+        version: u64,
+        subscribers: usize,
+        counters: &TopicCounters,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            version,
+            subscribers,
+            publishes: counters.publishes.load(Ordering::Relaxed),
+            reads: counters.reads.load(Ordering::Relaxed),
+            empty_polls: counters.empty_polls.load(Ordering::Relaxed),
+            coalesced: counters.coalesced.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Counters of a bus and each of its topics.
+///
+/// Created by [`Bus::stats`](crate::Bus::stats). The totals include topics
+/// that were removed since, while [`topics`](BusStats::topics) only lists
+/// the live ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BusStats {
+    /// Topics created over the bus's lifetime, including derived ones.
+    pub topics_created: u64,
+    /// Topics removed over the bus's lifetime.
+    pub topics_removed: u64,
+    /// Messages published to any topic.
+    pub publishes: u64,
+    /// Messages handed to any subscriber.
+    pub reads: u64,
+    /// Non-blocking reads that found nothing new.
+    pub empty_polls: u64,
+    /// Updates replaced by a newer one before a subscriber read them.
+    pub coalesced: u64,
+    /// Live subscribers over all topics.
+    pub subscribers: usize,
+    /// Counters of every live topic, sorted by name.
+    pub topics: Vec<TopicStats>,
+}
+
+impl BusStats {
+    /// Returns the counters of the topic with this name.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&TopicStats> {
+        self.topics
+            .binary_search_by(|topic| topic.name.as_str().cmp(name))
+            .ok()
+            .map(|index| &self.topics[index])
+    }
+
+    /// Reports these counters to the installed `metrics` recorder.
+    ///
+    /// Bus totals are exported as `dropslot_topics_created_total`,
+    /// `dropslot_topics_removed_total`, `dropslot_publishes_total`,
+    /// `dropslot_reads_total`, `dropslot_empty_polls_total`,
+    /// `dropslot_coalesced_total` and the gauges `dropslot_topics` and
+    /// `dropslot_subscribers`. Per-topic values use the names
+    /// `dropslot_topic_publishes_total`, `dropslot_topic_reads_total`,
+    /// `dropslot_topic_empty_polls_total`, `dropslot_topic_coalesced_total`
+    /// and `dropslot_topic_subscribers`, labelled with `topic`.
+    ///
+    /// Counters are set to their absolute values, so calling this
+    /// periodically, for example right before a scrape, is enough.
+    #[cfg(feature = "metrics")]
+    pub fn export_metrics(&self) {
+        use metrics::{counter, gauge};
+
+        counter!("dropslot_topics_created_total").absolute(self.topics_created);
+        counter!("dropslot_topics_removed_total").absolute(self.topics_removed);
+        counter!("dropslot_publishes_total").absolute(self.publishes);
+        counter!("dropslot_reads_total").absolute(self.reads);
+        counter!("dropslot_empty_polls_total").absolute(self.empty_polls);
+        counter!("dropslot_coalesced_total").absolute(self.coalesced);
+        gauge!("dropslot_topics").set(self.topics.len() as f64);
+        gauge!("dropslot_subscribers").set(self.subscribers as f64);
+
+        for topic in &self.topics {
+            let label = [("topic", topic.name.clone())];
+            counter!("dropslot_topic_publishes_total", &label).absolute(topic.publishes);
+            counter!("dropslot_topic_reads_total", &label).absolute(topic.reads);
+            counter!("dropslot_topic_empty_polls_total", &label).absolute(topic.empty_polls);
+            counter!("dropslot_topic_coalesced_total", &label).absolute(topic.coalesced);
+            gauge!("dropslot_topic_subscribers", &label).set(topic.subscribers as f64);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! [`Stream`] adapters for subscribers.
+//!
+//! Enabled with the `stream` feature. A [`SubStream`] yields the latest
+//! message each time the topic publishes, coalescing updates that arrive
+//! faster than the stream is polled, and ends once the topic is dropped.
+//!
+//! ```rust
+//! use dropslot::prelude::*;
+//! use futures_util::StreamExt;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let bus = Bus::<String>::new();
+//! let topic = bus.topic("events");
+//! let mut stream = topic.subscribe().into_stream();
+//!
+//! topic.publish("Hello".to_string());
+//! assert_eq!(stream.next().await, Some("Hello".to_string()));
+//! # }
+//! ```
+
+use crate::sub::{PollSub, Sub};
+use futures_core::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A [`Stream`] of the latest messages published to a topic.
+///
+/// Created by [`Sub::into_stream`].
+pub struct SubStream<T> {
+    inner: PollSub<T>,
+    topic_name: Box<str>,
+}
+
+impl<T> SubStream<T> {
+    /// Returns the name of the subscribed topic.
+    #[inline(always)]
+    pub fn topic_name(&self) -> &str {
+        &self.topic_name
+    }
+}
+
+impl<T> Stream for SubStream<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.get_mut().inner.poll_with(cx, T::clone)
+    }
+}
+
+impl<T> std::fmt::Debug for SubStream<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SubStream")
+            .field("topic_name", &self.topic_name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A [`Stream`] that applies a function to each latest message by reference.
+///
+/// Created by [`Sub::into_stream_map`].
+pub struct SubStreamMap<T, F> {
+    inner: PollSub<T>,
+    topic_name: Box<str>,
+    f: F,
+}
+
+impl<T, F> SubStreamMap<T, F> {
+    /// Returns the name of the subscribed topic.
+    #[inline(always)]
+    pub fn topic_name(&self) -> &str {
+        &self.topic_name
+    }
+}
+
+impl<T, F, R> Stream for SubStreamMap<T, F>
+where
+    T: Clone + Send + Sync + 'static,
+    F: FnMut(&T) -> R + Unpin,
+{
+    type Item = R;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<R>> {
+        let this = self.get_mut();
+        this.inner.poll_with(cx, &mut this.f)
+    }
+}
+
+impl<T, F> std::fmt::Debug for SubStreamMap<T, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SubStreamMap")
+            .field("topic_name", &self.topic_name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Sub<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    /// Converts this subscriber into a [`Stream`] of messages.
+    ///
+    /// The stream keeps latest-only semantics: if several messages are
+    /// published between two polls, only the most recent one is yielded.
+    /// It ends once the topic has been removed from its bus and dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// use futures_util::StreamExt;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let bus = Bus::<String>::new();
+    /// let topic = bus.topic("events");
+    /// let mut stream = topic.subscribe().into_stream();
+    ///
+    /// topic.publish("First".to_string());
+    /// topic.publish("Second".to_string());
+    /// assert_eq!(stream.next().await, Some("Second".to_string()));
+    ///
+    /// drop(topic);
+    /// bus.remove_topic("events");
+    /// assert_eq!(stream.next().await, None);
+    /// # }
+    /// ```
+    pub fn into_stream(self) -> SubStream<T> {
+        SubStream {
+            topic_name: self.topic_name().into(),
+            inner: PollSub::new(self),
+        }
+    }
+
+    /// Converts this subscriber into a [`Stream`] that applies `f` to each
+    /// message by reference.
+    ///
+    /// This is the streaming counterpart of
+    /// [`wait_for_message_and_apply`](Sub::wait_for_message_and_apply) and
+    /// avoids cloning the message.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// use futures_util::StreamExt;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let bus = Bus::<String>::new();
+    /// let topic = bus.topic("events");
+    /// let mut lengths = topic.subscribe().into_stream_map(|msg| msg.len());
+    ///
+    /// topic.publish("hello world".to_string());
+    /// assert_eq!(lengths.next().await, Some(11));
+    /// # }
+    /// ```
+    pub fn into_stream_map<R, F>(self, f: F) -> SubStreamMap<T, F>
+    where
+        F: FnMut(&T) -> R,
+    {
+        SubStreamMap {
+            topic_name: self.topic_name().into(),
+            inner: PollSub::new(self),
+            f,
+        }
+    }
+}
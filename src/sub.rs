@@ -1,15 +1,31 @@
-use crate::{error::BusError, topic::Topic};
+use crate::{
+    blocking,
+    envelope::{Envelope, Meta},
+    error::BusError,
+    history::{Delivery, Drained, Sequenced},
+    slot,
+    stats::TopicCounters,
+    topic::Topic,
+    ttl::Freshness,
+};
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::{Arc, Weak};
-use tokio::sync::watch;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
 /// Receives the latest messages from a topic.
 #[derive(Debug)]
 pub struct Sub<T> {
-    receiver: watch::Receiver<Option<T>>,
+    receiver: slot::Receiver<Option<T>>,
     topic_name: Box<str>,
     topic_ref: Weak<Topic<T>>,
     last_seen_version: u64,
     cached_topic: Option<Arc<Topic<T>>>,
+    received_count: u64,
+    skipped_count: u64,
+    counters: Arc<TopicCounters>,
+    freshness: Arc<Freshness>,
 }
 
 impl<T> Sub<T>
@@ -18,11 +34,13 @@ where
 {
     #[inline]
     pub(crate) fn new(
-        receiver: watch::Receiver<Option<T>>,
+        receiver: slot::Receiver<Option<T>>,
         topic_name: Box<str>,
         topic_ref: Weak<Topic<T>>,
         last_seen_version: u64,
         cached_topic: Option<Arc<Topic<T>>>,
+        counters: Arc<TopicCounters>,
+        freshness: Arc<Freshness>,
     ) -> Self {
         Self {
             receiver,
@@ -30,6 +48,10 @@ where
             topic_ref,
             last_seen_version,
             cached_topic,
+            received_count: 0,
+            skipped_count: 0,
+            counters,
+            freshness,
         }
     }
 
@@ -52,15 +74,9 @@ where
     /// # }
     /// ```
     pub async fn wait_for_message(&mut self) -> Option<T> {
-        loop {
-            if self.receiver.changed().await.is_err() {
-                return None;
-            }
-            let borrowed = self.receiver.borrow();
-            if let Some(message) = borrowed.as_ref() {
-                return Some(message.clone());
-            }
-        }
+        self.wait_for_delivery_impl(T::clone)
+            .await
+            .map(|delivery| delivery.message)
     }
 
     /// Waits for a message and applies a transformation.
@@ -88,23 +104,88 @@ where
     /// # }
     /// ```
     pub async fn wait_for_message_and_apply<R>(&mut self, f: impl FnOnce(&T) -> R) -> Option<R> {
-        loop {
-            if self.receiver.changed().await.is_err() {
-                return None;
-            }
-            let borrowed = self.receiver.borrow();
-            if let Some(message) = borrowed.as_ref() {
-                return Some(f(message));
-            }
-        }
+        self.wait_for_delivery_impl(f)
+            .await
+            .map(|delivery| delivery.message)
+    }
+
+    /// Waits for the next message and reports how it was delivered.
+    ///
+    /// The returned [`Delivery`] carries the topic version of the message and
+    /// the number of updates that were coalesced into it since the previous
+    /// read. Returns `None` once the topic is dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let bus = Bus::<String>::new();
+    /// let topic = bus.topic("prices");
+    /// let mut subscriber = topic.subscribe();
+    ///
+    /// topic.publish("100".to_string());
+    /// topic.publish("101".to_string());
+    /// topic.publish("102".to_string());
+    ///
+    /// let delivery = subscriber.wait_for_delivery().await.unwrap();
+    /// assert_eq!(delivery.message, "102");
+    /// assert_eq!(delivery.version, 3);
+    /// assert_eq!(delivery.skipped, 2);
+    /// # }
+    /// ```
+    #[inline]
+    pub async fn wait_for_delivery(&mut self) -> Option<Delivery<T>> {
+        self.wait_for_delivery_impl(T::clone).await
+    }
+
+    /// Waits for the next message and returns it with its publish details.
+    ///
+    /// Like [`wait_for_message`](Self::wait_for_message), but the message
+    /// comes in an [`Envelope`] with its version, publish time and the
+    /// headers given to [`Topic::publish_with_meta`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::{Bus, Headers};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let bus = Bus::<u32>::new();
+    /// let topic = bus.topic("jobs");
+    /// let mut subscriber = topic.subscribe();
+    ///
+    /// topic.publish_with_meta(7, Headers::new().with("attempt", "2"));
+    ///
+    /// let envelope = subscriber.wait_for_envelope().await.unwrap();
+    /// assert_eq!(envelope.message, 7);
+    /// assert_eq!(envelope.headers.get("attempt"), Some("2"));
+    /// # }
+    /// ```
+    pub async fn wait_for_envelope(&mut self) -> Option<Envelope<T>> {
+        let freshness = self.freshness.clone();
+        let delivery = self
+            .wait_for_delivery_impl(|message| stamped(message, &freshness))
+            .await?;
+        let (message, published_at, meta) = delivery.message;
+        Some(Envelope::new(message, delivery.version, published_at, meta))
     }
 
     /// Attempts to receive a message without blocking.
     ///
     /// Returns `Ok(Some(message))` if a new message is available,
     /// `Ok(None)` if no new message since last check,
-    /// `Err(BusError::message_queue_empty())` if no message available,
-    /// `Err(BusError::topic_disconnected())` if topic is dropped.
+    /// `Err(BusError::Empty { .. })` if no message available,
+    /// `Err(BusError::Disconnected { .. })` if topic is dropped.
+    /// A new message that expired before this call is reported once as
+    /// `Err(BusError::Stale { .. })`.
+    ///
+    /// Only the message is returned, which keeps this the cheapest read.
+    /// Messages dropped since the previous read are still counted in
+    /// [`skipped_count`](Sub::skipped_count). To get the drop count of this
+    /// read, use [`try_get_delivery`](Sub::try_get_delivery) and its
+    /// [`Delivery::skipped`]. On history topics, use
+    /// [`try_drain`](Sub::try_drain) to recover the missed messages
+    /// themselves.
     ///
     /// # Examples
     /// ```
@@ -155,10 +236,201 @@ where
         self.try_get_message_impl(f)
     }
 
+    /// Attempts to receive a message without blocking and reports how it was
+    /// delivered.
+    ///
+    /// Behaves like [`try_get_message`](Sub::try_get_message), but the
+    /// message comes wrapped in a [`Delivery`] with its topic version and the
+    /// number of updates skipped since the previous read.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<u32>::new();
+    /// let topic = bus.topic("ticks");
+    /// let mut subscriber = topic.subscribe();
+    ///
+    /// topic.publish(1);
+    /// assert_eq!(subscriber.try_get_delivery().unwrap().unwrap().skipped, 0);
+    ///
+    /// topic.publish(2);
+    /// topic.publish(3);
+    /// let delivery = subscriber.try_get_delivery().unwrap().unwrap();
+    /// assert_eq!((delivery.message, delivery.skipped), (3, 1));
+    /// assert_eq!(subscriber.skipped_count(), 1);
+    /// ```
+    #[inline]
+    pub fn try_get_delivery(&mut self) -> Result<Option<Delivery<T>>, BusError> {
+        self.try_get_delivery_impl(T::clone)
+    }
+
+    /// Drains every retained message published since the last read.
+    ///
+    /// On [`DeliveryMode::History`](crate::DeliveryMode::History) topics this
+    /// returns up to the topic's capacity of messages in publish order, along
+    /// with the number of older messages that were evicted before they could
+    /// be read. On latest-only topics it returns the latest message and counts
+    /// the ones it replaced as dropped.
+    ///
+    /// Returns `Err(BusError::Empty { .. })` if nothing was published
+    /// since the last read, or `Err(BusError::Disconnected { .. })` if the
+    /// topic is dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::{Bus, DeliveryMode};
+    /// let bus = Bus::<u32>::new();
+    /// let topic = bus.topic_with_mode("deltas", DeliveryMode::history(2));
+    /// let mut subscriber = topic.subscribe();
+    ///
+    /// topic.publish(1);
+    /// topic.publish(2);
+    /// topic.publish(3);
+    ///
+    /// let drained = subscriber.try_drain().unwrap();
+    /// assert_eq!(drained.dropped, 1);
+    /// let messages: Vec<_> = drained.messages.iter().map(|m| m.message).collect();
+    /// assert_eq!(messages, [2, 3]);
+    ///
+    /// assert!(subscriber.try_drain().unwrap_err().is_empty());
+    /// ```
+    pub fn try_drain(&mut self) -> Result<Drained<T>, BusError> {
+        let result = self.drain();
+        if let Err(error) = &result {
+            self.record_poll_error(error);
+        }
+        result
+    }
+
+    fn drain(&mut self) -> Result<Drained<T>, BusError> {
+        let last_seen = self.last_seen_version;
+        let Some(topic) = self.get_or_refresh_topic().cloned() else {
+            return Err(BusError::disconnected(&*self.topic_name));
+        };
+
+        // Publishers bump the version and push to the history under the
+        // slot's write lock, so both are read under the borrow to keep the
+        // drained messages in step with the version.
+        let borrowed = self.receiver.borrow();
+        let current_version = topic.get_current_version();
+        if !is_newer_version(current_version, last_seen) {
+            return Err(BusError::empty(&*self.topic_name));
+        }
+        let drained = topic
+            .drain_history(last_seen, current_version)
+            .unwrap_or_else(|| Drained {
+                messages: borrowed
+                    .iter()
+                    .map(|message| Sequenced {
+                        seq: current_version,
+                        message: message.clone(),
+                    })
+                    .collect(),
+                dropped: current_version.saturating_sub(last_seen).saturating_sub(1),
+            });
+        drop(borrowed);
+
+        self.last_seen_version = current_version;
+        self.received_count += drained.messages.len() as u64;
+        self.skipped_count += drained.dropped;
+        self.counters
+            .record_reads(drained.messages.len() as u64, drained.dropped);
+        Ok(drained)
+    }
+
+    /// Waits until something is published, then drains every retained message
+    /// since the last read.
+    ///
+    /// See [`try_drain`](Sub::try_drain) for details. Returns `None` once the
+    /// topic is dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::{Bus, DeliveryMode};
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let bus = Bus::<String>::new();
+    /// let topic = bus.topic_with_mode("audit", DeliveryMode::history(8));
+    /// let mut subscriber = topic.subscribe();
+    ///
+    /// topic.publish("login".to_string());
+    /// topic.publish("logout".to_string());
+    ///
+    /// let drained = subscriber.wait_for_drain().await.unwrap();
+    /// assert_eq!(drained.messages.len(), 2);
+    /// assert_eq!(drained.dropped, 0);
+    /// # }
+    /// ```
+    pub async fn wait_for_drain(&mut self) -> Option<Drained<T>> {
+        loop {
+            match self.drain() {
+                Ok(drained) => return Some(drained),
+                Err(error) if error.is_disconnected() => return None,
+                Err(_) => {}
+            }
+            if !self.changed().await {
+                return None;
+            }
+        }
+    }
+
+    /// Blocks the current thread until a new message is published.
+    ///
+    /// This is the synchronous counterpart of
+    /// [`wait_for_message`](Sub::wait_for_message) for threads that do not
+    /// run an async runtime. The thread is parked while waiting, not spun.
+    /// Returns `None` once the topic is dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// use std::thread;
+    ///
+    /// let bus = Bus::<String>::new();
+    /// let topic = bus.topic("frames");
+    /// let mut subscriber = topic.subscribe();
+    ///
+    /// let consumer = thread::spawn(move || subscriber.recv_blocking());
+    /// topic.publish("frame 1".to_string());
+    ///
+    /// assert_eq!(consumer.join().unwrap(), Some("frame 1".to_string()));
+    /// ```
+    pub fn recv_blocking(&mut self) -> Option<T> {
+        self.recv_blocking_impl(None).ok()
+    }
+
+    /// Blocks the current thread until a new message is published or
+    /// `timeout` elapses.
+    ///
+    /// Returns `Err(BusError::Timeout { .. })` if nothing was
+    /// published in time, or `Err(BusError::Disconnected { .. })` if the
+    /// topic is dropped. Like [`recv_blocking`](Sub::recv_blocking), it needs
+    /// no async runtime.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// use std::time::Duration;
+    ///
+    /// let bus = Bus::<u32>::new();
+    /// let topic = bus.topic("samples");
+    /// let mut subscriber = topic.subscribe();
+    ///
+    /// let result = subscriber.recv_timeout(Duration::from_millis(10));
+    /// assert!(result.unwrap_err().is_timeout());
+    ///
+    /// topic.publish(7);
+    /// assert_eq!(subscriber.recv_timeout(Duration::from_millis(10)), Ok(7));
+    /// ```
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<T, BusError> {
+        self.recv_blocking_impl(Instant::now().checked_add(timeout))
+    }
+
     /// Gets the latest message without consuming it.
     ///
     /// This method returns the most recent message published to the topic,
-    /// if any. It does not block and does not mark the message as consumed.
+    /// if any and not [stale](Sub::is_stale). It does not block and does not
+    /// mark the message as consumed.
     ///
     /// # Examples
     /// ```
@@ -174,7 +446,35 @@ where
     /// ```
     #[inline(always)]
     pub fn get_latest(&self) -> Option<T> {
-        self.receiver.borrow().clone()
+        self.get_latest_with(T::clone)
+    }
+
+    /// Gets the latest message with its publish details, without consuming it.
+    ///
+    /// Returns `None` if no message is available. See [`Envelope`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::{Bus, Headers};
+    /// let bus = Bus::<String>::new();
+    /// let topic = bus.topic("events");
+    /// let subscriber = topic.subscribe();
+    ///
+    /// topic.publish("plain".to_string());
+    /// let envelope = subscriber.get_latest_envelope().unwrap();
+    /// assert_eq!(envelope.version, 1);
+    /// assert!(envelope.headers.is_empty());
+    ///
+    /// topic.publish_with_meta("tagged".to_string(), Headers::new().with("k", "v"));
+    /// let envelope = subscriber.get_latest_envelope().unwrap();
+    /// assert_eq!(envelope.version, 2);
+    /// assert_eq!(envelope.headers.get("k"), Some("v"));
+    /// ```
+    pub fn get_latest_envelope(&self) -> Option<Envelope<T>> {
+        self.get_latest_versioned_with(|message, version| {
+            let (message, published_at, meta) = stamped(message, &self.freshness);
+            Envelope::new(message, version, published_at, meta)
+        })
     }
 
     /// Gets and transforms the latest message without consuming it.
@@ -200,9 +500,24 @@ where
     #[inline(always)]
     pub fn get_latest_with<R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
         let borrowed = self.receiver.borrow();
+        if self.freshness.is_expired() {
+            return None;
+        }
         borrowed.as_ref().map(f)
     }
 
+    /// Like [`get_latest_with`](Self::get_latest_with), but also passes the
+    /// topic version the message was published at.
+    #[inline]
+    pub(crate) fn get_latest_versioned_with<R>(&self, f: impl FnOnce(&T, u64) -> R) -> Option<R> {
+        let borrowed = self.receiver.borrow();
+        if self.freshness.is_expired() {
+            return None;
+        }
+        let version = topic_version(&self.cached_topic, &self.topic_ref, self.last_seen_version);
+        borrowed.as_ref().map(|message| f(message, version))
+    }
+
     /// Returns true if a message is currently available.
     ///
     /// This method checks if there is a message available without retrieving it.
@@ -222,7 +537,70 @@ where
     /// ```
     #[inline(always)]
     pub fn has_latest(&self) -> bool {
-        self.receiver.borrow().is_some()
+        self.receiver.borrow().is_some() && !self.freshness.is_expired()
+    }
+
+    /// Returns how long ago the latest message was published, or `None` if
+    /// nothing was published.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<u32>::new();
+    /// let topic = bus.topic("ticks");
+    /// let subscriber = topic.subscribe();
+    /// assert_eq!(subscriber.latest_age(), None);
+    ///
+    /// topic.publish(1);
+    /// assert!(subscriber.latest_age().unwrap().as_secs() < 60);
+    /// ```
+    #[inline]
+    pub fn latest_age(&self) -> Option<std::time::Duration> {
+        self.freshness.age()
+    }
+
+    /// Returns true if the latest message has outlived its time-to-live.
+    ///
+    /// See [`Topic::publish_with_ttl`] and [`Topic::set_default_ttl`].
+    #[inline]
+    pub fn is_stale(&self) -> bool {
+        self.freshness.is_expired()
+    }
+
+    /// Returns the number of messages this subscriber has received.
+    ///
+    /// Messages read with [`get_latest`](Sub::get_latest) and through streams
+    /// are not counted.
+    #[inline(always)]
+    pub fn received_count(&self) -> u64 {
+        self.received_count
+    }
+
+    /// Returns the total number of updates this subscriber skipped because
+    /// newer messages replaced them before they were read.
+    ///
+    /// A steadily growing count relative to
+    /// [`received_count`](Sub::received_count) means the consumer cannot
+    /// keep up with the publish rate.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<u32>::new();
+    /// let topic = bus.topic("ticks");
+    /// let mut subscriber = topic.subscribe();
+    ///
+    /// for tick in 0..10 {
+    ///     topic.publish(tick);
+    /// }
+    /// subscriber.try_get_message().unwrap();
+    ///
+    /// assert_eq!(subscriber.received_count(), 1);
+    /// assert_eq!(subscriber.skipped_count(), 9);
+    /// ```
+    #[inline(always)]
+    pub fn skipped_count(&self) -> u64 {
+        self.skipped_count
     }
 
     /// Returns the name of the subscribed topic.
@@ -241,25 +619,167 @@ where
         &self.topic_name
     }
 
+    /// Waits until the topic publishes again, returning `false` once it is gone.
+    ///
+    /// Waiting with a cached strong reference would keep the topic, and with
+    /// it the sender, alive forever, so the cache is released first.
+    #[inline]
+    pub(crate) async fn changed(&mut self) -> bool {
+        self.release_topic();
+        let open = self.receiver.changed().await.is_ok();
+        #[cfg(feature = "tracing")]
+        if !open {
+            self.trace_disconnected();
+        }
+        open
+    }
+
+    #[inline]
+    pub(crate) fn topic_ref(&self) -> &Weak<Topic<T>> {
+        &self.topic_ref
+    }
+
+    /// Marks the current value as unseen so the next wait returns it.
+    #[inline]
+    pub(crate) fn mark_unseen(&mut self) {
+        self.receiver.mark_changed();
+    }
+
+    /// Drops the cached strong reference so that the subscriber alone does
+    /// not keep its topic (and therefore the channel sender) alive.
+    #[inline]
+    pub(crate) fn release_topic(&mut self) {
+        self.cached_topic = None;
+    }
+
     #[inline]
     fn try_get_message_impl<R>(
         &mut self,
         transform: impl FnOnce(&T) -> R,
     ) -> Result<Option<R>, BusError> {
-        match self.get_or_refresh_topic() {
-            Some(topic) => {
-                let current_version = topic.get_current_version();
-                if current_version > self.last_seen_version
-                    || (current_version == u64::MAX && self.last_seen_version < u64::MAX)
-                {
-                    self.last_seen_version = current_version;
-                    let borrowed = self.receiver.borrow();
-                    Ok(borrowed.as_ref().map(transform))
-                } else {
-                    Err(BusError::message_queue_empty())
+        self.try_get_delivery_impl(transform)
+            .map(|delivery| delivery.map(|delivery| delivery.message))
+    }
+
+    #[inline]
+    fn try_get_delivery_impl<R>(
+        &mut self,
+        transform: impl FnOnce(&T) -> R,
+    ) -> Result<Option<Delivery<R>>, BusError> {
+        let result = self.poll_delivery(transform);
+        if let Err(error) = &result {
+            self.record_poll_error(error);
+        }
+        result
+    }
+
+    #[inline]
+    fn record_poll_error(&self, error: &BusError) {
+        if error.is_empty() {
+            self.counters.record_empty_poll();
+        } else if error.is_disconnected() {
+            #[cfg(feature = "tracing")]
+            self.trace_disconnected();
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    #[cold]
+    fn trace_disconnected(&self) {
+        tracing::debug!(
+            topic = %self.topic_name,
+            version = self.last_seen_version,
+            "topic disconnected"
+        );
+    }
+
+    /// Like [`try_get_delivery_impl`](Self::try_get_delivery_impl), without
+    /// counting an empty poll.
+    #[inline]
+    fn poll_delivery<R>(
+        &mut self,
+        transform: impl FnOnce(&T) -> R,
+    ) -> Result<Option<Delivery<R>>, BusError> {
+        if self.get_or_refresh_topic().is_none() {
+            return Err(BusError::disconnected(&*self.topic_name));
+        }
+
+        // Publishers bump the version while holding the slot's write lock, so
+        // reading it under the borrow pairs the value with its own version.
+        let borrowed = self.receiver.borrow();
+        let current_version =
+            topic_version(&self.cached_topic, &self.topic_ref, self.last_seen_version);
+        if !is_newer_version(current_version, self.last_seen_version) {
+            return Err(BusError::empty(&*self.topic_name));
+        }
+        if self.freshness.is_expired() {
+            drop(borrowed);
+            self.last_seen_version = current_version;
+            return Err(BusError::stale(&*self.topic_name));
+        }
+        let message = borrowed.as_ref().map(transform);
+        drop(borrowed);
+
+        let delivery = message.map(|message| self.record_delivery(current_version, message));
+        self.last_seen_version = current_version;
+        Ok(delivery)
+    }
+
+    async fn wait_for_delivery_impl<R>(
+        &mut self,
+        transform: impl FnOnce(&T) -> R,
+    ) -> Option<Delivery<R>> {
+        loop {
+            if !self.changed().await {
+                return None;
+            }
+            if self.receiver.borrow().is_some() && !self.freshness.is_expired() {
+                break;
+            }
+        }
+
+        let borrowed = self.receiver.borrow_and_update();
+        let current_version =
+            topic_version(&self.cached_topic, &self.topic_ref, self.last_seen_version);
+        let message = borrowed.as_ref().map(transform)?;
+        drop(borrowed);
+
+        Some(self.record_delivery(current_version, message))
+    }
+
+    fn recv_blocking_impl(&mut self, deadline: Option<Instant>) -> Result<T, BusError> {
+        loop {
+            // Waiting is not polling, so misses here are not counted.
+            match self.poll_delivery(T::clone) {
+                Ok(Some(delivery)) => return Ok(delivery.message),
+                Err(error) if error.is_disconnected() => {
+                    #[cfg(feature = "tracing")]
+                    self.trace_disconnected();
+                    return Err(error);
                 }
+                _ => {}
+            }
+            match blocking::block_on(self.changed(), deadline) {
+                Some(true) => {}
+                Some(false) => return Err(BusError::disconnected(&*self.topic_name)),
+                None => return Err(BusError::timeout(&*self.topic_name)),
             }
-            None => Err(BusError::topic_disconnected()),
+        }
+    }
+
+    #[inline]
+    fn record_delivery<R>(&mut self, version: u64, message: R) -> Delivery<R> {
+        let skipped = version
+            .saturating_sub(self.last_seen_version)
+            .saturating_sub(1);
+        self.last_seen_version = self.last_seen_version.max(version);
+        self.received_count += 1;
+        self.skipped_count += skipped;
+        self.counters.record_reads(1, skipped);
+        Delivery {
+            message,
+            version,
+            skipped,
         }
     }
 
@@ -289,12 +809,145 @@ where
     }
 }
 
+/// Reads the topic version without caching a strong reference, so a waiting
+/// subscriber never keeps its topic alive. Falls back to `last_seen` once the
+/// topic is gone.
+#[inline]
+fn topic_version<T: Clone>(
+    cached: &Option<Arc<Topic<T>>>,
+    topic_ref: &Weak<Topic<T>>,
+    last_seen: u64,
+) -> u64 {
+    match cached {
+        Some(topic) => topic.get_current_version(),
+        None => topic_ref
+            .upgrade()
+            .map_or(last_seen, |topic| topic.get_current_version()),
+    }
+}
+
+/// Clones `message` with the publish stamps of the value it was read from;
+/// must be called under the borrow of that value.
+#[inline]
+fn stamped<T: Clone>(message: &T, freshness: &Freshness) -> (T, Instant, Option<Arc<Meta>>) {
+    let published_at = freshness.published_at().unwrap_or_else(Instant::now);
+    (message.clone(), published_at, freshness.meta())
+}
+
+/// Returns true if `current` is ahead of `last_seen`, treating a saturated
+/// counter as always new for subscribers that haven't reached it yet.
+#[inline(always)]
+fn is_newer_version(current: u64, last_seen: u64) -> bool {
+    current > last_seen || (current == u64::MAX && last_seen < u64::MAX)
+}
+
+type PendingChange<T> = Pin<Box<dyn Future<Output = (bool, Sub<T>)> + Send>>;
+
+enum PollState<T> {
+    Idle(Sub<T>),
+    Waiting(PendingChange<T>),
+    Closed,
+}
+
+/// An owned subscriber that can be driven from `poll`-based code such as
+/// streams and multi-topic subscribers.
+///
+/// The subscriber does not keep its topic alive, so polling ends once the
+/// topic is dropped.
+pub(crate) struct PollSub<T> {
+    state: PollState<T>,
+    topic_ref: Weak<Topic<T>>,
+}
+
+impl<T> PollSub<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    #[inline]
+    pub(crate) fn new(mut sub: Sub<T>) -> Self {
+        sub.release_topic();
+        Self {
+            topic_ref: sub.topic_ref.clone(),
+            state: PollState::Idle(sub),
+        }
+    }
+
+    /// Returns true if this subscriber belongs to the given topic instance.
+    #[inline]
+    pub(crate) fn is_same_topic(&self, topic_ref: &Weak<Topic<T>>) -> bool {
+        self.topic_ref.ptr_eq(topic_ref)
+    }
+
+    /// Polls until a message is available, then hands it to `f` by reference.
+    /// Returns `None` once the topic is gone.
+    #[cfg(feature = "stream")]
+    #[inline]
+    pub(crate) fn poll_with<R>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut f: impl FnMut(&T) -> R,
+    ) -> Poll<Option<R>> {
+        self.poll_versioned_with(cx, |message, _| f(message))
+    }
+
+    /// Like [`poll_with`](Self::poll_with), but also passes the message's
+    /// topic version to `f`.
+    pub(crate) fn poll_versioned_with<R>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut f: impl FnMut(&T, u64) -> R,
+    ) -> Poll<Option<R>> {
+        loop {
+            match std::mem::replace(&mut self.state, PollState::Closed) {
+                PollState::Idle(mut sub) => {
+                    self.state = PollState::Waiting(Box::pin(async move {
+                        let open = sub.changed().await;
+                        (open, sub)
+                    }));
+                }
+                PollState::Waiting(mut pending) => match pending.as_mut().poll(cx) {
+                    Poll::Ready((true, mut sub)) => {
+                        let result = sub.get_latest_versioned_with(|message, version| {
+                            (f(message, version), version)
+                        });
+                        if let Some((result, version)) = result {
+                            sub.record_delivery(version, ());
+                            self.state = PollState::Idle(sub);
+                            return Poll::Ready(Some(result));
+                        }
+                        self.state = PollState::Idle(sub);
+                    }
+                    Poll::Ready((false, _)) => return Poll::Ready(None),
+                    Poll::Pending => {
+                        self.state = PollState::Waiting(pending);
+                        return Poll::Pending;
+                    }
+                },
+                PollState::Closed => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
 impl<T> PartialEq for Sub<T> {
     fn eq(&self, other: &Self) -> bool {
         self.topic_name == other.topic_name
     }
 }
 
+impl<T> Drop for Sub<T> {
+    fn drop(&mut self) {
+        match &self.cached_topic {
+            Some(topic) => topic.unsubscribed(),
+            None => {
+                if let Some(topic) = self.topic_ref.upgrade() {
+                    topic.unsubscribed();
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -318,7 +971,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_wait_for_message_with_disconnected_topic() {
-        let (sender, receiver) = watch::channel(None::<String>);
+        let sender = slot::Sender::new(None::<String>);
+        let receiver = sender.subscribe();
         let topic = Arc::new(Topic::<String>::new("test".to_string()));
         let weak_topic = Arc::downgrade(&topic);
 
@@ -328,6 +982,8 @@ mod tests {
             weak_topic,
             0,
             None,
+            Arc::default(),
+            Arc::default(),
         );
 
         drop(sender);
@@ -343,7 +999,7 @@ mod tests {
     fn test_get_or_refresh_topic_with_dead_weak_ref() {
         let topic = Arc::new(Topic::<String>::new("test".to_string()));
         let weak_topic = Arc::downgrade(&topic);
-        let (_, receiver) = watch::channel(None::<String>);
+        let receiver = slot::Sender::new(None::<String>).subscribe();
 
         let mut subscriber = crate::sub::Sub::new(
             receiver,
@@ -351,6 +1007,8 @@ mod tests {
             weak_topic,
             0,
             None,
+            Arc::default(),
+            Arc::default(),
         );
 
         drop(topic);
@@ -363,7 +1021,7 @@ mod tests {
     fn test_cached_topic_clear_on_low_count() {
         let topic = Arc::new(Topic::<String>::new("test".to_string()));
         let weak_topic = Arc::downgrade(&topic);
-        let (_, receiver) = watch::channel(None::<String>);
+        let receiver = slot::Sender::new(None::<String>).subscribe();
 
         let mut subscriber = crate::sub::Sub::new(
             receiver,
@@ -371,6 +1029,8 @@ mod tests {
             weak_topic,
             0,
             Some(topic.clone()),
+            Arc::default(),
+            Arc::default(),
         );
 
         drop(topic);
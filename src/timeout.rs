@@ -0,0 +1,109 @@
+//! Async receive with a deadline, backed by tokio's timer.
+//!
+//! Enabled with the `tokio` feature. The rest of the crate is runtime-neutral;
+//! only these methods need a tokio runtime with the time driver enabled.
+//!
+//! ```rust
+//! use dropslot::prelude::*;
+//! use std::time::Duration;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let bus = Bus::<String>::new();
+//! let topic = bus.topic("events");
+//! let mut subscriber = topic.subscribe();
+//!
+//! let result = subscriber.wait_for_message_timeout(Duration::from_millis(10)).await;
+//! assert!(result.unwrap_err().is_timeout());
+//! # }
+//! ```
+
+use crate::{bus::Bus, error::BusError, history::Delivery, sub::Sub};
+use std::time::Duration;
+
+impl<T> Sub<T>
+where
+    T: Clone,
+{
+    /// Waits for the next message for at most `timeout`.
+    ///
+    /// Returns `Err(BusError::Timeout { .. })` if nothing was
+    /// published in time, with tokio's elapsed error as its source, or
+    /// `Err(BusError::Disconnected { .. })` if the topic is dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// use std::time::Duration;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let bus = Bus::<u32>::new();
+    /// let topic = bus.topic("samples");
+    /// let mut subscriber = topic.subscribe();
+    ///
+    /// topic.publish(7);
+    /// let message = subscriber.wait_for_message_timeout(Duration::from_secs(1)).await;
+    /// assert_eq!(message, Ok(7));
+    /// # }
+    /// ```
+    pub async fn wait_for_message_timeout(&mut self, timeout: Duration) -> Result<T, BusError> {
+        self.wait_for_delivery_timeout(timeout)
+            .await
+            .map(|delivery| delivery.message)
+    }
+
+    /// Waits for the next message for at most `timeout` and reports how it
+    /// was delivered.
+    ///
+    /// See [`wait_for_delivery`](Sub::wait_for_delivery) and
+    /// [`wait_for_message_timeout`](Sub::wait_for_message_timeout).
+    pub async fn wait_for_delivery_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<Delivery<T>, BusError> {
+        match tokio::time::timeout(timeout, self.wait_for_delivery()).await {
+            Ok(Some(delivery)) => Ok(delivery),
+            Ok(None) => Err(BusError::disconnected(self.topic_name())),
+            Err(elapsed) => Err(BusError::timeout(self.topic_name()).with_source(elapsed)),
+        }
+    }
+}
+
+impl<T> Bus<T>
+where
+    T: Clone,
+{
+    /// Sends a request like [`request`](Bus::request), waiting at most
+    /// `timeout` for the reply.
+    ///
+    /// Returns `Err(BusError::Timeout { .. })` if no reply arrived in time,
+    /// with tokio's elapsed error as its source; the request is then
+    /// withdrawn.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// use std::time::Duration;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let bus = Bus::<u32>::new();
+    /// let _idle = bus.responder("slow");
+    ///
+    /// let result = bus.request_timeout("slow", 1, Duration::from_millis(10)).await;
+    /// assert!(result.unwrap_err().is_timeout());
+    /// # }
+    /// ```
+    pub async fn request_timeout(
+        &self,
+        topic: &str,
+        message: T,
+        timeout: Duration,
+    ) -> Result<T, BusError> {
+        match tokio::time::timeout(timeout, self.request(topic, message)).await {
+            Ok(result) => result,
+            Err(elapsed) => Err(BusError::timeout(topic).with_source(elapsed)),
+        }
+    }
+}
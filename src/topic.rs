@@ -1,27 +1,107 @@
+use crate::config::TopicConfig;
+use crate::derived::DerivedHook;
+use crate::envelope::{Headers, Meta};
+use crate::error::BusError;
+use crate::history::{DeliveryMode, Drained, HistoryBuffer};
+use crate::lifecycle::{Lifecycle, LifecycleEvent};
+use crate::slot;
+use crate::stats::{TopicCounters, TopicStats};
 use crate::sub::Sub;
-use std::sync::Arc;
-use tokio::sync::watch;
+use crate::ttl::Freshness;
+use crate::wal::TopicLog;
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError, RwLock, Weak};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Subscriber count kept for lifecycle events and reaping, and where to
+/// report them.
+///
+/// Counted under one lock so that a topic's first-subscriber and
+/// last-subscriber-gone events are reported in the order they happened.
+#[derive(Debug, Default)]
+struct Presence {
+    subscribers: usize,
+    unsubscribed_since: Option<Instant>,
+    lifecycle: Option<Arc<Lifecycle>>,
+}
 
 /// A message topic that delivers only the latest published message to subscribers.
 #[repr(align(64))]
 #[derive(Debug)]
 pub struct Topic<T> {
-    sender: watch::Sender<Option<T>>,
+    sender: slot::Sender<Option<T>>,
     name: Box<str>,
     version: std::sync::atomic::AtomicU64,
+    mode: DeliveryMode,
+    history: Option<Mutex<HistoryBuffer<T>>>,
+    sources: Box<[Arc<str>]>,
+    hooks: RwLock<Vec<DerivedHook<T>>>,
+    hook_count: AtomicUsize,
+    log: OnceLock<Arc<TopicLog<T>>>,
+    counters: Arc<TopicCounters>,
+    freshness: Arc<Freshness>,
+    dedup: AtomicBool,
+    dedup_eq: OnceLock<fn(&T, &T) -> bool>,
+    presence: Mutex<Presence>,
+    created_at: Instant,
+    pinned: AtomicBool,
+    admission_limit: AtomicUsize,
+    config: TopicConfig<T>,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
 }
 
 impl<T> Topic<T>
 where
     T: Clone,
 {
-    #[inline]
+    #[cfg(test)]
     pub(crate) fn new(name: String) -> Self {
-        let (sender, _receiver) = watch::channel(None);
+        Self::with_mode(name, DeliveryMode::Latest)
+    }
+
+    #[inline]
+    pub(crate) fn with_mode(name: String, mode: DeliveryMode) -> Self {
+        let sender = slot::Sender::new(None);
+        let created_at = Instant::now();
+        let history = match mode {
+            DeliveryMode::Latest => None,
+            DeliveryMode::History(capacity) => Some(Mutex::new(HistoryBuffer::new(capacity))),
+        };
         Self {
             sender,
+            #[cfg(feature = "tracing")]
+            span: tracing::debug_span!("topic", name = %name),
             name: name.into_boxed_str(),
             version: std::sync::atomic::AtomicU64::new(0),
+            mode,
+            history,
+            sources: Box::default(),
+            hooks: RwLock::new(Vec::new()),
+            hook_count: AtomicUsize::new(0),
+            log: OnceLock::new(),
+            counters: Arc::default(),
+            freshness: Arc::default(),
+            dedup: AtomicBool::new(false),
+            dedup_eq: OnceLock::new(),
+            presence: Mutex::new(Presence {
+                unsubscribed_since: Some(created_at),
+                ..Presence::default()
+            }),
+            created_at,
+            pinned: AtomicBool::new(false),
+            admission_limit: AtomicUsize::new(usize::MAX),
+            config: TopicConfig::new().mode(mode),
+        }
+    }
+
+    /// Creates a latest-only topic whose value is derived from `sources`.
+    #[inline]
+    pub(crate) fn derived(name: String, sources: Box<[Arc<str>]>) -> Self {
+        Self {
+            sources,
+            ..Self::with_mode(name, DeliveryMode::Latest)
         }
     }
 
@@ -37,8 +117,137 @@ where
     /// ```
     #[inline(always)]
     pub fn publish(&self, message: T) {
-        let _ = self.sender.send(Some(message));
-        self.increment_version();
+        self.publish_stamped(message, None, None);
+    }
+
+    /// Publishes a message that expires after `ttl`, overriding the topic's
+    /// [default time-to-live](Topic::set_default_ttl).
+    ///
+    /// Once expired, subscribers treat the value as if nothing was published:
+    /// [`Sub::get_latest`] returns `None`, waits skip it, and
+    /// [`Sub::try_get_message`] reports it as [`BusError::Stale`] once.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// use std::time::Duration;
+    ///
+    /// let bus = Bus::<f64>::new();
+    /// let topic = bus.topic("sensors/temp");
+    /// let subscriber = topic.subscribe();
+    ///
+    /// topic.publish_with_ttl(21.5, Duration::from_secs(60));
+    /// assert_eq!(subscriber.get_latest(), Some(21.5));
+    ///
+    /// topic.publish_with_ttl(22.0, Duration::ZERO);
+    /// assert_eq!(subscriber.get_latest(), None);
+    /// assert!(subscriber.is_stale());
+    /// ```
+    #[inline]
+    pub fn publish_with_ttl(&self, message: T, ttl: Duration) {
+        self.publish_stamped(message, Some(ttl), None);
+    }
+
+    /// Publishes a message along with `headers`, recording the wall-clock
+    /// time of the publish.
+    ///
+    /// Subscribers read the message, its version, publish time and headers
+    /// with [`Sub::get_latest_envelope`] and [`Sub::wait_for_envelope`]; the
+    /// plain message APIs see an ordinary publish. Derived topics receive the
+    /// message without the headers.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::{Bus, Headers};
+    /// let bus = Bus::<String>::new();
+    /// let topic = bus.topic("config");
+    /// let subscriber = topic.subscribe();
+    ///
+    /// let headers = Headers::new().with("origin", "deployer").with("trace", "7f3a");
+    /// topic.publish_with_meta("v2".to_string(), headers);
+    ///
+    /// assert_eq!(subscriber.get_latest(), Some("v2".to_string()));
+    /// let envelope = subscriber.get_latest_envelope().unwrap();
+    /// assert_eq!(envelope.headers.get("trace"), Some("7f3a"));
+    /// ```
+    #[inline]
+    pub fn publish_with_meta(&self, message: T, headers: Headers) {
+        self.publish_stamped(message, None, Some(headers));
+    }
+
+    /// Publishes a message only if the topic is still at version `expected`,
+    /// and returns the new version.
+    ///
+    /// Version 0 means nothing was published yet. Checking and publishing
+    /// happen atomically, so concurrent writers can build optimistic
+    /// concurrency on this: read the value and its version (for example with
+    /// [`Sub::get_latest_envelope`]), compute the update, and retry if
+    /// another writer got there first.
+    ///
+    /// # Errors
+    /// Returns [`BusError::Conflict`] with the current version if the topic
+    /// has moved on.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<u32>::new();
+    /// let topic = bus.topic("counter");
+    ///
+    /// assert_eq!(topic.publish_if_version(0, 1), Ok(1));
+    /// let error = topic.publish_if_version(0, 5).unwrap_err();
+    /// assert!(error.is_conflict());
+    /// assert_eq!(topic.publish_if_version(1, 2), Ok(2));
+    /// ```
+    pub fn publish_if_version(&self, expected: u64, message: T) -> Result<u64, BusError> {
+        self.store_if(None, None, |_, current| {
+            (current == expected).then_some(message)
+        })
+        .map_err(|found| BusError::conflict(&*self.name, expected, found))
+    }
+
+    /// Atomically replaces the latest message with one computed from it.
+    ///
+    /// `f` gets the latest message, or `None` if nothing was published or
+    /// the latest message expired, and returns the message to publish, or
+    /// `None` to leave the topic as it is. Returns the new version if a
+    /// message was published.
+    ///
+    /// `f` runs while the topic is locked for writing, so it must not read
+    /// from or publish to this topic.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<u32>::new();
+    /// let topic = bus.topic("hits");
+    ///
+    /// assert_eq!(topic.update(|old| Some(old.map_or(1, |n| n + 1))), Some(1));
+    /// assert_eq!(topic.update(|old| Some(old.map_or(1, |n| n + 1))), Some(2));
+    /// assert_eq!(topic.update(|old| old.filter(|&&n| n > 5).copied()), None);
+    /// assert_eq!(topic.version(), 2);
+    /// ```
+    pub fn update(&self, f: impl FnOnce(Option<&T>) -> Option<T>) -> Option<u64> {
+        self.store_if(None, None, |latest, _| f(latest)).ok()
+    }
+
+    #[inline(always)]
+    fn publish_stamped(&self, message: T, ttl: Option<Duration>, headers: Option<Headers>) {
+        if self.dedup.load(Ordering::Acquire) {
+            if let Some(&eq) = self.dedup_eq.get() {
+                self.store_unless_equal(message, ttl, headers, eq);
+                return;
+            }
+        }
+        // Derived topics publish from the hooks, so their events nest here.
+        #[cfg(feature = "tracing")]
+        let _entered = self.span.enter();
+        if self.hook_count.load(Ordering::Acquire) == 0 {
+            self.store(message, ttl, headers);
+        } else {
+            self.store(message.clone(), ttl, headers);
+            self.run_hooks(&message);
+        }
     }
 
     /// Creates a new subscriber for this topic.
@@ -54,16 +263,68 @@ where
     /// let topic = bus.topic("events");
     /// let mut subscriber = topic.subscribe();
     /// ```
+    ///
+    /// This ignores the topic's admission limit; use
+    /// [`try_subscribe`](Self::try_subscribe) to respect it.
     #[inline]
     pub fn subscribe(self: &Arc<Self>) -> Sub<T> {
+        self.subscribe_counted(self.presence())
+    }
+
+    /// Creates a new subscriber unless the topic already has as many as
+    /// its [admission limit](Self::set_admission_limit) allows.
+    ///
+    /// # Errors
+    /// Returns [`BusError::CapacityExceeded`] if the topic is full.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<String>::new();
+    /// let topic = bus.topic("jobs");
+    /// topic.set_admission_limit(Some(1));
+    ///
+    /// let worker = topic.try_subscribe().unwrap();
+    /// assert!(topic.try_subscribe().unwrap_err().is_capacity_exceeded());
+    /// drop(worker);
+    /// assert!(topic.try_subscribe().is_ok());
+    /// ```
+    pub fn try_subscribe(self: &Arc<Self>) -> Result<Sub<T>, BusError> {
+        let presence = self.presence();
+        if let Some(limit) = self.admission_limit() {
+            if presence.subscribers >= limit {
+                return Err(BusError::capacity_exceeded(self.name(), limit));
+            }
+        }
+        Ok(self.subscribe_counted(presence))
+    }
+
+    fn subscribe_counted(self: &Arc<Self>, mut presence: MutexGuard<'_, Presence>) -> Sub<T> {
         let current_version = self.get_current_version();
-        Sub::new(
+        let sub = Sub::new(
             self.sender.subscribe(),
             self.name.clone(),
             Arc::downgrade(self),
             current_version,
             Some(self.clone()),
-        )
+            self.counters.clone(),
+            self.freshness.clone(),
+        );
+        presence.subscribers += 1;
+        presence.unsubscribed_since = None;
+        if let (1, Some(lifecycle)) = (presence.subscribers, &presence.lifecycle) {
+            lifecycle.emit(|| LifecycleEvent::FirstSubscriber(self.name().into()));
+        }
+        drop(presence);
+        #[cfg(feature = "tracing")]
+        self.span.in_scope(|| {
+            tracing::debug!(
+                version = current_version,
+                subscribers = self.subscriber_count(),
+                "subscribed"
+            );
+        });
+        sub
     }
 
     /// Returns the number of active subscribers.
@@ -122,6 +383,566 @@ where
         self.sender.receiver_count() > 0
     }
 
+    /// Returns how this topic retains published messages.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::{Bus, DeliveryMode};
+    /// let bus = Bus::<String>::new();
+    /// let topic = bus.topic_with_mode("audit", DeliveryMode::history(16));
+    ///
+    /// assert_eq!(topic.delivery_mode().capacity(), 16);
+    /// assert_eq!(bus.topic("events").delivery_mode(), DeliveryMode::Latest);
+    /// ```
+    #[inline(always)]
+    pub fn delivery_mode(&self) -> DeliveryMode {
+        self.mode
+    }
+
+    /// Returns true if this topic was created by a derived-topic operator such
+    /// as [`Bus::map`](crate::Bus::map).
+    ///
+    /// Derived topics are updated by the bus whenever their sources publish.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<i32>::new();
+    /// let doubled = bus.map("values", "values/doubled", |v| v * 2).unwrap();
+    ///
+    /// assert!(doubled.is_derived());
+    /// assert!(!bus.topic("values").is_derived());
+    /// ```
+    #[inline(always)]
+    pub fn is_derived(&self) -> bool {
+        !self.sources.is_empty()
+    }
+
+    /// Returns true if every publish to this topic is appended to a
+    /// [`TopicLog`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// # use dropslot::wal::{BytesCodec, TopicLog};
+    /// # use std::sync::Arc;
+    /// # let dir = std::env::temp_dir().join("dropslot-doc-is-durable");
+    /// let log = Arc::new(TopicLog::open(&dir, BytesCodec).unwrap());
+    /// let bus = Bus::new();
+    ///
+    /// assert!(bus.durable_topic("orders", &log).is_durable());
+    /// assert!(!bus.topic("quotes").is_durable());
+    /// # drop(log);
+    /// # std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    #[inline]
+    pub fn is_durable(&self) -> bool {
+        self.log.get().is_some()
+    }
+
+    /// Publishes a message only once it has been appended to the topic's
+    /// [`TopicLog`], and returns its version.
+    ///
+    /// [`publish`](Self::publish) also appends before subscribers can see a
+    /// message, but keeps append errors for [`TopicLog::flush`]. This returns
+    /// them instead, leaving the topic unchanged. Deduplication set with
+    /// [`TopicConfig`] is not applied.
+    ///
+    /// # Errors
+    /// Returns the append error, or an error of kind
+    /// [`Unsupported`](io::ErrorKind::Unsupported) if the topic is not
+    /// durable.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// # use dropslot::wal::{BytesCodec, TopicLog};
+    /// # use bytes::Bytes;
+    /// # use std::sync::Arc;
+    /// # let dir = std::env::temp_dir().join("dropslot-doc-publish-durable");
+    /// let log = Arc::new(TopicLog::open(&dir, BytesCodec).unwrap());
+    /// let bus = Bus::<Bytes>::new();
+    ///
+    /// let orders = bus.durable_topic("orders", &log);
+    /// assert_eq!(orders.publish_durable(Bytes::from("buy")).unwrap(), 1);
+    /// assert!(bus.topic("quotes").publish_durable(Bytes::new()).is_err());
+    /// # drop(log);
+    /// # std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub fn publish_durable(&self, message: T) -> io::Result<u64> {
+        let Some(log) = self.log.get() else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("topic {} is not durable", self.name),
+            ));
+        };
+        #[cfg(feature = "tracing")]
+        let _entered = self.span.enter();
+        let hooked = self.hook_count.load(Ordering::Acquire) != 0;
+        let mut outcome = Ok(0);
+        let mut published = None;
+        self.sender.send_if_modified(|slot| {
+            // Every version bump happens under this lock, so this is the
+            // version the message is published at.
+            let version = self.get_current_version().saturating_add(1);
+            if let Err(error) = log.try_append(&self.name, version, &message) {
+                outcome = Err(error);
+                return false;
+            }
+            if hooked {
+                published = Some(message.clone());
+            }
+            self.increment_version();
+            self.fill(slot, version, message, None, None);
+            outcome = Ok(version);
+            true
+        });
+        let version = outcome?;
+        self.counters.record_publish();
+        #[cfg(feature = "tracing")]
+        tracing::trace!(version, subscribers = self.subscriber_count(), "published");
+        if let Some(message) = published {
+            self.run_hooks(&message);
+        }
+        Ok(version)
+    }
+
+    /// Returns the names of the topics this topic is derived from.
+    ///
+    /// Empty for regular topics.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<i32>::new();
+    /// let sum = bus.combine_latest(&["a", "b"], "sum", |v| v.iter().sum()).unwrap();
+    ///
+    /// assert_eq!(sum.sources(), ["a", "b"]);
+    /// ```
+    #[inline]
+    pub fn sources(&self) -> Vec<String> {
+        self.sources
+            .iter()
+            .map(|source| source.to_string())
+            .collect()
+    }
+
+    /// Sets the time-to-live of values published without one, or `None` to
+    /// keep them until replaced.
+    ///
+    /// Applies to later publishes only.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// use std::time::Duration;
+    ///
+    /// let bus = Bus::<u64>::new();
+    /// let heartbeat = bus.topic("heartbeat");
+    /// heartbeat.set_default_ttl(Some(Duration::from_secs(5)));
+    /// assert_eq!(heartbeat.default_ttl(), Some(Duration::from_secs(5)));
+    /// ```
+    #[inline]
+    pub fn set_default_ttl(&self, ttl: Option<Duration>) {
+        self.freshness.set_default_ttl(ttl);
+    }
+
+    /// Returns the time-to-live of values published without one.
+    #[inline]
+    pub fn default_ttl(&self) -> Option<Duration> {
+        self.freshness.default_ttl()
+    }
+
+    /// Returns true if publishes skip messages equal to the latest one.
+    ///
+    /// See [`set_dedup`](Self::set_dedup).
+    #[inline]
+    pub fn dedups(&self) -> bool {
+        self.dedup.load(Ordering::Relaxed)
+    }
+
+    /// Turns on deduplication with `eq`, which is `T::eq` of a `PartialEq`
+    /// message type.
+    pub(crate) fn enable_dedup(&self, eq: fn(&T, &T) -> bool) {
+        self.dedup_eq.get_or_init(|| eq);
+        self.dedup.store(true, Ordering::Release);
+    }
+
+    /// Sets the most subscribers [`try_subscribe`](Self::try_subscribe)
+    /// admits, or `None` for no limit.
+    ///
+    /// This is an admission limit for `try_subscribe` only:
+    /// [`subscribe`](Self::subscribe) and the subscribing methods of [`Bus`]
+    /// other than [`Bus::try_subscribe`] always succeed and still count
+    /// towards it.
+    ///
+    /// [`Bus`]: crate::Bus
+    /// [`Bus::try_subscribe`]: crate::Bus::try_subscribe
+    #[inline]
+    pub fn set_admission_limit(&self, limit: Option<usize>) {
+        self.admission_limit
+            .store(limit.unwrap_or(usize::MAX), Ordering::Relaxed);
+    }
+
+    /// Returns the admission limit. See
+    /// [`set_admission_limit`](Self::set_admission_limit).
+    #[inline]
+    pub fn admission_limit(&self) -> Option<usize> {
+        match self.admission_limit.load(Ordering::Relaxed) {
+            usize::MAX => None,
+            limit => Some(limit),
+        }
+    }
+
+    /// Returns the config the topic was created with, regardless of changes
+    /// made to it since.
+    #[inline]
+    pub(crate) fn config(&self) -> &TopicConfig<T> {
+        &self.config
+    }
+
+    #[inline]
+    pub(crate) fn set_config(&mut self, config: TopicConfig<T>) {
+        self.config = config;
+    }
+
+    /// Sets whether this topic is kept by [`Bus::cleanup_unused_topics`]
+    /// and [`Bus::reap`], whatever the policy.
+    ///
+    /// [`Bus::cleanup_unused_topics`]: crate::Bus::cleanup_unused_topics
+    /// [`Bus::reap`]: crate::Bus::reap
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<String>::new();
+    /// let topic = bus.topic("config");
+    /// topic.set_pinned(true);
+    /// assert!(topic.is_pinned());
+    /// ```
+    #[inline]
+    pub fn set_pinned(&self, pinned: bool) {
+        self.pinned.store(pinned, Ordering::Relaxed);
+    }
+
+    /// Returns true if the topic is pinned. See [`set_pinned`](Self::set_pinned).
+    #[inline]
+    pub fn is_pinned(&self) -> bool {
+        self.pinned.load(Ordering::Relaxed)
+    }
+
+    /// Returns how long ago the latest value was published, or `None` if
+    /// nothing was published.
+    ///
+    /// Restored values count as published when they were restored.
+    #[inline]
+    pub fn latest_age(&self) -> Option<Duration> {
+        self.freshness.age()
+    }
+
+    /// Returns true if the latest value has outlived its time-to-live.
+    #[inline]
+    pub fn is_stale(&self) -> bool {
+        self.freshness.is_expired()
+    }
+
+    /// Returns the usage counters of this topic.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<u32>::new();
+    /// let topic = bus.topic("ticks");
+    /// let _subscriber = topic.subscribe();
+    /// topic.publish(1);
+    ///
+    /// let stats = topic.stats();
+    /// assert_eq!((stats.publishes, stats.subscribers), (1, 1));
+    /// ```
+    #[inline]
+    pub fn stats(&self) -> TopicStats {
+        TopicStats::new(
+            &self.name,
+            self.get_current_version(),
+            self.subscriber_count(),
+            &self.counters,
+        )
+    }
+
+    #[inline(always)]
+    pub(crate) fn counters(&self) -> &TopicCounters {
+        &self.counters
+    }
+
+    #[cfg(feature = "tracing")]
+    #[inline(always)]
+    pub(crate) fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+
+    #[inline]
+    pub(crate) fn source_names(&self) -> &[Arc<str>] {
+        &self.sources
+    }
+
+    /// Returns a clone of the latest published message.
+    #[inline]
+    pub(crate) fn latest(&self) -> Option<T> {
+        self.sender.borrow().clone()
+    }
+
+    /// Returns a clone of the latest message together with its version.
+    #[inline]
+    pub(crate) fn latest_with_version(&self) -> Option<(T, u64)> {
+        // Publishers bump the version under the slot's write lock, so reading
+        // it under the borrow pairs the value with its own version.
+        let latest = self.sender.borrow();
+        let version = self.get_current_version();
+        latest.clone().map(|message| (message, version))
+    }
+
+    /// Replaces the latest message and version with previously captured ones,
+    /// unless the topic has already moved past `version`. Returns whether the
+    /// message was restored.
+    ///
+    /// Derived-topic hooks are not run, since their targets are restored too.
+    pub(crate) fn restore(&self, message: T, version: u64) -> bool {
+        let restored = self.sender.send_if_modified(|slot| {
+            if version < self.get_current_version() {
+                return false;
+            }
+            self.version
+                .store(version, std::sync::atomic::Ordering::Relaxed);
+            self.freshness.stamp(None);
+            if let Some(history) = &self.history {
+                history
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .push(version, message.clone());
+            }
+            *slot = Some(message);
+            true
+        });
+        #[cfg(feature = "tracing")]
+        self.span.in_scope(|| {
+            if restored {
+                tracing::debug!(version, "restored");
+            } else {
+                tracing::debug!(version, "restore skipped, topic is newer");
+            }
+        });
+        restored
+    }
+
+    /// Moves the version past `floor` if it is behind, so that later
+    /// publishes are numbered after it. A value the topic holds counts as
+    /// one publish after `floor`.
+    fn advance_version(&self, floor: u64) {
+        self.sender.send_if_modified(|slot| {
+            if self.get_current_version() < floor {
+                let version = floor + u64::from(slot.is_some());
+                self.version
+                    .store(version, std::sync::atomic::Ordering::Relaxed);
+            }
+            false
+        });
+    }
+
+    /// Registers a hook that runs after every publish to this topic.
+    pub(crate) fn add_hook(&self, hook: DerivedHook<T>) {
+        let mut hooks = self.hooks.write().unwrap_or_else(PoisonError::into_inner);
+        hooks.push(hook);
+        self.update_hook_count(hooks.len());
+    }
+
+    /// Appends every future publish to `log`, and the current value too if
+    /// `append_current` is set. Returns `false` if the topic is already
+    /// attached to a log.
+    pub(crate) fn attach_log(&self, log: &Arc<TopicLog<T>>, append_current: bool) -> bool {
+        if self.log.set(log.clone()).is_err() {
+            return false;
+        }
+        if let Some(logged) = log.latest_version(&self.name) {
+            self.advance_version(logged);
+        }
+        if let Some((message, version)) = self.latest_with_version().filter(|_| append_current) {
+            log.append(&self.name, version, &message);
+        }
+        true
+    }
+
+    /// Removes hooks feeding `target`, along with any whose target is gone.
+    pub(crate) fn remove_hooks_for(&self, target: &Weak<Topic<T>>) {
+        self.retain_hooks(|hook| !hook.feeds(target));
+    }
+
+    /// Removes every hook registered on this topic, returning whether any of
+    /// them still fed a derived topic.
+    pub(crate) fn clear_hooks(&self) -> bool {
+        let mut fed = false;
+        self.retain_hooks(|_| {
+            fed = true;
+            false
+        });
+        fed
+    }
+
+    fn retain_hooks(&self, mut keep: impl FnMut(&DerivedHook<T>) -> bool) {
+        let mut hooks = self.hooks.write().unwrap_or_else(PoisonError::into_inner);
+        hooks.retain(|hook| hook.is_alive() && keep(hook));
+        self.update_hook_count(hooks.len());
+    }
+
+    #[inline]
+    fn update_hook_count(&self, hooks: usize) {
+        self.hook_count.store(hooks, Ordering::Release);
+    }
+
+    /// Stores `message` and returns the version it was published at.
+    #[inline(always)]
+    fn store(&self, message: T, ttl: Option<Duration>, headers: Option<Headers>) -> u64 {
+        let mut version = 0;
+        self.counters.record_publish();
+        // The version is bumped while the channel is locked so that readers
+        // never observe a new version without the matching message.
+        self.sender.send_modify(|slot| {
+            version = self.write(slot, message, ttl, headers);
+        });
+        #[cfg(feature = "tracing")]
+        tracing::trace!(version, subscribers = self.subscriber_count(), "published");
+        version
+    }
+
+    /// Stores the message `produce` returns for the latest unexpired message
+    /// and the current version, deciding and writing under one lock.
+    /// Returns the new version, or the current one as the error if `produce`
+    /// returns `None`.
+    fn store_if(
+        &self,
+        ttl: Option<Duration>,
+        headers: Option<Headers>,
+        produce: impl FnOnce(Option<&T>, u64) -> Option<T>,
+    ) -> Result<u64, u64> {
+        #[cfg(feature = "tracing")]
+        let _entered = self.span.enter();
+        let hooked = self.hook_count.load(Ordering::Acquire) != 0;
+        let mut outcome = Err(0);
+        let mut published = None;
+        self.sender.send_if_modified(|slot| {
+            let current = self.get_current_version();
+            let latest = slot.as_ref().filter(|_| !self.freshness.is_expired());
+            let Some(message) = produce(latest, current) else {
+                outcome = Err(current);
+                return false;
+            };
+            if hooked {
+                published = Some(message.clone());
+            }
+            outcome = Ok(self.write(slot, message, ttl, headers));
+            true
+        });
+        let version = outcome?;
+        self.counters.record_publish();
+        #[cfg(feature = "tracing")]
+        tracing::trace!(version, subscribers = self.subscriber_count(), "published");
+        if let Some(message) = published {
+            self.run_hooks(&message);
+        }
+        Ok(version)
+    }
+
+    /// Stores `message` unless it equals the latest unexpired message, and
+    /// returns whether it was stored.
+    fn store_unless_equal(
+        &self,
+        message: T,
+        ttl: Option<Duration>,
+        headers: Option<Headers>,
+        eq: fn(&T, &T) -> bool,
+    ) -> bool {
+        self.store_if(ttl, headers, |latest, _| match latest {
+            Some(latest) if eq(latest, &message) => None,
+            _ => Some(message),
+        })
+        .is_ok()
+    }
+
+    /// Writes `message` into the locked slot and returns its version.
+    ///
+    /// The message is appended to the durable log, if any, before the slot
+    /// holds it, so subscribers never see a publish the log is missing.
+    #[inline(always)]
+    fn write(
+        &self,
+        slot: &mut Option<T>,
+        message: T,
+        ttl: Option<Duration>,
+        headers: Option<Headers>,
+    ) -> u64 {
+        self.increment_version();
+        let version = self.get_current_version();
+        if let Some(log) = self.log.get() {
+            log.append(&self.name, version, &message);
+        }
+        self.fill(slot, version, message, ttl, headers);
+        version
+    }
+
+    /// Stamps and stores `message`, already numbered `version`, in the
+    /// locked slot.
+    #[inline(always)]
+    fn fill(
+        &self,
+        slot: &mut Option<T>,
+        version: u64,
+        message: T,
+        ttl: Option<Duration>,
+        headers: Option<Headers>,
+    ) {
+        self.freshness.stamp(ttl);
+        if let Some(headers) = headers {
+            self.freshness.stamp_meta(Meta {
+                version,
+                timestamp: SystemTime::now(),
+                headers: Arc::new(headers),
+            });
+        }
+        if let Some(history) = &self.history {
+            history
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(version, message.clone());
+        }
+        *slot = Some(message);
+    }
+
+    fn run_hooks(&self, message: &T) {
+        let mut stale = false;
+        for hook in self
+            .hooks
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+        {
+            stale |= !hook.run(message);
+        }
+        if stale {
+            self.retain_hooks(|_| true);
+        }
+    }
+
+    /// Returns retained messages published after `last_seen` up to `latest`,
+    /// or `None` for latest-only topics.
+    #[inline]
+    pub(crate) fn drain_history(&self, last_seen: u64, latest: u64) -> Option<Drained<T>> {
+        self.history.as_ref().map(|history| {
+            history
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .drain_since(last_seen, latest)
+        })
+    }
+
     #[inline(always)]
     pub(crate) fn increment_version(&self) {
         let mut current = self.version.load(std::sync::atomic::Ordering::Relaxed);
@@ -142,6 +963,24 @@ where
         }
     }
 
+    /// Returns the version of the latest publish, or 0 if nothing was
+    /// published.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<u32>::new();
+    /// let topic = bus.topic("t");
+    /// assert_eq!(topic.version(), 0);
+    ///
+    /// topic.publish(7);
+    /// assert_eq!(topic.version(), 1);
+    /// ```
+    #[inline(always)]
+    pub fn version(&self) -> u64 {
+        self.get_current_version()
+    }
+
     #[inline(always)]
     pub(crate) fn get_current_version(&self) -> u64 {
         self.version.load(std::sync::atomic::Ordering::Relaxed)
@@ -154,6 +993,102 @@ where
     }
 }
 
+impl<T> Topic<T> {
+    fn presence(&self) -> MutexGuard<'_, Presence> {
+        self.presence.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Reports lifecycle events of this topic to `lifecycle`.
+    pub(crate) fn attach_lifecycle(&self, lifecycle: Arc<Lifecycle>) {
+        self.presence().lifecycle = Some(lifecycle);
+    }
+
+    /// Stops reporting lifecycle events of this topic.
+    pub(crate) fn detach_lifecycle(&self) {
+        self.presence().lifecycle = None;
+    }
+
+    /// Returns the time since the last publish, or since creation if there
+    /// was none.
+    pub(crate) fn idle_for(&self) -> Duration {
+        self.freshness
+            .age()
+            .unwrap_or_else(|| self.created_at.elapsed())
+    }
+
+    /// Returns how long the topic has been without subscribers, or `None`
+    /// if it has any.
+    pub(crate) fn unsubscribed_for(&self) -> Option<Duration> {
+        self.presence()
+            .unsubscribed_since
+            .map(|since| since.elapsed())
+    }
+
+    /// Returns true if the topic holds a value that has not expired.
+    pub(crate) fn retains_value(&self) -> bool {
+        self.sender.borrow().is_some() && !self.freshness.is_expired()
+    }
+
+    /// Called when one of this topic's subscribers is dropped.
+    pub(crate) fn unsubscribed(&self) {
+        let mut presence = self.presence();
+        presence.subscribers = presence.subscribers.saturating_sub(1);
+        if presence.subscribers == 0 {
+            presence.unsubscribed_since = Some(Instant::now());
+        }
+        if let (0, Some(lifecycle)) = (presence.subscribers, &presence.lifecycle) {
+            lifecycle.emit(|| LifecycleEvent::LastSubscriberGone(self.name.as_ref().into()));
+        }
+    }
+}
+
+impl<T> Topic<T>
+where
+    T: Clone + PartialEq,
+{
+    /// Publishes a message unless it equals the latest one, and returns
+    /// whether it was published.
+    ///
+    /// A skipped publish neither bumps the version nor wakes subscribers.
+    /// An expired latest message counts as no message, so an equal message
+    /// is published again once the previous one went stale.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<String>::new();
+    /// let topic = bus.topic("config");
+    ///
+    /// assert!(topic.publish_if_changed("v1".to_string()));
+    /// assert!(!topic.publish_if_changed("v1".to_string()));
+    /// assert!(topic.publish_if_changed("v2".to_string()));
+    /// assert_eq!(topic.version(), 2);
+    /// ```
+    #[inline]
+    pub fn publish_if_changed(&self, message: T) -> bool {
+        self.store_unless_equal(message, None, None, T::eq)
+    }
+
+    /// Sets whether every publish to this topic skips messages equal to the
+    /// latest one, as [`publish_if_changed`](Self::publish_if_changed) does.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// let bus = Bus::<u32>::new();
+    /// let topic = bus.topic("config");
+    /// topic.set_dedup(true);
+    ///
+    /// topic.publish(1);
+    /// topic.publish(1);
+    /// assert_eq!(topic.version(), 1);
+    /// ```
+    pub fn set_dedup(&self, enabled: bool) {
+        self.dedup_eq.get_or_init(|| T::eq);
+        self.dedup.store(enabled, Ordering::Release);
+    }
+}
+
 /// Zero-copy bytes operations for Topics.
 impl Topic<bytes::Bytes> {
     /// Publishes a byte slice to this topic.
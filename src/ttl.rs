@@ -0,0 +1,146 @@
+//! Publish timestamps and expiry of latest values.
+//!
+//! A topic stamps every value with the time it was published and, if the
+//! publish or the topic carries a time-to-live, the time it expires. The
+//! stamps are shared with subscribers, which treat an expired value as if
+//! nothing was published. Publishes with metadata also leave their
+//! [`Meta`] here for subscribers to read alongside the value.
+
+use crate::envelope::Meta;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+
+#[inline]
+fn epoch() -> Instant {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    *EPOCH.get_or_init(Instant::now)
+}
+
+/// Returns the nanoseconds elapsed since a process-wide epoch, never 0.
+#[inline]
+fn now() -> u64 {
+    let elapsed = epoch().elapsed();
+    u64::try_from(elapsed.as_nanos())
+        .unwrap_or(u64::MAX)
+        .saturating_add(1)
+}
+
+#[inline]
+pub(crate) fn nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
+/// Timestamps of a topic's latest value, shared with its subscribers.
+///
+/// Stamps are written while the topic's slot is locked for writing, so
+/// readers holding a borrow of the slot see the stamps of that value. A
+/// field holding 0 is unset.
+#[derive(Debug, Default)]
+pub(crate) struct Freshness {
+    published_at: AtomicU64,
+    expires_at: AtomicU64,
+    default_ttl: AtomicU64,
+    meta: Mutex<Option<Arc<Meta>>>,
+}
+
+impl Freshness {
+    /// Stamps a value published now that lives for `ttl`, or for the
+    /// default time-to-live if `None`.
+    #[inline]
+    pub(crate) fn stamp(&self, ttl: Option<Duration>) {
+        let published_at = now();
+        let ttl = match ttl {
+            Some(ttl) => Some(nanos(ttl)),
+            None => Some(self.default_ttl.load(Ordering::Relaxed)).filter(|&ttl| ttl > 0),
+        };
+        let expires_at = ttl.map_or(0, |ttl| published_at.saturating_add(ttl));
+        self.published_at.store(published_at, Ordering::Relaxed);
+        self.expires_at.store(expires_at, Ordering::Relaxed);
+    }
+
+    /// Returns true if the latest value has outlived its time-to-live.
+    #[inline(always)]
+    pub(crate) fn is_expired(&self) -> bool {
+        match self.expires_at.load(Ordering::Relaxed) {
+            0 => false,
+            expires_at => now() >= expires_at,
+        }
+    }
+
+    /// Returns the time since the latest value was published.
+    #[inline]
+    pub(crate) fn age(&self) -> Option<Duration> {
+        match self.published_at.load(Ordering::Relaxed) {
+            0 => None,
+            published_at => Some(Duration::from_nanos(now().saturating_sub(published_at))),
+        }
+    }
+
+    /// Returns when the latest value was published.
+    #[inline]
+    pub(crate) fn published_at(&self) -> Option<Instant> {
+        match self.published_at.load(Ordering::Relaxed) {
+            0 => None,
+            published_at => Some(epoch() + Duration::from_nanos(published_at - 1)),
+        }
+    }
+
+    /// Records the metadata of the value being published. Plain publishes
+    /// leave the previous metadata in place; its version tells it apart.
+    #[inline]
+    pub(crate) fn stamp_meta(&self, meta: Meta) {
+        *self.meta.lock().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(meta));
+    }
+
+    /// Returns the metadata of the last publish that had any.
+    #[inline]
+    pub(crate) fn meta(&self) -> Option<Arc<Meta>> {
+        self.meta
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    #[inline]
+    pub(crate) fn default_ttl(&self) -> Option<Duration> {
+        match self.default_ttl.load(Ordering::Relaxed) {
+            0 => None,
+            ttl => Some(Duration::from_nanos(ttl)),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn set_default_ttl(&self, ttl: Option<Duration>) {
+        let ttl = ttl.map_or(0, |ttl| nanos(ttl).max(1));
+        self.default_ttl.store(ttl, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stamp_and_expiry() {
+        let freshness = Freshness::default();
+        assert_eq!(freshness.age(), None);
+        assert!(!freshness.is_expired());
+
+        freshness.stamp(None);
+        assert!(freshness.age().is_some());
+        assert!(freshness.published_at().unwrap() <= Instant::now());
+        assert!(!freshness.is_expired());
+
+        freshness.stamp(Some(Duration::ZERO));
+        assert!(freshness.is_expired());
+
+        freshness.set_default_ttl(Some(Duration::ZERO));
+        assert_eq!(freshness.default_ttl(), Some(Duration::from_nanos(1)));
+        freshness.stamp(Some(Duration::from_secs(60)));
+        assert!(!freshness.is_expired());
+        freshness.set_default_ttl(None);
+        freshness.stamp(None);
+        assert!(!freshness.is_expired());
+    }
+}
@@ -0,0 +1,320 @@
+//! A bus whose topics each carry their own message type.
+//!
+//! Topics are declared with a [`TopicKey`] that ties a name to a message
+//! type, so lookups are checked at compile time. The bus stores topics
+//! type-erased and reports a [`BusError::TypeMismatch`] if the same name is
+//! used with two different types.
+//!
+//! ```rust
+//! use dropslot::typed::{TopicKey, TypedBus};
+//!
+//! const PRICE: TopicKey<f64> = TopicKey::new("price");
+//! const STATUS: TopicKey<String> = TopicKey::new("status");
+//!
+//! let bus = TypedBus::new();
+//! let mut prices = bus.subscribe(&PRICE).unwrap();
+//!
+//! bus.publish(&PRICE, 42.5).unwrap();
+//! bus.publish(&STATUS, "open".to_string()).unwrap();
+//!
+//! assert_eq!(prices.try_get_message().unwrap(), Some(42.5));
+//! ```
+
+use crate::{error::BusError, history::DeliveryMode, sub::Sub, topic::Topic};
+use ahash::AHasher;
+use dashmap::DashMap;
+use std::any::Any;
+use std::hash::BuildHasherDefault;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// A topic name bound to the type of messages it carries.
+///
+/// Keys are usually declared as constants and shared between publishers and
+/// subscribers.
+///
+/// # Examples
+/// ```
+/// # use dropslot::typed::TopicKey;
+/// const TEMPERATURE: TopicKey<f32> = TopicKey::new("sensors/temperature");
+/// assert_eq!(TEMPERATURE.name(), "sensors/temperature");
+/// ```
+pub struct TopicKey<T> {
+    name: &'static str,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> TopicKey<T> {
+    /// Creates a key for the topic `name` carrying messages of type `T`.
+    #[inline]
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the topic name.
+    #[inline(always)]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<T> Clone for TopicKey<T> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for TopicKey<T> {}
+
+impl<T> std::fmt::Debug for TopicKey<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TopicKey")
+            .field("name", &self.name)
+            .field("type", &std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+/// Type-erased view of a `Topic<T>`.
+trait ErasedTopic: Send + Sync {
+    fn subscriber_count(&self) -> usize;
+
+    fn is_pinned(&self) -> bool;
+
+    fn type_name(&self) -> &'static str;
+
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+}
+
+impl<T> ErasedTopic for Topic<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    #[inline]
+    fn subscriber_count(&self) -> usize {
+        Topic::subscriber_count(self)
+    }
+
+    #[inline]
+    fn is_pinned(&self) -> bool {
+        Topic::is_pinned(self)
+    }
+
+    #[inline]
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    #[inline]
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+}
+
+/// Publish-subscribe broker whose topics may carry different message types.
+///
+/// Every topic is accessed through a [`TopicKey<T>`]; the topic is created
+/// on first use and afterwards only accepts keys of the same type.
+pub struct TypedBus {
+    topics: DashMap<Arc<str>, Arc<dyn ErasedTopic>, BuildHasherDefault<AHasher>>,
+}
+
+impl Default for TypedBus {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TypedBus {
+    /// Creates a new typed bus.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_capacity(16)
+    }
+
+    /// Creates a typed bus with the specified initial topic capacity.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::typed::TypedBus;
+    /// let bus = TypedBus::with_capacity(32);
+    /// assert_eq!(bus.topic_count(), 0);
+    /// ```
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            topics: DashMap::with_capacity_and_hasher(
+                capacity,
+                BuildHasherDefault::<AHasher>::default(),
+            ),
+        }
+    }
+
+    /// Gets the topic for `key`, creating it if it doesn't exist.
+    ///
+    /// # Errors
+    /// Returns `Err(BusError::type_mismatch(..))` if a topic with the same
+    /// name was created with a different message type.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::typed::{TopicKey, TypedBus};
+    /// const COUNT: TopicKey<u64> = TopicKey::new("count");
+    /// const COUNT_TEXT: TopicKey<String> = TopicKey::new("count");
+    ///
+    /// let bus = TypedBus::new();
+    /// let topic = bus.topic(&COUNT).unwrap();
+    /// topic.publish(1);
+    ///
+    /// let error = bus.topic(&COUNT_TEXT).unwrap_err();
+    /// assert!(error.is_type_mismatch());
+    /// ```
+    #[inline]
+    pub fn topic<T>(&self, key: &TopicKey<T>) -> Result<Arc<Topic<T>>, BusError>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.topic_with_mode(key, DeliveryMode::Latest)
+    }
+
+    /// Gets the topic for `key`, creating it with the given delivery mode if
+    /// it doesn't exist.
+    ///
+    /// The mode only applies when the topic is created.
+    ///
+    /// # Errors
+    /// Returns `Err(BusError::type_mismatch(..))` if a topic with the same
+    /// name was created with a different message type.
+    pub fn topic_with_mode<T>(
+        &self,
+        key: &TopicKey<T>,
+        mode: DeliveryMode,
+    ) -> Result<Arc<Topic<T>>, BusError>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        let erased = match self.topics.get(key.name()) {
+            Some(entry) => entry.value().clone(),
+            None => self
+                .topics
+                .entry(key.name().into())
+                .or_insert_with(|| Arc::new(Topic::<T>::with_mode(key.name().to_string(), mode)))
+                .value()
+                .clone(),
+        };
+
+        let found = erased.type_name();
+        erased
+            .into_any()
+            .downcast::<Topic<T>>()
+            .map_err(|_| BusError::type_mismatch(key.name(), std::any::type_name::<T>(), found))
+    }
+
+    /// Publishes a message to the topic for `key`.
+    ///
+    /// # Errors
+    /// Returns `Err(BusError::type_mismatch(..))` if the topic carries a
+    /// different message type.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::typed::{TopicKey, TypedBus};
+    /// const EVENTS: TopicKey<String> = TopicKey::new("events");
+    ///
+    /// let bus = TypedBus::new();
+    /// bus.publish(&EVENTS, "started".to_string()).unwrap();
+    /// ```
+    #[inline]
+    pub fn publish<T>(&self, key: &TopicKey<T>, message: T) -> Result<(), BusError>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.topic(key)?.publish(message);
+        Ok(())
+    }
+
+    /// Creates a subscriber for the topic for `key`.
+    ///
+    /// # Errors
+    /// Returns `Err(BusError::type_mismatch(..))` if the topic carries a
+    /// different message type.
+    #[inline]
+    pub fn subscribe<T>(&self, key: &TopicKey<T>) -> Result<Sub<T>, BusError>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        Ok(self.topic(key)?.subscribe())
+    }
+
+    /// Returns the number of topics.
+    #[inline]
+    pub fn topic_count(&self) -> usize {
+        self.topics.len()
+    }
+
+    /// Returns all topic names.
+    #[inline]
+    pub fn topic_names(&self) -> Vec<String> {
+        self.topics
+            .iter()
+            .map(|entry| entry.key().to_string())
+            .collect()
+    }
+
+    /// Returns the message type name of the topic `name`, if it exists.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::typed::{TopicKey, TypedBus};
+    /// const FLAG: TopicKey<bool> = TopicKey::new("flag");
+    ///
+    /// let bus = TypedBus::new();
+    /// bus.topic(&FLAG).unwrap();
+    /// assert_eq!(bus.topic_type_name("flag"), Some("bool"));
+    /// assert_eq!(bus.topic_type_name("missing"), None);
+    /// ```
+    #[inline]
+    pub fn topic_type_name(&self, name: &str) -> Option<&'static str> {
+        self.topics.get(name).map(|entry| entry.value().type_name())
+    }
+
+    /// Removes a topic, whatever its message type.
+    ///
+    /// Returns the number of subscribers the topic had, or `None` if it
+    /// didn't exist. Removing a topic frees its name for another type.
+    #[inline]
+    pub fn remove_topic(&self, name: &str) -> Option<usize> {
+        self.topics
+            .remove(name)
+            .map(|(_, topic)| topic.subscriber_count())
+    }
+
+    /// Removes topics with no active subscribers, except pinned ones.
+    ///
+    /// Returns the number of topics that were removed.
+    pub fn cleanup_unused_topics(&self) -> usize {
+        let mut removed_count: usize = 0;
+        self.topics.retain(|_, topic| {
+            if topic.subscriber_count() == 0 && !topic.is_pinned() {
+                removed_count = removed_count.saturating_add(1);
+                false
+            } else {
+                true
+            }
+        });
+        removed_count
+    }
+}
+
+impl std::fmt::Debug for TypedBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TypedBus")
+            .field("topics", &self.topics.len())
+            .finish()
+    }
+}
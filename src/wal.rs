@@ -0,0 +1,558 @@
+//! Write-ahead log persistence for selected topics.
+//!
+//! A [`TopicLog`] appends a record of topic name, version and encoded payload
+//! for every publish to a durable topic, spread over numbered segment files
+//! in one directory. The record is written before subscribers can see the
+//! publish; [`Topic::publish_durable`] also reports a failed append to the
+//! caller. Because topics only keep their latest value,
+//! [`compact`](TopicLog::compact) can shrink the log to one record per topic,
+//! and [`Bus::replay`] rebuilds every topic's latest value and version from
+//! it after a restart.
+//!
+//! ```rust
+//! use dropslot::prelude::*;
+//! use dropslot::wal::{BytesCodec, TopicLog};
+//! use bytes::Bytes;
+//! use std::sync::Arc;
+//!
+//! # let dir = std::env::temp_dir().join("dropslot-doc-wal");
+//! let log = Arc::new(TopicLog::open(&dir, BytesCodec).unwrap());
+//! let bus = Bus::<Bytes>::new();
+//! bus.durable_topic("orders", &log).publish(Bytes::from("buy"));
+//! log.flush().unwrap();
+//! drop((bus, log));
+//!
+//! // After a restart
+//! let log = Arc::new(TopicLog::open(&dir, BytesCodec).unwrap());
+//! let bus = Bus::<Bytes>::new();
+//! assert_eq!(bus.replay(&log).unwrap(), 1);
+//! assert_eq!(bus.subscribe("orders").get_latest(), Some(Bytes::from("buy")));
+//! # drop(log);
+//! # std::fs::remove_dir_all(&dir).unwrap();
+//! ```
+
+use crate::{
+    bus::Bus,
+    snapshot::{self, BusSnapshot, TopicSnapshot},
+    topic::Topic,
+};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+const SEGMENT_EXTENSION: &str = "log";
+const HEADER_LEN: usize = 8;
+const BODY_PREFIX_LEN: usize = 12;
+
+/// Converts messages to and from the bytes stored in a [`TopicLog`].
+///
+/// Implement this for any serialization format, for example with serde.
+pub trait Codec<T>: Send + Sync {
+    /// Appends the encoded form of `message` to `buf`.
+    fn encode(&self, message: &T, buf: &mut Vec<u8>) -> io::Result<()>;
+
+    /// Decodes a message previously written by [`encode`](Codec::encode).
+    fn decode(&self, bytes: &[u8]) -> io::Result<T>;
+}
+
+/// Stores [`bytes::Bytes`] messages as-is.
+#[cfg(feature = "bytes")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BytesCodec;
+
+#[cfg(feature = "bytes")]
+impl Codec<bytes::Bytes> for BytesCodec {
+    #[inline]
+    fn encode(&self, message: &bytes::Bytes, buf: &mut Vec<u8>) -> io::Result<()> {
+        buf.extend_from_slice(message);
+        Ok(())
+    }
+
+    #[inline]
+    fn decode(&self, bytes: &[u8]) -> io::Result<bytes::Bytes> {
+        Ok(bytes::Bytes::copy_from_slice(bytes))
+    }
+}
+
+/// Tuning for a [`TopicLog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogOptions {
+    /// Size after which appends move on to a new segment file.
+    pub max_segment_bytes: u64,
+    /// Sync every record to disk before `publish` returns.
+    ///
+    /// Without it, records survive a process crash but may be lost on power
+    /// failure unless [`TopicLog::flush`] was called.
+    pub sync_on_append: bool,
+}
+
+impl Default for LogOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_segment_bytes: 64 * 1024 * 1024,
+            sync_on_append: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Writer {
+    file: File,
+    segment: u64,
+    len: u64,
+    /// First append error since the last flush; plain publishes can't
+    /// return it.
+    error: Option<io::Error>,
+    /// Highest version logged per topic, which topics attached later
+    /// continue from.
+    versions: HashMap<String, u64>,
+}
+
+/// A segmented append-only log of topic publishes.
+///
+/// Topics are attached with [`Bus::durable_topic`]. Appends from all attached
+/// topics are serialized through one writer, so a log is best shared by the
+/// topics of one bus.
+pub struct TopicLog<T> {
+    dir: PathBuf,
+    options: LogOptions,
+    codec: Box<dyn Codec<T>>,
+    writer: Mutex<Writer>,
+}
+
+impl<T> TopicLog<T> {
+    /// Opens the log in `dir` with default options, creating it if needed.
+    ///
+    /// # Errors
+    /// Returns any filesystem error.
+    #[inline]
+    pub fn open(dir: impl AsRef<Path>, codec: impl Codec<T> + 'static) -> io::Result<Self> {
+        Self::open_with(dir, codec, LogOptions::default())
+    }
+
+    /// Opens the log in `dir` with the given options, creating it if needed.
+    ///
+    /// A record left incomplete by a crash at the end of the newest segment
+    /// is cut off so new appends follow the last complete record.
+    ///
+    /// # Errors
+    /// Returns any filesystem error.
+    pub fn open_with(
+        dir: impl AsRef<Path>,
+        codec: impl Codec<T> + 'static,
+        options: LogOptions,
+    ) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let segments = list_segments(&dir)?;
+        let segment = segments.last().copied().unwrap_or(0);
+        let mut versions = HashMap::new();
+        let mut len = 0;
+        for &number in &segments {
+            let (records, valid) = read_records(&fs::read(segment_path(&dir, number))?);
+            for record in records {
+                let logged = versions.entry(record.topic).or_insert(0);
+                *logged = record.version.max(*logged);
+            }
+            len = valid;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .truncate(false)
+            .open(segment_path(&dir, segment))?;
+        file.set_len(len)?;
+
+        Ok(Self {
+            dir,
+            options,
+            codec: Box::new(codec),
+            writer: Mutex::new(Writer {
+                file,
+                segment,
+                len,
+                error: None,
+                versions,
+            }),
+        })
+    }
+
+    /// Returns the directory holding the segment files.
+    #[inline]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the options the log was opened with.
+    #[inline]
+    pub fn options(&self) -> LogOptions {
+        self.options
+    }
+
+    /// Returns the number of segment files.
+    ///
+    /// # Errors
+    /// Returns any filesystem error.
+    pub fn segment_count(&self) -> io::Result<usize> {
+        let _writer = self.lock();
+        Ok(list_segments(&self.dir)?.len())
+    }
+
+    /// Syncs all records to disk.
+    ///
+    /// # Errors
+    /// Returns the first error from an append since the previous flush, if
+    /// any, or an error from syncing.
+    pub fn flush(&self) -> io::Result<()> {
+        let mut writer = self.lock();
+        if let Some(error) = writer.error.take() {
+            return Err(error);
+        }
+        writer.file.sync_data()
+    }
+
+    /// Decodes the latest record of every topic in the log.
+    ///
+    /// # Errors
+    /// Returns any filesystem or decoding error.
+    pub fn snapshot(&self) -> io::Result<BusSnapshot<T>> {
+        let latest = {
+            let _writer = self.lock();
+            self.latest_records()?
+        };
+        let mut topics = latest
+            .into_values()
+            .map(|record| {
+                Ok(TopicSnapshot {
+                    value: self.codec.decode(&record.payload)?,
+                    name: record.topic,
+                    version: record.version,
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        topics.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        Ok(BusSnapshot { topics })
+    }
+
+    /// Rewrites the log to hold only the latest record of every topic and
+    /// returns the number of records kept.
+    ///
+    /// The compacted records are written to a new segment before the old
+    /// segments are deleted, so a crash part-way through loses nothing.
+    /// Publishes to durable topics wait while compaction runs.
+    ///
+    /// # Errors
+    /// Returns any filesystem error.
+    pub fn compact(&self) -> io::Result<usize> {
+        let mut writer = self.lock();
+        let mut records: Vec<_> = self.latest_records()?.into_values().collect();
+        records.sort_unstable_by(|a, b| a.topic.cmp(&b.topic));
+
+        let segment = writer.segment + 1;
+        let path = segment_path(&self.dir, segment);
+        snapshot::write_atomic(&path, |out| {
+            let mut buf = Vec::new();
+            for record in &records {
+                buf.clear();
+                encode_record(&mut buf, &record.topic, record.version, |payload| {
+                    payload.extend_from_slice(&record.payload);
+                    Ok(())
+                })?;
+                out.write_all(&buf)?;
+            }
+            Ok(())
+        })?;
+
+        for old in list_segments(&self.dir)? {
+            if old < segment {
+                fs::remove_file(segment_path(&self.dir, old))?;
+            }
+        }
+        let file = OpenOptions::new().append(true).open(&path)?;
+        writer.len = file.metadata()?.len();
+        writer.file = file;
+        writer.segment = segment;
+        Ok(records.len())
+    }
+
+    /// Appends a publish, keeping any error for the next [`flush`](Self::flush).
+    pub(crate) fn append(&self, topic: &str, version: u64, message: &T) {
+        if let Err(error) = self.try_append(topic, version, message) {
+            self.lock().error.get_or_insert(error);
+        }
+    }
+
+    /// Appends a publish, returning any error.
+    pub(crate) fn try_append(&self, topic: &str, version: u64, message: &T) -> io::Result<()> {
+        let mut buf = Vec::new();
+        encode_record(&mut buf, topic, version, |payload| {
+            self.codec.encode(message, payload)
+        })?;
+
+        let mut writer = self.lock();
+        self.write_record(&mut writer, &buf)?;
+        match writer.versions.get_mut(topic) {
+            Some(logged) => *logged = version.max(*logged),
+            None => {
+                writer.versions.insert(topic.to_string(), version);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the highest version logged for `topic`.
+    pub(crate) fn latest_version(&self, topic: &str) -> Option<u64> {
+        self.lock().versions.get(topic).copied()
+    }
+
+    fn write_record(&self, writer: &mut Writer, record: &[u8]) -> io::Result<()> {
+        let len = record.len() as u64;
+        if writer.len > 0 && writer.len + len > self.options.max_segment_bytes {
+            let segment = writer.segment + 1;
+            writer.file.sync_data()?;
+            writer.file = OpenOptions::new()
+                .create_new(true)
+                .append(true)
+                .open(segment_path(&self.dir, segment))?;
+            writer.segment = segment;
+            writer.len = 0;
+        }
+
+        writer.file.write_all(record)?;
+        writer.len += len;
+        if self.options.sync_on_append {
+            writer.file.sync_data()?;
+        }
+        Ok(())
+    }
+
+    /// Reads every segment and keeps the newest record per topic.
+    fn latest_records(&self) -> io::Result<HashMap<String, Record>> {
+        let mut latest: HashMap<String, Record> = HashMap::new();
+        for segment in list_segments(&self.dir)? {
+            let (records, _) = read_records(&fs::read(segment_path(&self.dir, segment))?);
+            for record in records {
+                match latest.get(&record.topic) {
+                    Some(existing) if existing.version > record.version => {}
+                    _ => {
+                        latest.insert(record.topic.clone(), record);
+                    }
+                }
+            }
+        }
+        Ok(latest)
+    }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, Writer> {
+        self.writer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> std::fmt::Debug for TopicLog<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TopicLog")
+            .field("dir", &self.dir)
+            .field("options", &self.options)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Bus<T>
+where
+    T: Clone,
+{
+    /// Gets or creates a topic and appends every publish to it to `log`.
+    ///
+    /// If the topic already holds a value, that value is appended right away.
+    /// A topic is attached to at most one log; attaching it again has no
+    /// effect.
+    ///
+    /// A topic behind the highest version `log` holds for its name, such as
+    /// one removed and created again, continues from that version, so that
+    /// [`replay`](Bus::replay) restores its newer publishes.
+    ///
+    /// # Examples
+    /// ```
+    /// # use dropslot::Bus;
+    /// use dropslot::wal::{BytesCodec, TopicLog};
+    /// use bytes::Bytes;
+    /// use std::sync::Arc;
+    ///
+    /// # let dir = std::env::temp_dir().join("dropslot-doc-durable-topic");
+    /// let log = Arc::new(TopicLog::open(&dir, BytesCodec).unwrap());
+    /// let bus = Bus::<Bytes>::new();
+    ///
+    /// let orders = bus.durable_topic("orders", &log);
+    /// orders.publish(Bytes::from("buy"));
+    /// log.flush().unwrap();
+    ///
+    /// let snapshot = log.snapshot().unwrap();
+    /// assert_eq!(snapshot.get("orders").unwrap().version, 1);
+    /// # drop(log);
+    /// # std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub fn durable_topic(&self, name: &str, log: &Arc<TopicLog<T>>) -> Arc<Topic<T>> {
+        let topic = self.topic(name);
+        topic.attach_log(log, true);
+        topic
+    }
+
+    /// Restores every topic's latest value and version from `log` and keeps
+    /// the restored topics attached to it. Returns the number of topics
+    /// restored.
+    ///
+    /// See [`restore`](Bus::restore) for how existing topics are handled.
+    ///
+    /// # Errors
+    /// Returns any filesystem or decoding error; nothing is restored then.
+    pub fn replay(&self, log: &Arc<TopicLog<T>>) -> io::Result<usize> {
+        let snapshot = log.snapshot()?;
+        for entry in snapshot.topics.iter() {
+            self.topic(&entry.name).attach_log(log, false);
+        }
+        Ok(self.restore(snapshot))
+    }
+}
+
+/// A decoded record with its payload still encoded.
+#[derive(Debug)]
+struct Record {
+    topic: String,
+    version: u64,
+    payload: Vec<u8>,
+}
+
+/// Appends `[body len][crc32 of body][version][topic len][topic][payload]`.
+fn encode_record(
+    buf: &mut Vec<u8>,
+    topic: &str,
+    version: u64,
+    payload: impl FnOnce(&mut Vec<u8>) -> io::Result<()>,
+) -> io::Result<()> {
+    let topic_len = u32::try_from(topic.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "topic name too long"))?;
+    let start = buf.len();
+    buf.extend_from_slice(&[0; HEADER_LEN]);
+    buf.extend_from_slice(&version.to_le_bytes());
+    buf.extend_from_slice(&topic_len.to_le_bytes());
+    buf.extend_from_slice(topic.as_bytes());
+    payload(buf)?;
+
+    let body = start + HEADER_LEN;
+    let body_len = u32::try_from(buf.len() - body)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "record too large"))?;
+    let checksum = crc32(&buf[body..]);
+    buf[start..start + 4].copy_from_slice(&body_len.to_le_bytes());
+    buf[start + 4..body].copy_from_slice(&checksum.to_le_bytes());
+    Ok(())
+}
+
+/// Decodes records up to the first incomplete or corrupt one, returning
+/// them with the length of the valid prefix.
+fn read_records(data: &[u8]) -> (Vec<Record>, u64) {
+    let mut records = Vec::new();
+    let mut offset = 0;
+    while let Some((record, len)) = decode_record(&data[offset..]) {
+        records.push(record);
+        offset += len;
+    }
+    (records, offset as u64)
+}
+
+fn decode_record(data: &[u8]) -> Option<(Record, usize)> {
+    let body_len = u32::from_le_bytes(data.get(..4)?.try_into().ok()?) as usize;
+    let checksum = u32::from_le_bytes(data.get(4..HEADER_LEN)?.try_into().ok()?);
+    let body = data.get(HEADER_LEN..HEADER_LEN.checked_add(body_len)?)?;
+    if body.len() < BODY_PREFIX_LEN || crc32(body) != checksum {
+        return None;
+    }
+
+    let version = u64::from_le_bytes(body[..8].try_into().ok()?);
+    let topic_len = u32::from_le_bytes(body[8..BODY_PREFIX_LEN].try_into().ok()?) as usize;
+    let topic = body.get(BODY_PREFIX_LEN..BODY_PREFIX_LEN.checked_add(topic_len)?)?;
+    let record = Record {
+        topic: std::str::from_utf8(topic).ok()?.to_string(),
+        version,
+        payload: body[BODY_PREFIX_LEN + topic_len..].to_vec(),
+    };
+    Some((record, HEADER_LEN + body_len))
+}
+
+fn segment_path(dir: &Path, segment: u64) -> PathBuf {
+    dir.join(format!("{segment:020}.{SEGMENT_EXTENSION}"))
+}
+
+/// Returns the numbers of all segment files in `dir`, in ascending order.
+fn list_segments(dir: &Path) -> io::Result<Vec<u64>> {
+    let mut segments = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == SEGMENT_EXTENSION) {
+            if let Some(segment) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse().ok())
+            {
+                segments.push(segment);
+            }
+        }
+    }
+    segments.sort_unstable();
+    Ok(segments)
+}
+
+/// CRC-32 (IEEE) used to detect torn or corrupt records.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32_known_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_read_records_stops_at_torn_tail() {
+        let mut data = Vec::new();
+        encode_record(&mut data, "a", 1, |payload| {
+            payload.extend_from_slice(b"one");
+            Ok(())
+        })
+        .unwrap();
+        let complete = data.len();
+        encode_record(&mut data, "b", 7, |payload| {
+            payload.extend_from_slice(b"two");
+            Ok(())
+        })
+        .unwrap();
+
+        let (records, len) = read_records(&data);
+        assert_eq!(records.len(), 2);
+        assert_eq!(len, data.len() as u64);
+        assert_eq!((records[1].topic.as_str(), records[1].version), ("b", 7));
+        assert_eq!(records[1].payload, b"two");
+
+        let (records, len) = read_records(&data[..data.len() - 1]);
+        assert_eq!((records.len(), len), (1, complete as u64));
+
+        let last = data.len() - 1;
+        data[last] ^= 0xFF;
+        let (records, len) = read_records(&data);
+        assert_eq!((records.len(), len), (1, complete as u64));
+    }
+}
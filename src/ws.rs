@@ -0,0 +1,323 @@
+//! Serving bus topics to browsers over WebSocket.
+//!
+//! Enabled with the `ws` feature. A [`WsGateway`] accepts WebSocket clients
+//! and lets them subscribe to topics of a [`Bus`] with JSON text frames:
+//!
+//! ```json
+//! {"type": "subscribe", "topic": "sensors/+/temp"}
+//! {"type": "unsubscribe", "topic": "sensors/+/temp"}
+//! ```
+//!
+//! Topic names may use the wildcards of
+//! [`Bus::subscribe_pattern`](crate::Bus::subscribe_pattern). The gateway
+//! answers with the current value of every matching topic and then each
+//! update:
+//!
+//! ```json
+//! {"type": "update", "topic": "sensors/kitchen/temp", "version": 3, "value": 21.5}
+//! ```
+//!
+//! Malformed frames and invalid patterns are answered with
+//! `{"type": "error", "message": "..."}` and leave the connection open.
+//!
+//! Updates are conflated per topic: a client that reads slower than topics
+//! publish receives the latest value of each topic once it catches up, never
+//! a backlog.
+
+use crate::{accept, bus::Bus, pattern::PatternSub};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::Notify;
+use tokio::task::{AbortHandle, JoinSet};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Error frames kept for a client that isn't reading, so that one sending
+/// bad frames without reading can't grow its queue without bound.
+const MAX_PENDING_ERRORS: usize = 16;
+
+/// A frame sent by a WebSocket client.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientFrame {
+    Subscribe { topic: String },
+    Unsubscribe { topic: String },
+}
+
+/// A frame sent to a WebSocket client.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerFrame<'a, T> {
+    Update {
+        topic: &'a str,
+        version: u64,
+        value: &'a T,
+    },
+    Error {
+        message: &'a str,
+    },
+}
+
+/// Serves the topics of a [`Bus`] to WebSocket clients.
+pub struct WsGateway<T> {
+    bus: Arc<Bus<T>>,
+    listener: TcpListener,
+}
+
+impl<T> WsGateway<T>
+where
+    T: Clone + Send + Sync + Serialize + 'static,
+{
+    /// Binds a gateway for `bus` to `addr`.
+    ///
+    /// # Errors
+    /// Returns any error from binding the listener.
+    pub async fn bind(addr: impl ToSocketAddrs, bus: Arc<Bus<T>>) -> io::Result<Self> {
+        Ok(Self {
+            bus,
+            listener: TcpListener::bind(addr).await?,
+        })
+    }
+
+    /// Returns the address the gateway is listening on.
+    ///
+    /// # Errors
+    /// Returns any error from the underlying socket.
+    #[inline]
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Returns the served bus.
+    #[inline]
+    pub fn bus(&self) -> &Arc<Bus<T>> {
+        &self.bus
+    }
+
+    /// Accepts and serves clients until the returned future is dropped.
+    ///
+    /// Every client is handled on its own task. Dropping the returned future
+    /// closes all client connections.
+    ///
+    /// Accept errors don't stop the gateway: errors of a single connection
+    /// are skipped, and others, such as running out of file descriptors,
+    /// pause accepting briefly. With the `tracing` feature they are logged.
+    pub async fn serve(self) {
+        let mut connections = JoinSet::new();
+        loop {
+            let stream = match self.listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(error) => {
+                    accept::recover(error).await;
+                    continue;
+                }
+            };
+            while connections.try_join_next().is_some() {}
+            // Without it the connection still works, only with more latency.
+            let _ = stream.set_nodelay(true);
+            connections.spawn(serve_connection(self.bus.clone(), stream));
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for WsGateway<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WsGateway")
+            .field("local_addr", &self.listener.local_addr().ok())
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug)]
+struct Pending<T> {
+    updates: HashMap<Arc<str>, (u64, T)>,
+    errors: Vec<String>,
+}
+
+/// Frames waiting for a client, keeping only the latest update per topic.
+#[derive(Debug)]
+struct Outbox<T> {
+    pending: Mutex<Pending<T>>,
+    notify: Notify,
+}
+
+impl<T> Outbox<T> {
+    fn new() -> Self {
+        Self {
+            pending: Mutex::new(Pending {
+                updates: HashMap::new(),
+                errors: Vec::new(),
+            }),
+            notify: Notify::new(),
+        }
+    }
+
+    #[inline]
+    fn pending(&self) -> MutexGuard<'_, Pending<T>> {
+        self.pending.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Queues an update, replacing any older one for the same topic.
+    fn push_update(&self, topic: Arc<str>, version: u64, value: T) {
+        match self.pending().updates.entry(topic) {
+            Entry::Occupied(mut entry) => {
+                if entry.get().0 < version {
+                    entry.insert((version, value));
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert((version, value));
+            }
+        }
+        self.notify.notify_one();
+    }
+
+    /// Queues an error, dropping the oldest one if a client that doesn't
+    /// read already has [`MAX_PENDING_ERRORS`] waiting.
+    fn push_error(&self, message: String) {
+        let mut pending = self.pending();
+        if pending.errors.len() == MAX_PENDING_ERRORS {
+            pending.errors.remove(0);
+        }
+        pending.errors.push(message);
+        drop(pending);
+        self.notify.notify_one();
+    }
+
+    fn take(&self) -> Pending<T> {
+        let mut pending = self.pending();
+        Pending {
+            updates: std::mem::take(&mut pending.updates),
+            errors: std::mem::take(&mut pending.errors),
+        }
+    }
+}
+
+async fn serve_connection<T>(bus: Arc<Bus<T>>, stream: TcpStream)
+where
+    T: Clone + Send + Sync + Serialize + 'static,
+{
+    let Ok(socket) = tokio_tungstenite::accept_async(stream).await else {
+        return;
+    };
+    let (mut sink, mut source) = socket.split();
+    let outbox = Arc::new(Outbox::new());
+    let mut tasks = JoinSet::new();
+    let mut subscriptions: HashMap<String, AbortHandle> = HashMap::new();
+
+    {
+        let outbox = outbox.clone();
+        tasks.spawn(async move {
+            loop {
+                outbox.notify.notified().await;
+                let Pending { updates, errors } = outbox.take();
+                let errors = errors.iter().map(|message| ServerFrame::Error { message });
+                let updates = updates
+                    .iter()
+                    .map(|(topic, (version, value))| ServerFrame::Update {
+                        topic,
+                        version: *version,
+                        value,
+                    });
+                for frame in errors.chain(updates) {
+                    let Ok(text) = serde_json::to_string(&frame) else {
+                        continue;
+                    };
+                    if sink.feed(Message::text(text)).await.is_err() {
+                        return;
+                    }
+                }
+                if sink.flush().await.is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    // The connection ends when the client closes it or the socket fails;
+    // dropping `tasks` then stops the writer and every subscription.
+    while let Some(Ok(message)) = source.next().await {
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        match serde_json::from_str::<ClientFrame>(&text) {
+            Ok(ClientFrame::Subscribe { topic }) => {
+                if subscriptions.contains_key(&topic) {
+                    continue;
+                }
+                match bus.subscribe_pattern(&topic) {
+                    Ok(sub) => {
+                        let handle = tasks.spawn(forward_updates(sub, outbox.clone()));
+                        subscriptions.insert(topic, handle);
+                    }
+                    Err(error) => outbox.push_error(error.to_string()),
+                }
+            }
+            Ok(ClientFrame::Unsubscribe { topic }) => {
+                if let Some(handle) = subscriptions.remove(&topic) {
+                    handle.abort();
+                }
+            }
+            Err(error) => outbox.push_error(format!("invalid frame: {error}")),
+        }
+    }
+}
+
+async fn forward_updates<T>(mut sub: PatternSub<T>, outbox: Arc<Outbox<T>>)
+where
+    T: Clone + Send + Sync + 'static,
+{
+    loop {
+        let delivery = std::future::poll_fn(|cx| {
+            sub.poll_recv_versioned_with(cx, |_, message, version| (version, message.clone()))
+        })
+        .await;
+        let Some((topic, (version, value))) = delivery else {
+            break;
+        };
+        outbox.push_update(topic, version, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outbox_conflates_per_topic() {
+        let outbox = Outbox::new();
+        let topic: Arc<str> = Arc::from("t");
+        for version in 1..=100 {
+            outbox.push_update(topic.clone(), version, version * 10);
+        }
+        outbox.push_update(topic.clone(), 50, 0);
+        outbox.push_update(Arc::from("other"), 1, 7);
+        outbox.push_error("bad".to_string());
+
+        let pending = outbox.take();
+        assert_eq!(pending.updates.len(), 2);
+        assert_eq!(pending.updates[&topic], (100, 1000));
+        assert_eq!(pending.errors, vec!["bad".to_string()]);
+        assert!(outbox.take().updates.is_empty());
+    }
+
+    #[test]
+    fn test_outbox_caps_errors() {
+        let outbox = Outbox::<u8>::new();
+        for i in 0..100 {
+            outbox.push_error(i.to_string());
+        }
+
+        let errors = outbox.take().errors;
+        assert_eq!(errors.len(), MAX_PENDING_ERRORS);
+        assert_eq!(errors.first().unwrap(), "84");
+        assert_eq!(errors.last().unwrap(), "99");
+    }
+}
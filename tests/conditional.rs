@@ -0,0 +1,115 @@
+use dropslot::{Bus, BusError, DeliveryMode};
+use std::thread;
+
+#[test]
+fn test_publish_if_version() {
+    let bus = Bus::<String>::new();
+    let topic = bus.topic("state");
+    let mut subscriber = topic.subscribe();
+
+    assert_eq!(topic.publish_if_version(0, "a".to_string()), Ok(1));
+    assert_eq!(
+        topic.publish_if_version(0, "b".to_string()),
+        Err(BusError::conflict("state", 0, 1))
+    );
+    assert_eq!(topic.version(), 1);
+    assert_eq!(subscriber.try_get_message(), Ok(Some("a".to_string())));
+
+    // A rejected publish does not wake subscribers.
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+    assert_eq!(topic.stats().publishes, 1);
+}
+
+#[test]
+fn test_update_reads_modifies_and_writes() {
+    let bus = Bus::<u32>::new();
+    let topic = bus.topic("hits");
+    let subscriber = topic.subscribe();
+
+    assert_eq!(topic.update(|old| Some(old.map_or(1, |n| n + 1))), Some(1));
+    assert_eq!(topic.update(|old| Some(old.map_or(1, |n| n + 1))), Some(2));
+    assert_eq!(subscriber.get_latest(), Some(2));
+
+    assert_eq!(topic.update(|_| None), None);
+    assert_eq!(topic.version(), 2);
+}
+
+#[test]
+fn test_update_sees_expired_value_as_missing() {
+    let bus = Bus::<u32>::new();
+    let topic = bus.topic("t");
+    topic.publish_with_ttl(5, std::time::Duration::ZERO);
+    assert_eq!(
+        topic.update(|old| Some(old.copied().unwrap_or(0) + 1)),
+        Some(2)
+    );
+}
+
+#[test]
+fn test_concurrent_updates_are_not_lost() {
+    let bus = Bus::<u64>::new();
+    let topic = bus.topic("counter");
+
+    let writers: Vec<_> = (0..8)
+        .map(|_| {
+            let topic = topic.clone();
+            thread::spawn(move || {
+                for _ in 0..500 {
+                    topic.update(|old| Some(old.copied().unwrap_or(0) + 1));
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+    assert_eq!(topic.subscribe().get_latest(), Some(4000));
+    assert_eq!(topic.version(), 4000);
+}
+
+#[test]
+fn test_optimistic_retry_loop() {
+    let bus = Bus::<u64>::new();
+    let topic = bus.topic("counter");
+
+    let writers: Vec<_> = (0..4)
+        .map(|_| {
+            let topic = topic.clone();
+            let subscriber = topic.subscribe();
+            thread::spawn(move || {
+                for _ in 0..200 {
+                    loop {
+                        let (value, version) = subscriber
+                            .get_latest_envelope()
+                            .map_or((0, 0), |envelope| (envelope.message, envelope.version));
+                        match topic.publish_if_version(version, value + 1) {
+                            Ok(_) => break,
+                            Err(error) => assert!(error.is_conflict()),
+                        }
+                    }
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+    assert_eq!(topic.subscribe().get_latest(), Some(800));
+}
+
+#[test]
+fn test_conditional_publishes_feed_history_and_derived_topics() {
+    let bus = Bus::<u32>::new();
+    let topic = bus.topic_with_mode("h", DeliveryMode::history(4));
+    let doubled = bus.map("h", "h/doubled", |n| n * 2).unwrap();
+    let mut subscriber = topic.subscribe();
+    let derived = doubled.subscribe();
+
+    topic.publish_if_version(0, 1).unwrap();
+    topic.update(|old| old.map(|n| n + 1));
+
+    let drained = subscriber.try_drain().unwrap();
+    let messages: Vec<_> = drained.messages.iter().map(|m| m.message).collect();
+    assert_eq!(messages, [1, 2]);
+    assert_eq!(derived.get_latest(), Some(4));
+}
@@ -0,0 +1,193 @@
+use dropslot::{Bus, BusHasher, DeliveryMode, TopicConfig};
+use std::time::Duration;
+
+#[test]
+fn test_default_config_applies_to_new_topics() {
+    let bus = Bus::<u32>::builder()
+        .default_config(
+            TopicConfig::new()
+                .ttl(Duration::from_secs(5))
+                .dedup(true)
+                .admission_limit(3),
+        )
+        .build();
+
+    let topic = bus.topic("a");
+    assert_eq!(topic.delivery_mode(), DeliveryMode::Latest);
+    assert_eq!(topic.default_ttl(), Some(Duration::from_secs(5)));
+    assert!(topic.dedups());
+    assert_eq!(topic.admission_limit(), Some(3));
+    assert!(!topic.is_pinned());
+
+    bus.publish("a", 1);
+    bus.publish("a", 1);
+    assert_eq!(topic.version(), 1);
+}
+
+#[test]
+fn test_first_matching_pattern_wins() {
+    let bus = Bus::<u32>::builder()
+        .pattern_config("audit/login", TopicConfig::new().pinned(true))
+        .unwrap()
+        .pattern_config("audit/#", TopicConfig::new().mode(DeliveryMode::history(4)))
+        .unwrap()
+        .build();
+
+    assert!(bus.topic("audit/login").is_pinned());
+    assert_eq!(
+        bus.topic("audit/login").delivery_mode(),
+        DeliveryMode::Latest
+    );
+    assert_eq!(
+        bus.topic("audit/logout").delivery_mode(),
+        DeliveryMode::history(4)
+    );
+    assert_eq!(bus.topic("metrics").delivery_mode(), DeliveryMode::Latest);
+    assert!(
+        Bus::<u32>::builder()
+            .pattern_config("a/#/b", TopicConfig::new())
+            .unwrap_err()
+            .is_invalid_pattern()
+    );
+}
+
+#[test]
+fn test_topic_with_mode_overrides_configured_mode() {
+    let bus = Bus::<u32>::builder()
+        .default_config(
+            TopicConfig::new()
+                .mode(DeliveryMode::history(2))
+                .pinned(true),
+        )
+        .build();
+
+    let topic = bus.topic_with_mode("t", DeliveryMode::Latest);
+    assert_eq!(topic.delivery_mode(), DeliveryMode::Latest);
+    assert!(topic.is_pinned());
+}
+
+#[test]
+fn test_derived_topics_are_configured_but_latest_only() {
+    let bus = Bus::<u32>::builder()
+        .default_config(
+            TopicConfig::new()
+                .mode(DeliveryMode::history(8))
+                .admission_limit(1),
+        )
+        .build();
+
+    let derived = bus.map("n", "n/doubled", |n| n * 2).unwrap();
+    assert_eq!(bus.topic("n").delivery_mode(), DeliveryMode::history(8));
+    assert_eq!(derived.delivery_mode(), DeliveryMode::Latest);
+    assert_eq!(derived.admission_limit(), Some(1));
+}
+
+#[test]
+fn test_admission_limit() {
+    let bus = Bus::<u32>::builder()
+        .pattern_config("jobs", TopicConfig::new().admission_limit(2))
+        .unwrap()
+        .build();
+
+    let first = bus.try_subscribe("jobs").unwrap();
+    let _second = bus.try_subscribe("jobs").unwrap();
+    let error = bus.try_subscribe("jobs").unwrap_err();
+    assert!(error.is_capacity_exceeded());
+    assert_eq!(
+        error.to_string(),
+        "Subscribe error: Topic 'jobs' admits at most 2 subscribers"
+    );
+
+    drop(first);
+    let _third = bus.try_subscribe("jobs").unwrap();
+
+    // Plain subscribing ignores the limit.
+    let _extra = bus.subscribe("jobs");
+    assert_eq!(bus.topic("jobs").subscriber_count(), 3);
+    assert!(bus.try_subscribe("other").is_ok());
+}
+
+#[test]
+fn test_pinned_topics_survive_cleanup() {
+    let bus = Bus::<u32>::builder()
+        .pattern_config("config/#", TopicConfig::new().pinned(true))
+        .unwrap()
+        .build();
+    bus.publish("config/db", 1);
+    bus.publish("scratch", 2);
+
+    assert_eq!(bus.cleanup_unused_topics(), 1);
+    assert_eq!(bus.topic_names(), ["config/db"]);
+
+    bus.topic("config/db").set_pinned(false);
+    assert_eq!(bus.cleanup_unused_topics(), 1);
+}
+
+#[test]
+fn test_topic_with_rejects_conflicting_config() {
+    let bus = Bus::<String>::new();
+    let config = TopicConfig::new()
+        .mode(DeliveryMode::history(16))
+        .ttl(Duration::from_secs(1))
+        .dedup(true);
+
+    let topic = bus.topic_with("audit", config).unwrap();
+    assert_eq!(topic.delivery_mode(), DeliveryMode::history(16));
+    assert_eq!(topic.default_ttl(), Some(Duration::from_secs(1)));
+    assert!(topic.dedups());
+    assert!(std::sync::Arc::ptr_eq(
+        &topic,
+        &bus.topic_with("audit", config).unwrap()
+    ));
+
+    for conflicting in [
+        TopicConfig::new().ttl(Duration::from_secs(1)).dedup(true),
+        config.ttl(Duration::from_secs(2)),
+        config.dedup(false),
+        config.admission_limit(1),
+        config.pinned(true),
+    ] {
+        assert!(
+            bus.topic_with("audit", conflicting)
+                .unwrap_err()
+                .is_config_conflict()
+        );
+    }
+
+    bus.topic("plain");
+    assert!(bus.topic_with("plain", TopicConfig::new()).is_ok());
+}
+
+#[test]
+fn test_topic_with_ignores_runtime_changes() {
+    let bus = Bus::<u32>::new();
+    let config = TopicConfig::new()
+        .ttl(Duration::from_nanos(1500))
+        .dedup(true)
+        .pinned(true);
+    let topic = bus.topic_with("state", config).unwrap();
+
+    topic.set_pinned(false);
+    topic.set_dedup(false);
+    topic.set_default_ttl(None);
+    topic.set_admission_limit(Some(1));
+    assert!(bus.topic_with("state", config).is_ok());
+    assert!(
+        bus.topic_with("state", TopicConfig::new())
+            .unwrap_err()
+            .is_config_conflict()
+    );
+}
+
+#[test]
+fn test_sip_hasher() {
+    let bus = Bus::<u32>::builder()
+        .capacity(64)
+        .hasher(BusHasher::SipHash)
+        .build();
+    for i in 0..100 {
+        bus.publish(&format!("t/{i}"), i);
+    }
+    assert_eq!(bus.topic_count(), 100);
+    assert_eq!(bus.subscribe("t/42").get_latest(), Some(42));
+}
@@ -0,0 +1,88 @@
+use dropslot::{Bus, Headers};
+use std::time::Duration;
+
+#[test]
+fn test_publish_if_changed_skips_equal_values() {
+    let bus = Bus::<String>::new();
+    let topic = bus.topic("config");
+    let mut subscriber = topic.subscribe();
+
+    assert!(topic.publish_if_changed("v1".to_string()));
+    assert_eq!(subscriber.try_get_message(), Ok(Some("v1".to_string())));
+
+    assert!(!topic.publish_if_changed("v1".to_string()));
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+    assert_eq!(topic.version(), 1);
+    assert_eq!(topic.stats().publishes, 1);
+
+    assert!(topic.publish_if_changed("v2".to_string()));
+    assert_eq!(subscriber.try_get_message(), Ok(Some("v2".to_string())));
+}
+
+#[test]
+fn test_dedup_policy_applies_to_every_publish() {
+    let bus = Bus::<u32>::new();
+    bus.set_dedup("config", true);
+    let topic = bus.topic("config");
+    assert!(topic.dedups());
+
+    bus.publish("config", 1);
+    topic.publish(1);
+    topic.publish_with_meta(1, Headers::new().with("k", "v"));
+    assert_eq!(topic.version(), 1);
+
+    topic.publish(2);
+    assert_eq!(topic.version(), 2);
+
+    topic.set_dedup(false);
+    assert!(!topic.dedups());
+    topic.publish(2);
+    assert_eq!(topic.version(), 3);
+}
+
+#[test]
+fn test_expired_value_is_republished() {
+    let bus = Bus::<u32>::new();
+    let topic = bus.topic("t");
+    topic.set_dedup(true);
+
+    topic.publish_with_ttl(1, Duration::ZERO);
+    topic.publish(1);
+    assert_eq!(topic.version(), 2);
+    assert_eq!(topic.subscribe().get_latest(), Some(1));
+}
+
+#[tokio::test]
+async fn test_skipped_publish_does_not_wake_waiters() {
+    let bus = Bus::<u32>::new();
+    let topic = bus.topic("t");
+    topic.set_dedup(true);
+    topic.publish(1);
+
+    let mut subscriber = topic.subscribe();
+    let waiter = tokio::spawn(async move { subscriber.wait_for_delivery().await });
+    tokio::task::yield_now().await;
+    topic.publish(1);
+    tokio::task::yield_now().await;
+    assert!(!waiter.is_finished());
+
+    topic.publish(2);
+    let delivery = waiter.await.unwrap().unwrap();
+    assert_eq!(
+        (delivery.message, delivery.version, delivery.skipped),
+        (2, 2, 0)
+    );
+}
+
+#[test]
+fn test_dedup_on_derived_topic() {
+    let bus = Bus::<u32>::new();
+    let parity = bus.map("n", "n/parity", |n| n % 2).unwrap();
+    parity.set_dedup(true);
+
+    for n in [1, 3, 5, 6, 8] {
+        bus.publish("n", n);
+    }
+    // 1, 3 and 5 map to 1, then 6 and 8 to 0.
+    assert_eq!(parity.version(), 2);
+}
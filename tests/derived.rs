@@ -0,0 +1,221 @@
+mod common;
+
+use common::*;
+use dropslot::Bus;
+use std::time::Duration;
+use tokio::time::timeout;
+
+#[test]
+fn test_map_updates_on_publish() {
+    let bus = create_string_bus();
+    let upper = bus
+        .map("names", "names/upper", |name| name.to_uppercase())
+        .unwrap();
+    let mut subscriber = upper.subscribe();
+
+    bus.publish("names", "ada".to_string());
+    assert_eq!(
+        subscriber.try_get_message().unwrap(),
+        Some("ADA".to_string())
+    );
+
+    bus.topic("names").publish("grace".to_string());
+    assert_eq!(
+        subscriber.try_get_message().unwrap(),
+        Some("GRACE".to_string())
+    );
+}
+
+#[test]
+fn test_derived_topic_starts_from_current_source_value() {
+    let bus = Bus::<i32>::new();
+    bus.publish("values", 4);
+
+    let squared = bus.map("values", "values/squared", |v| v * v).unwrap();
+    assert_eq!(squared.subscribe().get_latest(), Some(16));
+}
+
+#[test]
+fn test_filter_and_distinct_until_changed() {
+    let bus = Bus::<i32>::new();
+    let even = bus
+        .filter("numbers", "numbers/even", |v| v % 2 == 0)
+        .unwrap();
+    let distinct = bus
+        .distinct_until_changed("numbers", "numbers/distinct")
+        .unwrap();
+    let mut even_sub = even.subscribe();
+    let mut distinct_sub = distinct.subscribe();
+
+    for value in [1, 2, 2, 3, 3, 4] {
+        bus.publish("numbers", value);
+    }
+
+    let delivery = even_sub.try_get_delivery().unwrap().unwrap();
+    assert_eq!((delivery.message, delivery.skipped), (4, 2));
+
+    let delivery = distinct_sub.try_get_delivery().unwrap().unwrap();
+    assert_eq!((delivery.message, delivery.version), (4, 4));
+}
+
+#[test]
+fn test_combine_latest_waits_for_all_sources() {
+    let bus = Bus::<i32>::new();
+    let sum = bus
+        .combine_latest(&["a", "b", "c"], "sum", |values| values.iter().sum())
+        .unwrap();
+    let mut subscriber = sum.subscribe();
+
+    bus.publish("a", 1);
+    bus.publish("b", 2);
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+
+    bus.publish("c", 3);
+    assert_eq!(subscriber.try_get_message().unwrap(), Some(6));
+
+    bus.publish("b", 10);
+    assert_eq!(subscriber.try_get_message().unwrap(), Some(14));
+}
+
+#[test]
+fn test_derived_topics_are_listed_and_marked() {
+    let bus = create_string_bus();
+    let derived = bus.map("source", "derived", String::clone).unwrap();
+
+    let names = bus.topic_names();
+    assert!(names.contains(&"source".to_string()));
+    assert!(names.contains(&"derived".to_string()));
+
+    assert!(derived.is_derived());
+    assert_eq!(derived.sources(), ["source"]);
+    assert!(!bus.topic("source").is_derived());
+    assert!(bus.topic("source").sources().is_empty());
+}
+
+#[test]
+fn test_derived_name_must_be_new() {
+    let bus = create_string_bus();
+    bus.topic("existing");
+
+    let error = bus.map("source", "existing", String::clone).unwrap_err();
+    assert!(error.is_topic_exists());
+    assert_eq!(
+        error.to_string(),
+        "Topic error: Topic 'existing' already exists"
+    );
+
+    assert!(
+        bus.map("loop", "loop", String::clone)
+            .unwrap_err()
+            .is_topic_exists()
+    );
+
+    // Failed derivations leave their sources uncreated.
+    let mut names = bus.topic_names();
+    names.sort();
+    assert_eq!(names, ["existing"]);
+}
+
+#[test]
+fn test_combine_latest_needs_a_source() {
+    let bus = create_string_bus();
+    let error = bus
+        .combine_latest(&[], "nothing", |values| values.concat())
+        .unwrap_err();
+    assert!(error.is_no_sources());
+    assert_eq!(error.topic(), Some("nothing"));
+    assert!(bus.topic_names().is_empty());
+}
+
+#[test]
+fn test_cleanup_tears_down_derived_topics() {
+    let bus = Bus::<u32>::new();
+    let doubled = bus.map("a", "a/doubled", |value| value * 2).unwrap();
+    let subscriber = doubled.subscribe();
+
+    assert_eq!(bus.cleanup_unused_topics(), 1);
+    assert!(bus.topic_names().is_empty());
+
+    // A recreated source doesn't feed the removed derived topic.
+    bus.publish("a", 1);
+    assert_eq!(subscriber.get_latest(), None);
+}
+
+#[test]
+fn test_seed_does_not_overwrite_concurrent_publishes() {
+    use std::sync::{Mutex, mpsc};
+
+    let bus = std::sync::Arc::new(Bus::<u32>::new());
+    bus.publish("source", 0);
+
+    // Publishes 1 while the derived topic computes its seed from 0.
+    let (seeding_tx, seeding_rx) = mpsc::channel();
+    let (published_tx, published_rx) = mpsc::channel();
+    let published_rx = Mutex::new(published_rx);
+    let publisher = bus.clone();
+    let handle = std::thread::spawn(move || {
+        seeding_rx.recv().unwrap();
+        publisher.publish("source", 1);
+        published_tx.send(()).unwrap();
+    });
+
+    let derived = bus
+        .map("source", "derived", move |value| {
+            if *value == 0 {
+                seeding_tx.send(()).unwrap();
+                published_rx.lock().unwrap().recv().unwrap();
+            }
+            *value
+        })
+        .unwrap();
+    handle.join().unwrap();
+    assert_eq!(derived.subscribe().get_latest(), Some(1));
+}
+
+#[test]
+fn test_remove_source_tears_down_derived_chain() {
+    let bus = Bus::<i32>::new();
+    bus.map("a", "a/doubled", |v| v * 2).unwrap();
+    bus.map("a/doubled", "a/quadrupled", |v| v * 2).unwrap();
+    let sum = bus
+        .combine_latest(&["a", "b"], "a+b", |v| v[0] + v[1])
+        .unwrap();
+    let mut subscriber = sum.subscribe();
+
+    let source = bus.topic("a");
+    source.publish(1);
+    bus.publish("b", 2);
+    assert_eq!(subscriber.try_get_message().unwrap(), Some(3));
+
+    assert_eq!(bus.topic_count(), 5);
+    bus.remove_topic("a");
+
+    let mut names = bus.topic_names();
+    names.sort();
+    assert_eq!(names, ["b"]);
+
+    // Neither the removed nor the surviving source feeds the removed topic.
+    source.publish(10);
+    bus.publish("b", 20);
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+}
+
+#[tokio::test]
+async fn test_derived_topic_wakes_waiting_subscriber() {
+    let bus = create_string_bus_arc();
+    let lengths = bus
+        .map("words", "words/len", |word| word.len().to_string())
+        .unwrap();
+    let mut subscriber = lengths.subscribe();
+
+    let publisher = bus.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        publisher.publish("words", "hello".to_string());
+    });
+
+    let message = timeout(Duration::from_secs(1), subscriber.wait_for_message())
+        .await
+        .unwrap();
+    assert_eq!(message, Some("5".to_string()));
+}
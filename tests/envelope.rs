@@ -0,0 +1,97 @@
+use dropslot::{Bus, Headers};
+use std::time::{Duration, Instant, SystemTime};
+
+#[test]
+fn test_envelope_carries_headers_and_timestamps() {
+    let bus = Bus::<String>::new();
+    let topic = bus.topic("config");
+    let subscriber = topic.subscribe();
+    assert_eq!(subscriber.get_latest_envelope(), None);
+
+    let before = (Instant::now(), SystemTime::now());
+    topic.publish_with_meta(
+        "v1".to_string(),
+        Headers::new()
+            .with("origin", "deployer")
+            .with("trace", "7f3a"),
+    );
+    let after = (Instant::now(), SystemTime::now());
+
+    let envelope = subscriber.get_latest_envelope().unwrap();
+    assert_eq!(envelope.message, "v1");
+    assert_eq!(envelope.version, 1);
+    assert!(before.0 <= envelope.published_at && envelope.published_at <= after.0);
+    assert!(before.1 <= envelope.timestamp && envelope.timestamp <= after.1);
+    assert_eq!(
+        envelope.headers.iter().collect::<Vec<_>>(),
+        [("origin", "deployer"), ("trace", "7f3a")]
+    );
+
+    // Plain APIs see an ordinary publish.
+    assert_eq!(subscriber.get_latest(), Some("v1".to_string()));
+}
+
+#[test]
+fn test_plain_publish_has_empty_headers() {
+    let bus = Bus::<u32>::new();
+    let topic = bus.topic("t");
+    let subscriber = topic.subscribe();
+
+    topic.publish_with_meta(1, Headers::new().with("k", "v"));
+    topic.publish(2);
+
+    let envelope = subscriber.get_latest_envelope().unwrap();
+    assert_eq!((envelope.message, envelope.version), (2, 2));
+    assert!(envelope.headers.is_empty());
+    let drift = match SystemTime::now().duration_since(envelope.timestamp) {
+        Ok(elapsed) => elapsed,
+        Err(error) => error.duration(),
+    };
+    assert!(drift < Duration::from_secs(60));
+}
+
+#[test]
+fn test_envelope_respects_expiry() {
+    let bus = Bus::<u32>::new();
+    let topic = bus.topic("t");
+    let subscriber = topic.subscribe();
+
+    topic.publish_with_ttl(1, Duration::ZERO);
+    assert_eq!(subscriber.get_latest_envelope(), None);
+}
+
+#[test]
+fn test_headers_map() {
+    let mut headers: Headers = [("a", "1"), ("b", "2")].into_iter().collect();
+    assert_eq!(headers.len(), 2);
+    assert_eq!(headers.insert("a", "3"), Some("1".to_string()));
+    assert_eq!(headers.insert("c", "4"), None);
+    assert_eq!(headers.get("a"), Some("3"));
+    assert_eq!(headers.get("missing"), None);
+    assert_eq!(
+        headers.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+        ["a", "b", "c"]
+    );
+    assert!(Headers::new().is_empty());
+}
+
+#[tokio::test]
+async fn test_wait_for_envelope() {
+    let bus = Bus::<u32>::new();
+    let topic = bus.topic("jobs");
+    let mut subscriber = topic.subscribe();
+
+    topic.publish(1);
+    topic.publish_with_meta(2, Headers::new().with("attempt", "2"));
+    let envelope = subscriber.wait_for_envelope().await.unwrap();
+    assert_eq!((envelope.message, envelope.version), (2, 2));
+    assert_eq!(envelope.headers.get("attempt"), Some("2"));
+    assert_eq!(subscriber.skipped_count(), 1);
+
+    let waiter = tokio::spawn(async move { subscriber.wait_for_envelope().await });
+    tokio::task::yield_now().await;
+    topic.publish(3);
+    let envelope = waiter.await.unwrap().unwrap();
+    assert_eq!((envelope.message, envelope.version), (3, 3));
+    assert!(envelope.headers.is_empty());
+}
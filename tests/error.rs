@@ -3,73 +3,136 @@ mod common;
 use common::*;
 use dropslot::BusError;
 use std::error::Error;
+use std::io;
+use std::time::Duration;
 
 #[test]
 fn test_error_creation_and_properties() {
-    let empty_error = BusError::message_queue_empty();
-    let disconnected_error = BusError::topic_disconnected();
+    let empty_error = BusError::empty("events");
+    let disconnected_error = BusError::disconnected("events");
 
     assert_error_properties(&empty_error, true, false);
     assert_error_properties(&disconnected_error, false, true);
 
     assert_ne!(empty_error, disconnected_error);
-    assert_eq!(empty_error, BusError::message_queue_empty());
-    assert_eq!(disconnected_error, BusError::topic_disconnected());
+    assert_eq!(empty_error, BusError::empty("events"));
+    assert_ne!(empty_error, BusError::empty("other"));
+    assert_eq!(disconnected_error, BusError::disconnected("events"));
 
     let cloned_empty = empty_error.clone();
     assert_eq!(empty_error, cloned_empty);
 }
 
 #[test]
-fn test_error_display_formatting() {
-    let empty_error = BusError::message_queue_empty();
-    let disconnected_error = BusError::topic_disconnected();
-
-    let empty_display = empty_error.to_string();
-    let disconnected_display = disconnected_error.to_string();
-
-    assert!(empty_display.contains("No message available"));
-    assert!(disconnected_display.contains("Topic disconnected"));
-    assert!(empty_display.contains("TryRecv error"));
-    assert!(disconnected_display.contains("TryRecv error"));
-    assert_ne!(empty_display, disconnected_display);
+fn test_every_variant_names_its_topic() {
+    let errors = [
+        BusError::empty("t"),
+        BusError::disconnected("t"),
+        BusError::topic_not_found("t"),
+        BusError::type_mismatch("t", "u32", "String"),
+        BusError::topic_exists("t"),
+        BusError::config_conflict("t"),
+        BusError::no_sources("t"),
+        BusError::no_responder("t"),
+        BusError::timeout("t"),
+        BusError::closed("t"),
+        BusError::stale("t"),
+        BusError::conflict("t", 1, 2),
+        BusError::capacity_exceeded("t", 3),
+        BusError::permission_denied("t"),
+    ];
+    for error in &errors {
+        assert_eq!(error.topic(), Some("t"), "{error:?}");
+        assert!(error.to_string().contains("'t'"), "{error}");
+    }
+    assert_eq!(BusError::invalid_pattern("a/#/b").topic(), None);
+
+    let predicates: [fn(&BusError) -> bool; 14] = [
+        BusError::is_empty,
+        BusError::is_disconnected,
+        BusError::is_topic_not_found,
+        BusError::is_type_mismatch,
+        BusError::is_topic_exists,
+        BusError::is_config_conflict,
+        BusError::is_no_sources,
+        BusError::is_no_responder,
+        BusError::is_timeout,
+        BusError::is_closed,
+        BusError::is_stale,
+        BusError::is_conflict,
+        BusError::is_capacity_exceeded,
+        BusError::is_permission_denied,
+    ];
+    for (i, predicate) in predicates.iter().enumerate() {
+        for (j, error) in errors.iter().enumerate() {
+            assert_eq!(predicate(error), i == j, "{error:?}");
+        }
+    }
+}
 
-    let debug_str = format!("{empty_error:?}");
-    assert!(debug_str.contains("TryRecv"));
+#[test]
+fn test_error_display_formatting() {
+    let empty_display = BusError::empty("events").to_string();
+    let disconnected_display = BusError::disconnected("events").to_string();
+
+    assert_eq!(
+        empty_display,
+        "Receive error: No message available on topic 'events'"
+    );
+    assert_eq!(
+        disconnected_display,
+        "Receive error: Topic 'events' disconnected"
+    );
+    assert_eq!(
+        BusError::topic_not_found("events").to_string(),
+        "Topic error: Topic 'events' does not exist"
+    );
+    assert_eq!(
+        BusError::closed("events").to_string(),
+        "Topic error: Topic 'events' is closed"
+    );
+    assert_eq!(
+        BusError::permission_denied("events").to_string(),
+        "Access error: Permission denied for topic 'events'"
+    );
+
+    let debug_str = format!("{:?}", BusError::empty("events"));
+    assert!(debug_str.contains("Empty"));
 }
 
 #[test]
 fn test_error_pattern_matching() {
-    let empty_error = BusError::message_queue_empty();
-    let disconnected_error = BusError::topic_disconnected();
-
-    match empty_error {
-        BusError::TryRecv {
-            empty: true,
-            disconnected: false,
-        } => {}
+    match BusError::empty("events") {
+        BusError::Empty { topic } => assert_eq!(topic, "events"),
         _ => panic!("Unexpected error pattern"),
     }
 
-    match disconnected_error {
-        BusError::TryRecv {
-            empty: false,
-            disconnected: true,
-        } => {}
+    match BusError::disconnected("events") {
+        BusError::Disconnected { topic } => assert_eq!(topic, "events"),
         _ => panic!("Unexpected error pattern"),
     }
 
-    let BusError::TryRecv {
-        empty: e,
-        disconnected: d,
-    } = BusError::message_queue_empty();
-    assert!(e);
-    assert!(!d);
+    let BusError::CapacityExceeded { topic, limit } = BusError::capacity_exceeded("jobs", 2) else {
+        panic!("Unexpected error pattern");
+    };
+    assert_eq!(topic, "jobs");
+    assert_eq!(limit, 2);
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_deprecated_constructors() {
+    let empty_error = BusError::message_queue_empty();
+    let disconnected_error = BusError::topic_disconnected();
+
+    assert_error_properties(&empty_error, true, false);
+    assert_error_properties(&disconnected_error, false, true);
+    assert_eq!(empty_error.topic(), Some(""));
 }
 
 #[test]
 fn test_error_traits_and_behavior() {
-    let error = BusError::message_queue_empty();
+    let error = BusError::empty("events");
 
     let _: &dyn Error = &error;
 
@@ -78,14 +141,43 @@ fn test_error_traits_and_behavior() {
 
     assert!(error.source().is_none());
 
-    let error2 = BusError::message_queue_empty();
-    let error3 = BusError::topic_disconnected();
+    let error2 = BusError::empty("events");
+    let error3 = BusError::disconnected("events");
     assert_eq!(error, error2);
     assert_eq!(error2, error);
     assert_ne!(error, error3);
     assert_ne!(error3, error);
 }
 
+#[test]
+fn test_source_chaining() {
+    let cause = io::Error::new(io::ErrorKind::PermissionDenied, "read-only");
+    let error = BusError::permission_denied("secrets").with_source(cause);
+    let source = error.source().unwrap();
+    assert_eq!(source.to_string(), "read-only");
+    assert_eq!(
+        source.downcast_ref::<io::Error>().unwrap().kind(),
+        io::ErrorKind::PermissionDenied
+    );
+
+    let BusError::PermissionDenied {
+        source: Some(source),
+        ..
+    } = &error
+    else {
+        panic!("Unexpected error pattern");
+    };
+    assert!(source.get().is::<io::Error>());
+
+    assert!(BusError::timeout("t").source().is_none());
+    assert!(
+        BusError::stale("t")
+            .with_source(io::Error::other("ignored"))
+            .source()
+            .is_none()
+    );
+}
+
 #[test]
 fn test_error_from_actual_operations() {
     let bus = create_string_bus();
@@ -96,6 +188,7 @@ fn test_error_from_actual_operations() {
     assert!(result.is_err());
     let error = result.unwrap_err();
     assert_error_properties(&error, true, false);
+    assert_eq!(error.topic(), Some("error_test"));
 
     drop(topic);
     let removed_count = bus.remove_topic("error_test");
@@ -105,4 +198,45 @@ fn test_error_from_actual_operations() {
     assert!(result.is_err());
     let error = result.unwrap_err();
     assert_error_properties(&error, false, true);
+    assert_eq!(error, BusError::disconnected("error_test"));
+}
+
+#[test]
+fn test_errors_of_multi_and_pattern_subscribers() {
+    let bus = create_string_bus();
+
+    let mut many = bus.subscribe_many(&["a", "b"]);
+    assert_eq!(many.try_get_message().unwrap_err(), BusError::empty("*"));
+
+    let mut pattern = bus.subscribe_pattern("logs/+").unwrap();
+    assert_eq!(
+        pattern.try_get_message().unwrap_err(),
+        BusError::empty("logs/+")
+    );
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_request_timeout_chains_elapsed() {
+    let bus = create_string_bus();
+    let _idle = bus.responder("slow");
+
+    let error = bus
+        .request_timeout("slow", "ping".to_string(), Duration::from_millis(5))
+        .await
+        .unwrap_err();
+    assert!(error.is_timeout());
+    assert_eq!(error.topic(), Some("slow"));
+    assert!(error.source().unwrap().is::<tokio::time::error::Elapsed>());
+}
+
+#[test]
+fn test_blocking_receive_errors_name_topic() {
+    let bus = create_string_bus();
+    let mut subscriber = bus.subscribe("quiet");
+
+    let error = subscriber
+        .recv_timeout(Duration::from_millis(5))
+        .unwrap_err();
+    assert_eq!(error, BusError::timeout("quiet"));
 }
@@ -0,0 +1,179 @@
+mod common;
+
+use common::*;
+use dropslot::DeliveryMode;
+use std::time::Duration;
+use tokio::time::timeout;
+
+#[test]
+fn test_delivery_mode_construction() {
+    assert_eq!(DeliveryMode::default(), DeliveryMode::Latest);
+    assert_eq!(DeliveryMode::history(0), DeliveryMode::Latest);
+    assert_eq!(DeliveryMode::Latest.capacity(), 1);
+    assert_eq!(DeliveryMode::history(32).capacity(), 32);
+}
+
+#[test]
+fn test_topic_with_mode_keeps_existing_mode() {
+    let bus = create_string_bus();
+    let topic = bus.topic_with_mode("audit", DeliveryMode::history(4));
+    assert_eq!(topic.delivery_mode(), DeliveryMode::history(4));
+
+    let again = bus.topic_with_mode("audit", DeliveryMode::Latest);
+    assert_eq!(again.delivery_mode(), DeliveryMode::history(4));
+    assert_eq!(bus.topic("audit").delivery_mode(), DeliveryMode::history(4));
+}
+
+#[test]
+fn test_history_drain_within_capacity() {
+    let bus = create_string_bus();
+    let topic = bus.topic_with_mode("orders", DeliveryMode::history(4));
+    let mut subscriber = topic.subscribe();
+
+    assert!(subscriber.try_drain().unwrap_err().is_empty());
+
+    topic.publish("a".to_string());
+    topic.publish("b".to_string());
+    topic.publish("c".to_string());
+
+    let drained = subscriber.try_drain().unwrap();
+    assert_eq!(drained.dropped, 0);
+    let messages: Vec<_> = drained
+        .messages
+        .iter()
+        .map(|m| m.message.as_str())
+        .collect();
+    assert_eq!(messages, ["a", "b", "c"]);
+    let seqs: Vec<_> = drained.messages.iter().map(|m| m.seq).collect();
+    assert_eq!(seqs, [1, 2, 3]);
+
+    assert!(subscriber.try_drain().unwrap_err().is_empty());
+
+    topic.publish("d".to_string());
+    let drained = subscriber.try_drain().unwrap();
+    assert_eq!(drained.latest().unwrap().message, "d");
+    assert_eq!(drained.latest().unwrap().seq, 4);
+}
+
+#[test]
+fn test_history_slow_subscriber_loses_oldest() {
+    let bus = create_string_bus();
+    let topic = bus.topic_with_mode("deltas", DeliveryMode::history(3));
+    let mut slow = topic.subscribe();
+    let mut fast = topic.subscribe();
+
+    for i in 1..=5 {
+        topic.publish(format!("delta {i}"));
+        if i == 2 {
+            assert_eq!(fast.try_drain().unwrap().messages.len(), 2);
+        }
+    }
+
+    let drained = slow.try_drain().unwrap();
+    assert_eq!(drained.dropped, 2);
+    assert_eq!(drained.messages.first().unwrap().message, "delta 3");
+
+    let drained = fast.try_drain().unwrap();
+    assert_eq!(drained.dropped, 0);
+    assert_eq!(drained.messages.len(), 3);
+}
+
+#[test]
+fn test_history_only_after_subscribe() {
+    let bus = create_string_bus();
+    let topic = bus.topic_with_mode("late", DeliveryMode::history(8));
+    topic.publish("before".to_string());
+
+    let mut subscriber = topic.subscribe();
+    topic.publish("after".to_string());
+
+    let drained = subscriber.try_drain().unwrap();
+    assert_eq!(drained.dropped, 0);
+    assert_eq!(drained.messages.len(), 1);
+    assert_eq!(drained.messages[0].message, "after");
+}
+
+#[test]
+fn test_drain_on_latest_only_topic() {
+    let bus = create_string_bus();
+    let topic = bus.topic("latest");
+    let mut subscriber = topic.subscribe();
+
+    topic.publish("one".to_string());
+    topic.publish("two".to_string());
+    topic.publish("three".to_string());
+
+    let drained = subscriber.try_drain().unwrap();
+    assert_eq!(drained.dropped, 2);
+    assert_eq!(drained.messages.len(), 1);
+    assert_eq!(drained.messages[0].message, "three");
+    assert_eq!(drained.messages[0].seq, 3);
+
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+}
+
+#[test]
+fn test_drain_disconnected_topic() {
+    let bus = create_string_bus();
+    let topic = bus.topic_with_mode("gone", DeliveryMode::history(2));
+    let mut subscriber = topic.subscribe();
+
+    drop(topic);
+    bus.remove_topic("gone");
+
+    assert!(subscriber.try_drain().unwrap_err().is_disconnected());
+}
+
+#[test]
+fn test_concurrent_drain_accounts_for_every_message() {
+    const COUNT: u64 = 20_000;
+    let bus = dropslot::Bus::<u64>::new();
+    let topic = bus.topic_with_mode("deltas", DeliveryMode::history(8));
+    let mut subscriber = topic.subscribe();
+
+    let publisher = std::thread::spawn(move || {
+        for i in 1..=COUNT {
+            topic.publish(i);
+        }
+    });
+
+    let mut next = 1;
+    while next <= COUNT {
+        let Ok(drained) = subscriber.try_drain() else {
+            continue;
+        };
+        next += drained.dropped;
+        for message in &drained.messages {
+            assert_eq!(message.seq, next);
+            assert_eq!(message.message, next);
+            next += 1;
+        }
+    }
+    publisher.join().unwrap();
+    assert_eq!(next, COUNT + 1);
+}
+
+#[tokio::test]
+async fn test_wait_for_drain() {
+    let bus = create_string_bus_arc();
+    let topic = bus.topic_with_mode("events", DeliveryMode::history(8));
+    let mut subscriber = topic.subscribe();
+
+    let publisher = topic.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        publisher.publish("first".to_string());
+        publisher.publish("second".to_string());
+    });
+
+    let mut received = Vec::new();
+    while received.len() < 2 {
+        let drained = timeout(Duration::from_secs(1), subscriber.wait_for_drain())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(drained.dropped, 0);
+        received.extend(drained.messages.into_iter().map(|m| m.message));
+    }
+    assert_eq!(received, ["first", "second"]);
+}
@@ -0,0 +1,148 @@
+use dropslot::{Bus, LifecycleEvent, LifecycleEvents};
+
+fn drain(events: &mut LifecycleEvents) -> Vec<LifecycleEvent> {
+    std::iter::from_fn(|| events.try_get_event()).collect()
+}
+
+fn created(name: &str) -> LifecycleEvent {
+    LifecycleEvent::TopicCreated(name.into())
+}
+
+fn removed(name: &str) -> LifecycleEvent {
+    LifecycleEvent::TopicRemoved(name.into())
+}
+
+fn first(name: &str) -> LifecycleEvent {
+    LifecycleEvent::FirstSubscriber(name.into())
+}
+
+fn gone(name: &str) -> LifecycleEvent {
+    LifecycleEvent::LastSubscriberGone(name.into())
+}
+
+#[test]
+fn test_subscriber_transitions() {
+    let bus = Bus::<u32>::new();
+    let mut events = bus.lifecycle_events();
+
+    let a = bus.subscribe("t");
+    let b = bus.subscribe("t");
+    drop(a);
+    drop(b);
+    let c = bus.subscribe("t");
+    drop(c);
+
+    assert_eq!(
+        drain(&mut events),
+        [created("t"), first("t"), gone("t"), first("t"), gone("t")]
+    );
+}
+
+#[test]
+fn test_removal_and_cleanup() {
+    let bus = Bus::<u32>::new();
+    bus.topic("idle");
+    let _busy = bus.subscribe("busy");
+    let mut events = bus.lifecycle_events();
+
+    bus.topic("other");
+    assert_eq!(bus.cleanup_unused_topics(), 2);
+    let mut seen = drain(&mut events);
+    seen[1..].sort_by(|a, b| a.topic().cmp(b.topic()));
+    assert_eq!(seen, [created("other"), removed("idle"), removed("other")]);
+
+    assert_eq!(bus.remove_topic("busy"), Some(1));
+    assert_eq!(drain(&mut events), [removed("busy")]);
+    assert_eq!(bus.remove_topic("busy"), None);
+    assert!(drain(&mut events).is_empty());
+}
+
+#[test]
+fn test_removed_topic_stops_reporting() {
+    let bus = Bus::<u32>::new();
+    let subscriber = bus.subscribe("t");
+    let mut events = bus.lifecycle_events();
+
+    bus.remove_topic("t");
+    drop(subscriber);
+    let _again = bus.subscribe("t");
+
+    assert_eq!(drain(&mut events), [removed("t"), created("t"), first("t")]);
+}
+
+#[test]
+fn test_derived_topics_are_reported() {
+    let bus = Bus::<u32>::new();
+    let mut events = bus.lifecycle_events();
+
+    bus.map("n", "n/doubled", |n| n * 2).unwrap();
+    bus.remove_topic("n");
+
+    assert_eq!(
+        drain(&mut events),
+        [
+            created("n/doubled"),
+            created("n"),
+            removed("n"),
+            removed("n/doubled")
+        ]
+    );
+}
+
+#[test]
+fn test_every_receiver_sees_events_from_its_creation() {
+    let bus = Bus::<u32>::new();
+    let mut early = bus.lifecycle_events();
+    bus.topic("a");
+    let mut late = bus.lifecycle_events();
+    bus.topic("b");
+
+    assert_eq!(drain(&mut early), [created("a"), created("b")]);
+    assert_eq!(drain(&mut late), [created("b")]);
+
+    drop(late);
+    bus.topic("c");
+    assert_eq!(drain(&mut early), [created("c")]);
+}
+
+#[tokio::test]
+async fn test_wait_for_event() {
+    let bus = Bus::<u32>::new();
+    let mut events = bus.lifecycle_events();
+
+    let forwarder = tokio::spawn(async move {
+        let mut markets = Vec::new();
+        while let Some(event) = events.wait_for_event().await {
+            if let LifecycleEvent::TopicCreated(name) = event {
+                if name.starts_with("market/") {
+                    markets.push(name.to_string());
+                }
+            }
+        }
+        markets
+    });
+
+    bus.publish("market/eur", 1);
+    bus.publish("news", 2);
+    bus.publish("market/usd", 3);
+    drop(bus);
+
+    assert_eq!(forwarder.await.unwrap(), ["market/eur", "market/usd"]);
+}
+
+#[test]
+fn test_full_queue_drops_oldest_events() {
+    use dropslot::lifecycle::EVENT_CAPACITY;
+
+    let bus = Bus::<u32>::new();
+    let mut events = bus.lifecycle_events();
+    for i in 0..EVENT_CAPACITY + 10 {
+        bus.topic(&format!("t/{i}"));
+    }
+
+    assert_eq!(events.dropped_count(), 10);
+    let seen = drain(&mut events);
+    assert_eq!(seen.len(), EVENT_CAPACITY);
+    assert_eq!(seen[0], created("t/10"));
+    assert_eq!(events.dropped_count(), 10);
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::*;
+use dropslot::MultiSub;
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::time::timeout;
+
+#[test]
+fn test_multi_sub_membership() {
+    let bus = create_string_bus();
+    let mut subscriber = bus.subscribe_many(&["a", "b", "c"]);
+
+    assert_eq!(subscriber.len(), 3);
+    assert!(!subscriber.is_empty());
+    assert!(subscriber.contains("b"));
+    assert_eq!(bus.topic_count(), 3);
+
+    assert!(subscriber.add(bus.subscribe("d")));
+    assert!(!subscriber.add(bus.subscribe("d")));
+    assert!(subscriber.add_topic(&bus.topic("e")));
+    assert_eq!(subscriber.len(), 5);
+
+    assert!(subscriber.remove("a"));
+    assert!(!subscriber.remove("a"));
+    assert!(!subscriber.contains("a"));
+
+    let names: HashSet<_> = subscriber.topic_names().into_iter().collect();
+    let expected: HashSet<_> = ["b", "c", "d", "e"].map(String::from).into_iter().collect();
+    assert_eq!(names, expected);
+
+    let debug_str = format!("{subscriber:?}");
+    assert!(debug_str.contains("MultiSub"));
+}
+
+#[test]
+fn test_multi_sub_try_get_message() {
+    let bus = create_string_bus();
+    let mut subscriber = bus.subscribe_many(&["a", "b"]);
+
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+
+    bus.publish("b", "first".to_string());
+    bus.publish("b", "second".to_string());
+
+    let (topic, message) = subscriber.try_get_message().unwrap();
+    assert_eq!(&*topic, "b");
+    assert_eq!(message, "second");
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+}
+
+#[test]
+fn test_multi_sub_fair_scheduling() {
+    let bus = create_string_bus();
+    let mut subscriber = bus.subscribe_many(&["hot", "cold1", "cold2"]);
+
+    bus.publish("cold1", "c1".to_string());
+    bus.publish("cold2", "c2".to_string());
+
+    let mut seen = Vec::new();
+    for _ in 0..3 {
+        bus.publish("hot", "h".to_string());
+        let (topic, _) = subscriber.try_get_message().unwrap();
+        seen.push(topic.to_string());
+    }
+
+    assert!(seen.contains(&"cold1".to_string()));
+    assert!(seen.contains(&"cold2".to_string()));
+    assert!(seen.contains(&"hot".to_string()));
+}
+
+#[tokio::test]
+async fn test_multi_sub_wait_for_message() {
+    let bus = create_string_bus_arc();
+    let mut subscriber = bus.subscribe_many(&["x", "y"]);
+
+    let publisher = bus.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        publisher.publish("y", "hello".to_string());
+    });
+
+    let (topic, message) = timeout(Duration::from_secs(1), subscriber.wait_for_message())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(&*topic, "y");
+    assert_eq!(message, "hello");
+
+    bus.publish("x", "world".to_string());
+    let result = subscriber
+        .wait_for_message_and_apply(|topic, msg| format!("{topic}:{}", msg.len()))
+        .await;
+    assert_eq!(result, Some("x:5".to_string()));
+}
+
+#[tokio::test]
+async fn test_multi_sub_dynamic_add_remove() {
+    let bus = create_string_bus();
+    let mut subscriber = MultiSub::new();
+
+    subscriber.add(bus.subscribe("late"));
+    bus.publish("late", "joined".to_string());
+    let (topic, _) = subscriber.wait_for_message().await.unwrap();
+    assert_eq!(&*topic, "late");
+
+    subscriber.remove("late");
+    bus.publish("late", "ignored".to_string());
+    assert_eq!(subscriber.wait_for_message().await, None);
+}
+
+#[tokio::test]
+async fn test_multi_sub_drops_removed_topics() {
+    let bus = create_string_bus();
+    let mut subscriber = bus.subscribe_many(&["keep", "drop"]);
+
+    bus.remove_topic("drop");
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+    assert_eq!(subscriber.topic_names(), vec!["keep".to_string()]);
+
+    bus.remove_topic("keep");
+    assert!(subscriber.try_get_message().unwrap_err().is_disconnected());
+    assert!(subscriber.is_empty());
+}
@@ -0,0 +1,188 @@
+#![cfg(feature = "net")]
+
+mod common;
+
+use common::*;
+use dropslot::Bus;
+use dropslot::net::{BusServer, RemoteBus};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+
+const WAIT: Duration = Duration::from_secs(5);
+
+async fn start_server(addr: &str, bus: Arc<Bus<String>>) -> (SocketAddr, JoinHandle<()>) {
+    let server = BusServer::bind(addr, bus).await.unwrap();
+    let addr = server.local_addr().unwrap();
+    (addr, tokio::spawn(server.serve()))
+}
+
+#[tokio::test]
+async fn test_subscribe_receives_current_value_and_updates() {
+    let bus = create_string_bus_arc();
+    bus.publish("status", "online".to_string());
+    let (addr, _server) = start_server("127.0.0.1:0", bus.clone()).await;
+
+    let remote = RemoteBus::<String>::connect(addr).await.unwrap();
+    let mut status = remote.subscribe("status");
+    let message = timeout(WAIT, status.wait_for_message()).await.unwrap();
+    assert_eq!(message, Some("online".to_string()));
+
+    bus.publish("status", "busy".to_string());
+    let delivery = timeout(WAIT, status.wait_for_delivery()).await.unwrap();
+    let delivery = delivery.unwrap();
+    assert_eq!((delivery.message.as_str(), delivery.version), ("busy", 2));
+    assert_eq!(remote.subscribed_topics(), vec!["status".to_string()]);
+}
+
+#[tokio::test]
+async fn test_publish_reaches_server_and_other_clients() {
+    let bus = create_string_bus_arc();
+    let (addr, _server) = start_server("127.0.0.1:0", bus.clone()).await;
+    let mut local = bus.subscribe("chat");
+
+    let sender = RemoteBus::<String>::connect(addr).await.unwrap();
+    let receiver = RemoteBus::<String>::connect(addr).await.unwrap();
+    let mut remote = receiver.topic("chat").subscribe();
+    // Let the subscription reach the server before publishing.
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    sender.topic("chat").publish("hi".to_string()).unwrap();
+    let message = timeout(WAIT, local.wait_for_message()).await.unwrap();
+    assert_eq!(message, Some("hi".to_string()));
+    let message = timeout(WAIT, remote.wait_for_message()).await.unwrap();
+    assert_eq!(message, Some("hi".to_string()));
+}
+
+#[tokio::test]
+async fn test_unsent_publishes_are_conflated() {
+    let bus = create_string_bus_arc();
+    let (addr, _server) = start_server("127.0.0.1:0", bus.clone()).await;
+    let mut local = bus.subscribe("counter");
+
+    let remote = RemoteBus::<String>::connect(addr).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    for i in 0..10_000 {
+        remote.publish("counter", i.to_string()).unwrap();
+    }
+
+    while timeout(WAIT, local.wait_for_message()).await.unwrap() != Some("9999".to_string()) {}
+    assert!(bus.topic("counter").version() < 10_000);
+}
+
+#[tokio::test]
+async fn test_reconnect_resubscribes_and_receives_latest() {
+    let bus = create_string_bus_arc();
+    bus.publish("config", "v1".to_string());
+    let (addr, server) = start_server("127.0.0.1:0", bus).await;
+
+    let remote = RemoteBus::<String>::connect(addr).await.unwrap();
+    let mut config = remote.subscribe("config");
+    let message = timeout(WAIT, config.wait_for_message()).await.unwrap();
+    assert_eq!(message, Some("v1".to_string()));
+
+    server.abort();
+    let _ = server.await;
+    timeout(WAIT, async {
+        while remote.is_connected() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert!(remote.publish("config", "lost".to_string()).is_err());
+
+    // A restarted server with fresh state; the client picks it up on its own.
+    let bus = create_string_bus_arc();
+    bus.publish("config", "v2".to_string());
+    let (_, _server) = start_server(&addr.to_string(), bus).await;
+
+    let message = timeout(WAIT, config.wait_for_message()).await.unwrap();
+    assert_eq!(message, Some("v2".to_string()));
+    assert!(remote.is_connected());
+}
+
+#[tokio::test]
+async fn test_unsubscribe_stops_updates() {
+    let bus = create_string_bus_arc();
+    let (addr, _server) = start_server("127.0.0.1:0", bus.clone()).await;
+
+    let remote = RemoteBus::<String>::connect(addr).await.unwrap();
+    let mut ticks = remote.subscribe("ticks");
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    bus.publish("ticks", "1".to_string());
+    let message = timeout(WAIT, ticks.wait_for_message()).await.unwrap();
+    assert_eq!(message, Some("1".to_string()));
+
+    assert!(remote.unsubscribe("ticks"));
+    assert!(!remote.unsubscribe("ticks"));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    bus.publish("ticks", "2".to_string());
+    assert!(
+        timeout(Duration::from_millis(100), ticks.wait_for_message())
+            .await
+            .is_err()
+    );
+    assert_eq!(ticks.get_latest(), Some("1".to_string()));
+}
+
+#[cfg(all(unix, feature = "ipc"))]
+#[tokio::test]
+async fn test_unix_socket_transport_and_reconnect() {
+    use bytes::Bytes;
+
+    let path = std::env::temp_dir().join(format!("dropslot-net-{}.sock", std::process::id()));
+    let bus = Arc::new(Bus::<Bytes>::new());
+    bus.publish("frame", Bytes::from_static(b"\x00first"));
+    let server = BusServer::bind_unix(&path, bus.clone()).await.unwrap();
+    assert!(server.local_addr().is_err());
+    let handle = tokio::spawn(server.serve());
+
+    let remote = RemoteBus::<Bytes>::connect_unix(&path).await.unwrap();
+    let mut frames = remote.subscribe("frame");
+    let message = timeout(WAIT, frames.wait_for_message()).await.unwrap();
+    assert_eq!(message, Some(Bytes::from_static(b"\x00first")));
+
+    remote
+        .publish("frame", Bytes::from_static(b"\xffsecond"))
+        .unwrap();
+    let message = timeout(WAIT, frames.wait_for_message()).await.unwrap();
+    assert_eq!(message, Some(Bytes::from_static(b"\xffsecond")));
+
+    // Dropping the server removes the socket file; a new one takes its place.
+    handle.abort();
+    let _ = handle.await;
+    assert!(!path.exists());
+    let server = BusServer::bind_unix(&path, bus.clone()).await.unwrap();
+    let handle = tokio::spawn(server.serve());
+    bus.publish("frame", Bytes::from_static(b"third"));
+
+    let message = timeout(WAIT, frames.wait_for_message()).await.unwrap();
+    assert_eq!(message, Some(Bytes::from_static(b"third")));
+    handle.abort();
+}
+
+#[cfg(all(unix, feature = "ipc"))]
+#[tokio::test]
+async fn test_bind_unix_replaces_only_stale_sockets() {
+    use bytes::Bytes;
+
+    let path = std::env::temp_dir().join(format!("dropslot-stale-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    // A listener dropped without cleanup leaves its socket file behind.
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+
+    let bus = Arc::new(Bus::<Bytes>::new());
+    let server = BusServer::bind_unix(&path, bus.clone()).await.unwrap();
+    let Err(error) = BusServer::bind_unix(&path, bus).await else {
+        panic!("bound over a live server");
+    };
+    assert_eq!(error.kind(), std::io::ErrorKind::AddrInUse);
+    assert!(path.exists());
+
+    drop(server);
+    assert!(!path.exists());
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use common::*;
+use dropslot::TopicPattern;
+use std::time::Duration;
+use tokio::time::timeout;
+
+#[test]
+fn test_pattern_validation() {
+    assert!(TopicPattern::new("sensors/floor1/temp").is_ok());
+    assert!(TopicPattern::new("sensors/+/temp").is_ok());
+    assert!(TopicPattern::new("sensors/#").is_ok());
+    assert!(TopicPattern::new("#").is_ok());
+    assert!(TopicPattern::new("+").is_ok());
+
+    let error = TopicPattern::new("sensors/#/temp").unwrap_err();
+    assert!(error.is_invalid_pattern());
+    assert!(error.to_string().contains("sensors/#/temp"));
+
+    assert!(TopicPattern::new("sensors/floor+").is_err());
+    assert!(TopicPattern::new("sensors/te#mp").is_err());
+    assert!("sensors/+".parse::<TopicPattern>().is_ok());
+}
+
+#[test]
+fn test_pattern_matching() {
+    let single = TopicPattern::new("sensors/+/temp").unwrap();
+    assert!(single.is_wildcard());
+    assert!(single.matches("sensors/floor1/temp"));
+    assert!(!single.matches("sensors/floor1/humidity"));
+    assert!(!single.matches("sensors/floor1/room2/temp"));
+    assert!(!single.matches("sensors/temp"));
+
+    let multi = TopicPattern::new("sensors/#").unwrap();
+    assert!(multi.matches("sensors"));
+    assert!(multi.matches("sensors/floor1"));
+    assert!(multi.matches("sensors/floor1/room2/temp"));
+    assert!(!multi.matches("actuators/floor1"));
+
+    let exact = TopicPattern::new("sensors/floor1").unwrap();
+    assert!(!exact.is_wildcard());
+    assert!(exact.matches("sensors/floor1"));
+    assert!(!exact.matches("sensors/floor1/temp"));
+    assert_eq!(exact.as_str(), "sensors/floor1");
+    assert_eq!(exact.to_string(), "sensors/floor1");
+}
+
+#[test]
+fn test_subscribe_pattern_rejects_invalid() {
+    let bus = create_string_bus();
+    let result = bus.subscribe_pattern("logs/#/error");
+    assert!(result.unwrap_err().is_invalid_pattern());
+}
+
+#[tokio::test]
+async fn test_pattern_receives_existing_and_new_topics() {
+    let bus = create_string_bus();
+    bus.publish("sensors/floor1/temp", "21.5".to_string());
+    let _unrelated = bus.topic("sensors/floor1/humidity");
+
+    let mut subscriber = bus.subscribe_pattern("sensors/+/temp").unwrap();
+    assert_eq!(subscriber.pattern().as_str(), "sensors/+/temp");
+    assert_eq!(subscriber.topic_count(), 1);
+
+    let (topic, value) = subscriber.wait_for_message().await.unwrap();
+    assert_eq!(&*topic, "sensors/floor1/temp");
+    assert_eq!(value, "21.5");
+
+    bus.publish("sensors/floor2/temp", "19.0".to_string());
+    bus.publish("sensors/floor2/humidity", "40".to_string());
+    assert_eq!(subscriber.topic_count(), 2);
+
+    let (topic, value) = subscriber.wait_for_message().await.unwrap();
+    assert_eq!(&*topic, "sensors/floor2/temp");
+    assert_eq!(value, "19.0");
+
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+}
+
+#[tokio::test]
+async fn test_pattern_latest_only_per_topic() {
+    let bus = create_string_bus();
+    let mut subscriber = bus.subscribe_pattern("logs/#").unwrap();
+
+    bus.publish("logs/app", "first".to_string());
+    bus.publish("logs/app", "second".to_string());
+
+    let (topic, value) = subscriber.try_get_message().unwrap();
+    assert_eq!(&*topic, "logs/app");
+    assert_eq!(value, "second");
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+}
+
+#[tokio::test]
+async fn test_pattern_round_robin_between_topics() {
+    let bus = create_string_bus();
+    let mut subscriber = bus.subscribe_pattern("+/status").unwrap();
+
+    bus.publish("a/status", "a1".to_string());
+    bus.publish("b/status", "b1".to_string());
+
+    let (first, _) = subscriber.try_get_message().unwrap();
+    bus.publish(&first, "again".to_string());
+    let (second, _) = subscriber.try_get_message().unwrap();
+
+    assert_ne!(first, second);
+}
+
+#[tokio::test]
+async fn test_pattern_wakes_on_new_topic() {
+    let bus = create_string_bus_arc();
+    let mut subscriber = bus.subscribe_pattern("jobs/#").unwrap();
+
+    let publisher = bus.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        publisher.publish("jobs/42/done", "ok".to_string());
+    });
+
+    let (topic, value) = timeout(Duration::from_secs(1), subscriber.wait_for_message())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(&*topic, "jobs/42/done");
+    assert_eq!(value, "ok");
+}
+
+#[tokio::test]
+async fn test_pattern_drops_removed_topics() {
+    let bus = create_string_bus();
+    let mut subscriber = bus.subscribe_pattern("tmp/+").unwrap();
+
+    bus.topic("tmp/a");
+    bus.topic("tmp/b");
+    assert_eq!(subscriber.topic_count(), 2);
+
+    bus.remove_topic("tmp/a");
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+    assert_eq!(subscriber.topic_count(), 1);
+}
+
+#[tokio::test]
+async fn test_pattern_ends_when_bus_dropped() {
+    let bus = create_string_bus();
+    let mut subscriber = bus.subscribe_pattern("gone/#").unwrap();
+    bus.topic("gone/a");
+
+    drop(bus);
+
+    assert_eq!(subscriber.wait_for_message().await, None);
+    assert!(subscriber.try_get_message().unwrap_err().is_disconnected());
+}
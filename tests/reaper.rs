@@ -0,0 +1,143 @@
+use dropslot::{Bus, LifecycleEvent, ReapPolicy, ReapReason, Reaper};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+fn sorted(mut names: Vec<String>) -> Vec<String> {
+    names.sort();
+    names
+}
+
+#[test]
+fn test_default_policy_removes_nothing() {
+    let bus = Bus::<u32>::new();
+    bus.topic("a");
+    bus.publish("b", 1);
+    assert!(bus.reap(&ReapPolicy::default()).is_empty());
+    assert_eq!(bus.topic_count(), 2);
+}
+
+#[test]
+fn test_unsubscribed_timeout() {
+    let bus = Bus::<u32>::new();
+    let _subscriber = bus.subscribe("watched");
+    let dropped = bus.subscribe("abandoned");
+    bus.topic("never");
+
+    let policy = ReapPolicy::new().unsubscribed_after(Duration::from_millis(30));
+    assert!(bus.reap(&policy).is_empty());
+
+    drop(dropped);
+    thread::sleep(Duration::from_millis(40));
+    let report = bus.reap(&policy);
+    let mut names = report.removed_names();
+    names.sort_unstable();
+    assert_eq!(names, ["abandoned", "never"]);
+    for reaped in &report.removed {
+        match reaped.reason {
+            ReapReason::Unsubscribed(elapsed) => assert!(elapsed >= Duration::from_millis(30)),
+            other => panic!("unexpected reason {other:?}"),
+        }
+    }
+    assert_eq!(bus.topic_names(), ["watched"]);
+}
+
+#[test]
+fn test_resubscribing_resets_unsubscribed_clock() {
+    let bus = Bus::<u32>::new();
+    drop(bus.subscribe("t"));
+    thread::sleep(Duration::from_millis(40));
+    let subscriber = bus.subscribe("t");
+    drop(subscriber);
+
+    let policy = ReapPolicy::new().unsubscribed_after(Duration::from_millis(30));
+    assert!(bus.reap(&policy).is_empty());
+}
+
+#[test]
+fn test_idle_timeout_disconnects_subscribers() {
+    let bus = Bus::<u32>::new();
+    let mut subscriber = bus.subscribe("quiet");
+    bus.publish("quiet", 1);
+    assert_eq!(subscriber.try_get_message(), Ok(Some(1)));
+
+    let policy = ReapPolicy::new().idle_after(Duration::from_millis(30));
+    assert!(bus.reap(&policy).is_empty());
+
+    thread::sleep(Duration::from_millis(40));
+    let report = bus.reap(&policy);
+    assert_eq!(report.removed_names(), ["quiet"]);
+    assert!(matches!(report.removed[0].reason, ReapReason::Idle(_)));
+    assert_eq!(subscriber.recv_blocking(), None);
+}
+
+#[test]
+fn test_keep_alive_for_pinned_and_retained_topics() {
+    let bus = Bus::<u32>::new();
+    bus.topic("pinned").set_pinned(true);
+    bus.publish("retained", 1);
+    bus.topic("expired").publish_with_ttl(1, Duration::ZERO);
+    bus.topic("empty");
+
+    let policy = ReapPolicy::new()
+        .unsubscribed_after(Duration::ZERO)
+        .keep_retained(true);
+    let report = bus.reap(&policy);
+    let mut names = report.removed_names();
+    names.sort_unstable();
+    assert_eq!(names, ["empty", "expired"]);
+    assert_eq!(sorted(bus.topic_names()), ["pinned", "retained"]);
+
+    bus.topic("pinned").set_pinned(false);
+    let report = bus.reap(&policy);
+    assert_eq!(report.removed_names(), ["pinned"]);
+}
+
+#[test]
+fn test_reaping_removes_derived_topics_and_emits_events() {
+    let bus = Bus::<u32>::new();
+    bus.map("n", "n/doubled", |n| n * 2).unwrap();
+    let _watcher = bus.subscribe("n/doubled");
+    let mut events = bus.lifecycle_events();
+
+    let report = bus.reap(&ReapPolicy::new().unsubscribed_after(Duration::ZERO));
+    assert_eq!(report.removed_names(), ["n"]);
+    assert_eq!(bus.topic_count(), 0);
+    assert_eq!(
+        std::iter::from_fn(|| events.try_get_event()).collect::<Vec<_>>(),
+        [
+            LifecycleEvent::TopicRemoved("n".into()),
+            LifecycleEvent::TopicRemoved("n/doubled".into())
+        ]
+    );
+}
+
+#[test]
+fn test_reaper_tick_stops_with_bus() {
+    let bus = Arc::new(Bus::<u32>::new());
+    bus.topic("a");
+    let reaper = Reaper::new(&bus, ReapPolicy::new().idle_after(Duration::ZERO));
+
+    assert_eq!(reaper.tick().unwrap().removed_names(), ["a"]);
+    assert!(reaper.tick().unwrap().is_empty());
+    drop(bus);
+    assert_eq!(reaper.tick(), None);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_reaper_runs_until_bus_is_dropped() {
+    let bus = Arc::new(Bus::<u32>::new());
+    let (reports, mut received) = tokio::sync::mpsc::unbounded_channel();
+    let reaper = Reaper::new(&bus, ReapPolicy::new().unsubscribed_after(Duration::ZERO));
+    let task = tokio::spawn(reaper.run(Duration::from_millis(5), move |report| {
+        reports.send(report).unwrap();
+    }));
+
+    bus.topic("temp");
+    let report = received.recv().await.unwrap();
+    assert_eq!(report.removed_names(), ["temp"]);
+
+    drop(bus);
+    task.await.unwrap();
+}
@@ -0,0 +1,141 @@
+use dropslot::{Bus, BusError};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_request_reply_with_handler() {
+    let bus = Bus::<String>::new();
+    tokio::spawn(
+        bus.responder("echo")
+            .serve(|message| message.to_uppercase()),
+    );
+
+    assert_eq!(bus.request("echo", "hi".to_string()).await.unwrap(), "HI");
+    assert_eq!(
+        bus.request("echo", "again".to_string()).await.unwrap(),
+        "AGAIN"
+    );
+}
+
+#[tokio::test]
+async fn test_replies_are_correlated_by_id() {
+    let bus = Arc::new(Bus::<u32>::new());
+    let mut responder = bus.responder("square");
+
+    let requests: Vec<_> = (0..20)
+        .map(|n| {
+            let bus = bus.clone();
+            tokio::spawn(async move { bus.request("square", n).await })
+        })
+        .collect();
+
+    let mut received = Vec::new();
+    while received.len() < 20 {
+        received.push(responder.wait_for_request().await.unwrap());
+    }
+    let mut ids: Vec<_> = received.iter().map(|request| request.id()).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    assert_eq!(ids.len(), 20);
+
+    // Answer in reverse order of arrival.
+    while let Some(request) = received.pop() {
+        assert_eq!(request.topic(), "square");
+        let n = *request.message();
+        request.reply(n * n);
+    }
+    for (n, request) in requests.into_iter().enumerate() {
+        let n = n as u32;
+        assert_eq!(request.await.unwrap(), Ok(n * n));
+    }
+}
+
+#[tokio::test]
+async fn test_missing_and_dropped_responders() {
+    let bus = Bus::<u32>::new();
+    assert_eq!(
+        bus.request("nobody", 1).await,
+        Err(BusError::no_responder("nobody"))
+    );
+
+    // A request dropped without a reply.
+    let mut responder = bus.responder("flaky");
+    let (result, ()) = tokio::join!(bus.request("flaky", 1), async {
+        drop(responder.wait_for_request().await.unwrap());
+    });
+    assert!(result.unwrap_err().is_no_responder());
+
+    drop(responder);
+    assert!(bus.request("flaky", 2).await.unwrap_err().is_no_responder());
+}
+
+#[tokio::test]
+async fn test_queued_requests_fail_when_last_responder_drops() {
+    let bus = Arc::new(Bus::<u32>::new());
+    let first = bus.responder("work");
+    let second = bus.responder("work");
+
+    let pending = {
+        let bus = bus.clone();
+        tokio::spawn(async move { bus.request("work", 1).await })
+    };
+    tokio::task::yield_now().await;
+    drop(first);
+    tokio::task::yield_now().await;
+    assert!(!pending.is_finished());
+
+    drop(second);
+    assert!(pending.await.unwrap().unwrap_err().is_no_responder());
+}
+
+#[tokio::test]
+async fn test_responders_share_requests() {
+    let bus = Bus::<u32>::new();
+    let mut first = bus.responder("work");
+    let mut second = bus.responder("work");
+
+    let requests = async {
+        let (a, b) = tokio::join!(bus.request("work", 1), bus.request("work", 2));
+        (a.unwrap(), b.unwrap())
+    };
+    let responders = async {
+        let a = first.wait_for_request().await.unwrap();
+        let b = second.wait_for_request().await.unwrap();
+        assert_ne!(a.id(), b.id());
+        let (a_reply, b_reply) = (a.message() + 10, b.message() + 20);
+        a.reply(a_reply);
+        b.reply(b_reply);
+    };
+    let ((a, b), ()) = tokio::join!(requests, responders);
+    assert_eq!((a, b), (11, 22));
+}
+
+#[tokio::test]
+async fn test_responder_ends_with_bus() {
+    let bus = Bus::<u32>::new();
+    let mut responder = bus.responder("t");
+    drop(bus);
+    assert!(responder.wait_for_request().await.is_none());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_request_timeout() {
+    use std::time::Duration;
+
+    let bus = Bus::<u32>::new();
+    let mut responder = bus.responder("slow");
+
+    let result = bus
+        .request_timeout("slow", 1, Duration::from_millis(20))
+        .await;
+    let error = result.unwrap_err();
+    assert!(error.is_timeout());
+    assert_eq!(error.topic(), Some("slow"));
+
+    // The withdrawn request can still be answered without effect.
+    responder.try_get_request().unwrap().reply(2);
+
+    tokio::spawn(responder.serve(|n| n + 1));
+    let result = bus.request_timeout("slow", 1, Duration::from_secs(5)).await;
+    assert_eq!(result, Ok(2));
+}
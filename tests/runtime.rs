@@ -0,0 +1,79 @@
+mod common;
+
+use common::*;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_wait_for_message_under_foreign_executor() {
+    let bus = create_string_bus_arc();
+    let topic = bus.topic("executor");
+    let mut subscriber = topic.subscribe();
+
+    let publisher = {
+        let topic = topic.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            topic.publish("from thread".to_string());
+        })
+    };
+
+    let message = pollster::block_on(subscriber.wait_for_message());
+    assert_eq!(message, Some("from thread".to_string()));
+    publisher.join().unwrap();
+
+    drop(topic);
+    bus.remove_topic("executor");
+    assert_eq!(pollster::block_on(subscriber.wait_for_message()), None);
+}
+
+#[test]
+fn test_multi_sub_and_drain_under_foreign_executor() {
+    let bus = create_string_bus_arc();
+    let mut subscriber = bus.subscribe_many(&["left", "right"]);
+
+    let publisher = bus.clone();
+    let handle = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(10));
+        publisher.publish("right", "hello".to_string());
+    });
+
+    let (topic, message) = pollster::block_on(subscriber.wait_for_message()).unwrap();
+    assert_eq!((&*topic, message.as_str()), ("right", "hello"));
+    handle.join().unwrap();
+
+    let topic = bus.topic("drained");
+    let mut drainer = topic.subscribe();
+    topic.publish("a".to_string());
+    topic.publish("b".to_string());
+    let drained = pollster::block_on(drainer.wait_for_drain()).unwrap();
+    assert_eq!(drained.latest().unwrap().message, "b");
+    assert_eq!(drained.dropped, 1);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_wait_for_message_timeout() {
+    let bus = create_string_bus();
+    let topic = bus.topic("timeout");
+    let mut subscriber = topic.subscribe();
+
+    let result = subscriber
+        .wait_for_message_timeout(Duration::from_millis(10))
+        .await;
+    assert!(result.unwrap_err().is_timeout());
+
+    topic.publish("Ready".to_string());
+    let delivery = subscriber
+        .wait_for_delivery_timeout(Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert_eq!((delivery.message.as_str(), delivery.version), ("Ready", 1));
+
+    drop(topic);
+    bus.remove_topic("timeout");
+    let result = subscriber
+        .wait_for_message_timeout(Duration::from_secs(1))
+        .await;
+    assert!(result.unwrap_err().is_disconnected());
+}
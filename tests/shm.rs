@@ -0,0 +1,70 @@
+#![cfg(feature = "shm")]
+
+use dropslot::shm::{ShmSub, ShmTopic};
+use std::path::PathBuf;
+
+fn shm_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("dropslot-shm-{name}-{}", std::process::id()))
+}
+
+#[test]
+fn test_versions_and_disconnect() {
+    let path = shm_path("versions");
+    let mut topic = ShmTopic::<u64>::create(&path).unwrap();
+    let mut subscriber = ShmSub::<u64>::open(&path).unwrap();
+    assert!(!subscriber.has_latest());
+    assert_eq!(subscriber.get_latest(), None);
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+
+    topic.publish(1);
+    topic.publish(2);
+    assert_eq!(topic.get_current_version(), 2);
+    assert_eq!(subscriber.get_current_version(), 2);
+    assert_eq!(subscriber.try_get_message().unwrap(), Some(2));
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+
+    topic.publish(3);
+    drop(topic);
+    assert!(subscriber.is_closed());
+    assert_eq!(subscriber.try_get_message().unwrap(), Some(3));
+    assert!(subscriber.try_get_message().unwrap_err().is_disconnected());
+    assert_eq!(subscriber.get_latest(), Some(3));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_reopened_writer_continues_versions() {
+    let path = shm_path("reopen");
+    let mut topic = ShmTopic::<[f32; 3]>::create(&path).unwrap();
+    topic.publish([1.0, 2.0, 3.0]);
+    drop(topic);
+
+    let mut subscriber = ShmSub::<[f32; 3]>::open(&path).unwrap();
+    assert_eq!(subscriber.get_latest(), Some([1.0, 2.0, 3.0]));
+
+    let mut topic = ShmTopic::<[f32; 3]>::create(&path).unwrap();
+    assert!(!subscriber.is_closed());
+    assert_eq!(topic.get_current_version(), 1);
+    topic.publish([4.0, 5.0, 6.0]);
+    assert_eq!(subscriber.try_get_message().unwrap(), Some([4.0, 5.0, 6.0]));
+    assert_eq!(subscriber.get_current_version(), 2);
+
+    drop(topic);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_open_rejects_foreign_files() {
+    let path = shm_path("foreign");
+    std::fs::write(&path, [0u8; 128]).unwrap();
+    let error = ShmSub::<u64>::open(&path).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+    let topic = ShmTopic::<u64>::create(&path).unwrap();
+    let error = ShmSub::<[u64; 2]>::open(&path).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+    drop(topic);
+    std::fs::remove_file(&path).unwrap();
+}
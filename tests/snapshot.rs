@@ -0,0 +1,160 @@
+mod common;
+
+use common::*;
+use dropslot::{Bus, BusSnapshot, DeliveryMode, TopicSnapshot};
+
+#[test]
+fn test_snapshot_captures_latest_values_and_versions() {
+    let bus = create_string_bus();
+    bus.publish("b", "b1".to_string());
+    bus.publish("a", "a1".to_string());
+    bus.publish("a", "a2".to_string());
+    let _empty = bus.topic("empty");
+
+    let snapshot = bus.snapshot();
+    assert_eq!(
+        snapshot.topics,
+        vec![
+            TopicSnapshot {
+                name: "a".to_string(),
+                version: 2,
+                value: "a2".to_string(),
+            },
+            TopicSnapshot {
+                name: "b".to_string(),
+                version: 1,
+                value: "b1".to_string(),
+            },
+        ]
+    );
+    assert!(snapshot.get("empty").is_none());
+    assert!(create_string_bus().snapshot().is_empty());
+}
+
+#[test]
+fn test_restore_resumes_versions() {
+    let bus = create_string_bus();
+    for i in 0..5 {
+        bus.publish("counter", format!("value {i}"));
+    }
+    let snapshot = bus.snapshot();
+
+    let restored = create_string_bus();
+    let history = restored.topic_with_mode("audit", DeliveryMode::history(4));
+    let mut existing = history.subscribe();
+    assert_eq!(restored.restore(snapshot), 1);
+    assert_eq!(restored.topic_count(), 2);
+
+    let mut subscriber = restored.subscribe("counter");
+    assert_eq!(subscriber.get_latest(), Some("value 4".to_string()));
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+
+    restored.publish("counter", "value 5".to_string());
+    let delivery = subscriber.try_get_delivery().unwrap().unwrap();
+    assert_eq!(
+        (delivery.message.as_str(), delivery.version),
+        ("value 5", 6)
+    );
+
+    let snapshot = BusSnapshot {
+        topics: vec![TopicSnapshot {
+            name: "audit".to_string(),
+            version: 10,
+            value: "restored".to_string(),
+        }],
+    };
+    restored.restore(snapshot);
+    assert_eq!(history.delivery_mode(), DeliveryMode::history(4));
+    let drained = existing.try_drain().unwrap();
+    assert_eq!(drained.latest().unwrap().seq, 10);
+    assert_eq!(drained.latest().unwrap().message, "restored");
+}
+
+#[test]
+fn test_restore_never_moves_versions_backwards() {
+    let bus = create_string_bus();
+    bus.publish("status", "old".to_string());
+    let snapshot = bus.snapshot();
+    bus.publish("status", "new".to_string());
+
+    let mut subscriber = bus.subscribe("status");
+    assert_eq!(bus.restore(snapshot), 0);
+    assert_eq!(subscriber.get_latest(), Some("new".to_string()));
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+
+    bus.publish("status", "newer".to_string());
+    assert_eq!(subscriber.try_get_delivery().unwrap().unwrap().version, 3);
+}
+
+#[test]
+fn test_restore_keeps_derived_operators() {
+    let bus = Bus::<i32>::new();
+    bus.map("raw", "doubled", |v| v * 2).unwrap();
+    bus.publish("raw", 21);
+    let snapshot = bus.snapshot();
+    assert_eq!(snapshot.get("doubled").unwrap().value, 42);
+
+    let restored = Bus::<i32>::new();
+    let doubled = restored.map("raw", "doubled", |v| v * 2).unwrap();
+    restored.restore(snapshot);
+    assert!(doubled.is_derived());
+    assert_eq!(restored.subscribe("doubled").get_latest(), Some(42));
+
+    restored.publish("raw", 5);
+    assert_eq!(restored.subscribe("doubled").get_latest(), Some(10));
+}
+
+#[test]
+fn test_write_to_file_replaces_atomically() {
+    let dir = std::env::temp_dir().join(format!("dropslot-snapshot-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("state.txt");
+
+    let bus = create_string_bus();
+    bus.publish("status", "online".to_string());
+    let snapshot = bus.snapshot();
+
+    snapshot
+        .write_to_file(&path, |snapshot, writer| {
+            writeln!(writer, "{}", snapshot.len())
+        })
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "1\n");
+
+    let error = snapshot
+        .write_to_file(&path, |_, writer| {
+            writer.write_all(b"partial")?;
+            Err(std::io::Error::other("encoding failed"))
+        })
+        .unwrap_err();
+    assert_eq!(error.to_string(), "encoding failed");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "1\n");
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_snapshot_serde_round_trip() {
+    let bus = create_string_bus();
+    bus.publish("status", "online".to_string());
+    bus.publish("status", "busy".to_string());
+
+    let path = std::env::temp_dir().join(format!("dropslot-serde-{}.json", std::process::id()));
+    bus.snapshot()
+        .write_to_file(&path, |snapshot, writer| {
+            serde_json::to_writer(writer, snapshot).map_err(std::io::Error::from)
+        })
+        .unwrap();
+
+    let file = std::fs::File::open(&path).unwrap();
+    let snapshot: BusSnapshot<String> = serde_json::from_reader(file).unwrap();
+    assert_eq!(snapshot, bus.snapshot());
+    std::fs::remove_file(&path).unwrap();
+
+    let restored = create_string_bus();
+    restored.restore(snapshot);
+    let topic = restored.topic("status");
+    assert_eq!(topic.subscribe().get_latest(), Some("busy".to_string()));
+}
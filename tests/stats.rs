@@ -0,0 +1,219 @@
+use dropslot::{Bus, DeliveryMode};
+
+#[test]
+fn test_topic_counters() {
+    let bus = Bus::<u32>::new();
+    let topic = bus.topic("ticks");
+    let mut first = topic.subscribe();
+    let mut second = topic.subscribe();
+
+    for value in 1..=3 {
+        topic.publish(value);
+    }
+    assert_eq!(first.try_get_message().unwrap(), Some(3));
+    assert!(first.try_get_message().unwrap_err().is_empty());
+    assert!(first.try_get_message().unwrap_err().is_empty());
+    topic.publish(4);
+    assert_eq!(second.try_get_message().unwrap(), Some(4));
+
+    let stats = topic.stats();
+    assert_eq!(stats.name, "ticks");
+    assert_eq!(stats.version, 4);
+    assert_eq!(stats.subscribers, 2);
+    assert_eq!(stats.publishes, 4);
+    assert_eq!(stats.reads, 2);
+    assert_eq!(stats.empty_polls, 2);
+    assert_eq!(stats.coalesced, 2 + 3);
+
+    drop(second);
+    assert_eq!(topic.stats().subscribers, 1);
+}
+
+#[test]
+fn test_restore_is_not_a_publish() {
+    let bus = Bus::<u32>::new();
+    bus.publish("a", 1);
+    let restarted = Bus::<u32>::new();
+    restarted.restore(bus.snapshot());
+
+    let stats = restarted.topic("a").stats();
+    assert_eq!((stats.version, stats.publishes), (1, 0));
+}
+
+#[test]
+fn test_bus_totals_survive_removal() {
+    let bus = Bus::<u32>::new();
+    let mut subscriber = bus.subscribe("a");
+    bus.publish("a", 1);
+    bus.publish("b", 1);
+    bus.publish("b", 2);
+    bus.map("b", "b/doubled", |value| value * 2).unwrap();
+    assert_eq!(subscriber.try_get_message().unwrap(), Some(1));
+
+    assert_eq!(bus.remove_topic("b"), Some(0));
+    drop(subscriber);
+    assert_eq!(bus.cleanup_unused_topics(), 1);
+    bus.topic("c");
+
+    let stats = bus.stats();
+    assert_eq!(stats.topics_created, 4);
+    assert_eq!(stats.topics_removed, 3);
+    // The derived topic is seeded with the current value of its source.
+    assert_eq!(stats.publishes, 4);
+    assert_eq!(stats.reads, 1);
+    assert_eq!(stats.subscribers, 0);
+    assert_eq!(stats.topics.len(), 1);
+    assert!(stats.get("c").is_some());
+    assert!(stats.get("a").is_none());
+}
+
+#[test]
+fn test_stats_sorted_with_subscriber_totals() {
+    let bus = Bus::<u32>::new();
+    let _z = bus.subscribe("z");
+    let _a1 = bus.subscribe("a");
+    let _a2 = bus.subscribe("a");
+
+    let stats = bus.stats();
+    let names: Vec<_> = stats
+        .topics
+        .iter()
+        .map(|topic| topic.name.as_str())
+        .collect();
+    assert_eq!(names, ["a", "z"]);
+    assert_eq!(stats.subscribers, 3);
+    assert_eq!(stats.get("a").unwrap().subscribers, 2);
+}
+
+#[test]
+fn test_drain_counts_reads_and_evictions() {
+    let bus = Bus::<u32>::new();
+    let topic = bus.topic_with_mode("audit", DeliveryMode::history(2));
+    let mut subscriber = topic.subscribe();
+    for value in 1..=3 {
+        topic.publish(value);
+    }
+    assert_eq!(subscriber.try_drain().unwrap().messages.len(), 2);
+    assert!(subscriber.try_drain().unwrap_err().is_empty());
+
+    let stats = topic.stats();
+    assert_eq!((stats.reads, stats.coalesced, stats.empty_polls), (2, 1, 1));
+}
+
+#[tokio::test]
+async fn test_waiting_is_not_an_empty_poll() {
+    let bus = Bus::<u32>::new();
+    let topic = bus.topic("t");
+    let mut subscriber = topic.subscribe();
+
+    let publisher = {
+        let topic = topic.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            topic.publish(7);
+        })
+    };
+    assert_eq!(subscriber.recv_blocking(), Some(7));
+    publisher.join().unwrap();
+    topic.publish(8);
+    assert_eq!(subscriber.wait_for_message().await, Some(8));
+
+    let stats = topic.stats();
+    assert_eq!((stats.reads, stats.empty_polls), (2, 0));
+}
+
+#[cfg(feature = "metrics")]
+mod metrics_export {
+    use dropslot::Bus;
+    use metrics::{
+        Counter, CounterFn, Gauge, GaugeFn, Histogram, Key, KeyName, Metadata, Recorder,
+        SharedString, Unit,
+    };
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Value(Mutex<f64>);
+
+    impl CounterFn for Value {
+        fn increment(&self, value: u64) {
+            *self.0.lock().unwrap() += value as f64;
+        }
+
+        fn absolute(&self, value: u64) {
+            *self.0.lock().unwrap() = value as f64;
+        }
+    }
+
+    impl GaugeFn for Value {
+        fn increment(&self, value: f64) {
+            *self.0.lock().unwrap() += value;
+        }
+
+        fn decrement(&self, value: f64) {
+            *self.0.lock().unwrap() -= value;
+        }
+
+        fn set(&self, value: f64) {
+            *self.0.lock().unwrap() = value;
+        }
+    }
+
+    #[derive(Default)]
+    struct TestRecorder(Mutex<HashMap<String, Arc<Value>>>);
+
+    impl TestRecorder {
+        fn value(&self, key: &Key) -> Arc<Value> {
+            let labels: Vec<_> = key
+                .labels()
+                .map(|label| format!("{}={}", label.key(), label.value()))
+                .collect();
+            let name = format!("{}{{{}}}", key.name(), labels.join(","));
+            self.0.lock().unwrap().entry(name).or_default().clone()
+        }
+
+        fn get(&self, name: &str) -> f64 {
+            *self.0.lock().unwrap()[name].0.lock().unwrap()
+        }
+    }
+
+    impl Recorder for TestRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            Counter::from_arc(self.value(key))
+        }
+
+        fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::from_arc(self.value(key))
+        }
+
+        fn register_histogram(&self, _: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::noop()
+        }
+    }
+
+    #[test]
+    fn test_export_metrics() {
+        let bus = Bus::<u32>::new();
+        let mut subscriber = bus.subscribe("ticks");
+        bus.publish("ticks", 1);
+        bus.publish("ticks", 2);
+        subscriber.try_get_message().unwrap();
+
+        let recorder = TestRecorder::default();
+        metrics::with_local_recorder(&recorder, || bus.stats().export_metrics());
+
+        assert_eq!(recorder.get("dropslot_publishes_total{}"), 2.0);
+        assert_eq!(recorder.get("dropslot_topics_created_total{}"), 1.0);
+        assert_eq!(recorder.get("dropslot_topics{}"), 1.0);
+        assert_eq!(recorder.get("dropslot_topic_reads_total{topic=ticks}"), 1.0);
+        assert_eq!(
+            recorder.get("dropslot_topic_coalesced_total{topic=ticks}"),
+            1.0
+        );
+        assert_eq!(recorder.get("dropslot_topic_subscribers{topic=ticks}"), 1.0);
+    }
+}
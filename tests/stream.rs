@@ -0,0 +1,130 @@
+#![cfg(feature = "stream")]
+
+mod common;
+
+use common::*;
+use futures_util::StreamExt;
+use std::time::Duration;
+use tokio::time::timeout;
+
+#[tokio::test]
+async fn test_stream_yields_published_messages() {
+    let bus = create_string_bus();
+    let topic = bus.topic("stream_basic");
+    let mut stream = topic.subscribe().into_stream();
+
+    assert_eq!(stream.topic_name(), "stream_basic");
+
+    topic.publish("Hello".to_string());
+    assert_eq!(stream.next().await, Some("Hello".to_string()));
+
+    topic.publish("World".to_string());
+    assert_eq!(stream.next().await, Some("World".to_string()));
+}
+
+#[tokio::test]
+async fn test_stream_latest_only_semantics() {
+    let bus = create_string_bus();
+    let topic = bus.topic("stream_latest");
+    let mut stream = topic.subscribe().into_stream();
+
+    topic.publish("First".to_string());
+    topic.publish("Second".to_string());
+    topic.publish("Third".to_string());
+
+    assert_eq!(stream.next().await, Some("Third".to_string()));
+    assert!(
+        timeout(Duration::from_millis(10), stream.next())
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_stream_wakes_on_publish_from_other_task() {
+    let bus = create_string_bus_arc();
+    let mut stream = bus.subscribe("stream_wake").into_stream();
+
+    let publisher = bus.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        publisher.publish("stream_wake", "Woken".to_string());
+    });
+
+    let message = timeout(Duration::from_secs(1), stream.next())
+        .await
+        .unwrap();
+    assert_eq!(message, Some("Woken".to_string()));
+}
+
+#[tokio::test]
+async fn test_stream_ends_when_topic_dropped() {
+    let bus = create_string_bus();
+    let topic = bus.topic("stream_end");
+    let mut stream = topic.subscribe().into_stream();
+
+    topic.publish("Last".to_string());
+    assert_eq!(stream.next().await, Some("Last".to_string()));
+
+    drop(topic);
+    bus.remove_topic("stream_end");
+
+    assert_eq!(stream.next().await, None);
+    assert_eq!(stream.next().await, None);
+}
+
+#[tokio::test]
+async fn test_stream_map_applies_function() {
+    let bus = create_string_bus();
+    let topic = bus.topic("stream_map");
+    let mut lengths = topic.subscribe().into_stream_map(|msg| msg.len());
+
+    assert_eq!(lengths.topic_name(), "stream_map");
+
+    topic.publish("hello".to_string());
+    assert_eq!(lengths.next().await, Some(5));
+
+    topic.publish("hello world".to_string());
+    assert_eq!(lengths.next().await, Some(11));
+}
+
+#[tokio::test]
+async fn test_stream_combinators() {
+    let bus = create_string_bus_arc();
+    let stream = bus.subscribe("stream_combinators").into_stream();
+
+    let publisher = bus.clone();
+    tokio::spawn(async move {
+        for i in 0..3 {
+            publisher.publish("stream_combinators", format!("Message {i}"));
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        publisher.remove_topic("stream_combinators");
+    });
+
+    let collected: Vec<_> = timeout(
+        Duration::from_secs(1),
+        stream
+            .filter(|msg| std::future::ready(msg.starts_with("Message")))
+            .collect(),
+    )
+    .await
+    .unwrap();
+    assert!(!collected.is_empty());
+    assert!(collected.len() <= 3);
+}
+
+#[tokio::test]
+async fn test_multi_and_pattern_subscribers_as_streams() {
+    let bus = create_string_bus();
+    let mut multi = bus.subscribe_many(&["a", "b"]);
+    let mut pattern = bus.subscribe_pattern("+").unwrap();
+
+    bus.publish("b", "hello".to_string());
+
+    let (topic, message) = multi.next().await.unwrap();
+    assert_eq!((&*topic, message.as_str()), ("b", "hello"));
+
+    let (topic, message) = pattern.next().await.unwrap();
+    assert_eq!((&*topic, message.as_str()), ("b", "hello"));
+}
@@ -2,6 +2,8 @@ mod common;
 
 use bytes::Bytes;
 use common::*;
+use std::thread;
+use std::time::{Duration, Instant};
 
 #[test]
 fn test_subscriber_basic_properties() {
@@ -188,3 +190,150 @@ fn test_subscriber_edge_cases() {
     let unicode_subscriber = unicode_topic.subscribe();
     assert_eq!(unicode_subscriber.topic_name(), "测试🚀");
 }
+
+#[test]
+fn test_try_get_delivery_reports_skipped_updates() {
+    let bus = create_string_bus();
+    let topic = bus.topic("delivery_try");
+    let mut subscriber = topic.subscribe();
+
+    assert!(subscriber.try_get_delivery().unwrap_err().is_empty());
+
+    topic.publish("First".to_string());
+    let delivery = subscriber.try_get_delivery().unwrap().unwrap();
+    assert_eq!(delivery.message, "First");
+    assert_eq!(delivery.version, 1);
+    assert_eq!(delivery.skipped, 0);
+
+    for i in 0..5 {
+        topic.publish(format!("Message {i}"));
+    }
+    let delivery = subscriber.try_get_delivery().unwrap().unwrap();
+    assert_eq!(delivery.message, "Message 4");
+    assert_eq!(delivery.version, 6);
+    assert_eq!(delivery.skipped, 4);
+
+    assert!(subscriber.try_get_delivery().unwrap_err().is_empty());
+}
+
+#[tokio::test]
+async fn test_wait_for_delivery_reports_skipped_updates() {
+    let bus = create_string_bus();
+    let topic = bus.topic("delivery_wait");
+    let mut subscriber = topic.subscribe();
+
+    topic.publish("A".to_string());
+    topic.publish("B".to_string());
+    let delivery = subscriber.wait_for_delivery().await.unwrap();
+    assert_eq!(delivery.message, "B");
+    assert_eq!((delivery.version, delivery.skipped), (2, 1));
+
+    topic.publish("C".to_string());
+    let delivery = subscriber.wait_for_delivery().await.unwrap();
+    assert_eq!((delivery.version, delivery.skipped), (3, 0));
+    assert_eq!(subscriber.skipped_count(), 1);
+}
+
+#[tokio::test]
+async fn test_cumulative_counters_across_read_methods() {
+    let bus = create_string_bus();
+    let topic = bus.topic("delivery_counters");
+    let mut subscriber = topic.subscribe();
+
+    assert_eq!(subscriber.received_count(), 0);
+    assert_eq!(subscriber.skipped_count(), 0);
+
+    topic.publish("1".to_string());
+    topic.publish("2".to_string());
+    subscriber.try_get_message().unwrap();
+
+    topic.publish("3".to_string());
+    topic.publish("4".to_string());
+    topic.publish("5".to_string());
+    subscriber.wait_for_message().await.unwrap();
+
+    // wait_for_message consumed the update, so there is nothing left to read.
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+
+    topic.publish("6".to_string());
+    subscriber.wait_for_message_and_apply(|msg| msg.len()).await;
+
+    assert_eq!(subscriber.received_count(), 3);
+    assert_eq!(subscriber.skipped_count(), 3);
+
+    // Peeking does not count as a read.
+    subscriber.get_latest();
+    assert_eq!(subscriber.received_count(), 3);
+}
+
+#[test]
+fn test_recv_blocking_without_runtime() {
+    let bus = create_string_bus();
+    let topic = bus.topic("blocking_recv");
+    let mut subscriber = topic.subscribe();
+
+    let consumer = thread::spawn(move || {
+        let first = subscriber.recv_blocking();
+        let second = subscriber.recv_blocking();
+        (first, second, subscriber)
+    });
+
+    thread::sleep(Duration::from_millis(20));
+    topic.publish("First".to_string());
+    thread::sleep(Duration::from_millis(20));
+    topic.publish("Second".to_string());
+
+    let (first, second, mut subscriber) = consumer.join().unwrap();
+    assert_eq!(first.as_deref(), Some("First"));
+    assert_eq!(second.as_deref(), Some("Second"));
+    assert_eq!(subscriber.received_count(), 2);
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+}
+
+#[test]
+fn test_recv_timeout_scenarios() {
+    let bus = create_string_bus();
+    let topic = bus.topic("blocking_timeout");
+    let mut subscriber = topic.subscribe();
+
+    let started = Instant::now();
+    let error = subscriber
+        .recv_timeout(Duration::from_millis(20))
+        .unwrap_err();
+    assert!(error.is_timeout());
+    assert!(started.elapsed() >= Duration::from_millis(20));
+
+    topic.publish("Ready".to_string());
+    assert_eq!(
+        subscriber.recv_timeout(Duration::ZERO),
+        Ok("Ready".to_string())
+    );
+
+    let publisher = {
+        let topic = topic.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            topic.publish("Later".to_string());
+        })
+    };
+    assert_eq!(
+        subscriber.recv_timeout(Duration::from_secs(5)),
+        Ok("Later".to_string())
+    );
+    publisher.join().unwrap();
+}
+
+#[test]
+fn test_recv_blocking_returns_none_when_topic_dropped() {
+    let bus = create_string_bus();
+    let topic = bus.topic("blocking_dropped");
+    let mut subscriber = topic.subscribe();
+
+    let consumer = thread::spawn(move || subscriber.recv_blocking());
+
+    thread::sleep(Duration::from_millis(20));
+    drop(topic);
+    bus.remove_topic("blocking_dropped");
+
+    assert_eq!(consumer.join().unwrap(), None);
+}
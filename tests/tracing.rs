@@ -0,0 +1,153 @@
+#![cfg(feature = "tracing")]
+
+use dropslot::Bus;
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Formats fields as ` name=value`, with the message first and unprefixed.
+#[derive(Default)]
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0.insert_str(0, &format!("{value:?}"));
+        } else {
+            write!(self.0, " {}={value:?}", field.name()).unwrap();
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{value}"));
+    }
+}
+
+/// Records every event as `span > span: message fields`.
+#[derive(Default)]
+struct Capture {
+    next_id: AtomicU64,
+    spans: Mutex<Vec<(u64, String)>>,
+    stack: Mutex<Vec<u64>>,
+    events: Mutex<Vec<String>>,
+}
+
+impl Capture {
+    fn span_label(&self, id: u64) -> String {
+        let spans = self.spans.lock().unwrap();
+        spans
+            .iter()
+            .find(|(span, _)| *span == id)
+            .unwrap()
+            .1
+            .clone()
+    }
+
+    fn events(&self) -> Vec<String> {
+        self.events.lock().unwrap().clone()
+    }
+}
+
+impl Subscriber for Capture {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut fields = Fields::default();
+        span.record(&mut fields);
+        let label = format!("{}{}", span.metadata().name(), fields.0);
+        self.spans.lock().unwrap().push((id, label));
+        Id::from_u64(id)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let path: Vec<String> = match event.parent() {
+            Some(parent) => vec![self.span_label(parent.into_u64())],
+            None => {
+                let stack = self.stack.lock().unwrap().clone();
+                stack.into_iter().map(|id| self.span_label(id)).collect()
+            }
+        };
+        let line = format!("{}: {}", path.join(" > "), fields.0);
+        self.events.lock().unwrap().push(line);
+    }
+
+    fn enter(&self, span: &Id) {
+        self.stack.lock().unwrap().push(span.into_u64());
+    }
+
+    fn exit(&self, _: &Id) {
+        self.stack.lock().unwrap().pop();
+    }
+}
+
+fn capture(f: impl FnOnce()) -> Vec<String> {
+    let capture = Arc::new(Capture::default());
+    tracing::subscriber::with_default(capture.clone(), f);
+    capture.events()
+}
+
+#[test]
+fn test_topic_lifecycle_events() {
+    let events = capture(|| {
+        let bus = Bus::<u32>::new();
+        let mut subscriber = bus.subscribe("a");
+        bus.publish("a", 1);
+        bus.remove_topic("a");
+        assert_eq!(subscriber.recv_blocking(), None);
+    });
+
+    assert_eq!(
+        events,
+        [
+            "topic name=a: topic created mode=Latest",
+            "topic name=a: subscribed version=0 subscribers=1",
+            "topic name=a: published version=1 subscribers=1",
+            "topic name=a: topic removed version=1 subscribers=1",
+            ": topic disconnected topic=a version=0",
+        ]
+    );
+}
+
+#[test]
+fn test_derived_publishes_nest_in_source_span() {
+    let events = capture(|| {
+        let bus = Bus::<u32>::new();
+        bus.map("a", "a/doubled", |value| value * 2).unwrap();
+        bus.publish("a", 1);
+        bus.remove_topic("a");
+    });
+
+    assert!(events.contains(&"topic name=a/doubled: topic created sources=[\"a\"]".to_string()));
+    assert!(events.contains(
+        &"topic name=a > topic name=a/doubled: published version=1 subscribers=0".to_string()
+    ));
+    assert!(events.contains(&"topic name=a/doubled: derived topic removed source=a".to_string()));
+}
+
+#[test]
+fn test_cleanup_span() {
+    let events = capture(|| {
+        let bus = Bus::<u32>::new();
+        bus.topic("idle");
+        let _busy = bus.subscribe("busy");
+        assert_eq!(bus.cleanup_unused_topics(), 1);
+    });
+
+    assert!(events.ends_with(&[
+        "cleanup_unused_topics topics=2 > topic name=idle: topic removed version=0 subscribers=0"
+            .to_string(),
+        "cleanup_unused_topics topics=2: cleanup finished removed=1".to_string(),
+    ]));
+}
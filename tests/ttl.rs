@@ -0,0 +1,82 @@
+use dropslot::{Bus, BusError};
+use std::time::Duration;
+
+#[test]
+fn test_expired_message_reads_as_stale_once() {
+    let bus = Bus::<u32>::new();
+    let topic = bus.topic("readings");
+    let mut subscriber = topic.subscribe();
+
+    topic.publish_with_ttl(1, Duration::ZERO);
+    assert!(topic.is_stale());
+    assert!(!subscriber.has_latest());
+    assert_eq!(subscriber.get_latest(), None);
+    assert_eq!(subscriber.get_latest_with(|value| *value), None);
+    assert_eq!(
+        subscriber.try_get_message(),
+        Err(BusError::stale("readings"))
+    );
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+
+    topic.publish(2);
+    assert!(!subscriber.is_stale());
+    assert_eq!(subscriber.try_get_message(), Ok(Some(2)));
+}
+
+#[test]
+fn test_value_expires_after_ttl() {
+    let bus = Bus::<u32>::new();
+    let topic = bus.topic("heartbeat");
+    let subscriber = topic.subscribe();
+
+    topic.publish_with_ttl(1, Duration::from_millis(30));
+    assert_eq!(subscriber.get_latest(), Some(1));
+    std::thread::sleep(Duration::from_millis(40));
+    assert!(subscriber.is_stale());
+    assert_eq!(subscriber.get_latest(), None);
+    assert!(subscriber.latest_age().unwrap() >= Duration::from_millis(40));
+}
+
+#[test]
+fn test_default_ttl_and_override() {
+    let bus = Bus::<u32>::new();
+    let topic = bus.topic("t");
+    let subscriber = topic.subscribe();
+    assert_eq!(topic.default_ttl(), None);
+
+    topic.set_default_ttl(Some(Duration::ZERO));
+    topic.publish(1);
+    assert_eq!(subscriber.get_latest(), None);
+
+    topic.publish_with_ttl(2, Duration::from_secs(60));
+    assert_eq!(subscriber.get_latest(), Some(2));
+
+    topic.set_default_ttl(None);
+    topic.publish(3);
+    assert_eq!(subscriber.get_latest(), Some(3));
+    assert!(topic.latest_age().is_some());
+}
+
+#[tokio::test]
+async fn test_waits_skip_expired_messages() {
+    let bus = Bus::<u32>::new();
+    let topic = bus.topic("t");
+    let mut subscriber = topic.subscribe();
+
+    let publisher = {
+        let topic = topic.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            topic.publish_with_ttl(1, Duration::ZERO);
+            std::thread::sleep(Duration::from_millis(10));
+            topic.publish(2);
+            std::thread::sleep(Duration::from_millis(10));
+            topic.publish_with_ttl(3, Duration::ZERO);
+            std::thread::sleep(Duration::from_millis(10));
+            topic.publish(4);
+        })
+    };
+    assert_eq!(subscriber.wait_for_message().await, Some(2));
+    assert_eq!(subscriber.recv_blocking(), Some(4));
+    publisher.join().unwrap();
+}
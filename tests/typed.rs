@@ -0,0 +1,127 @@
+use bytes::Bytes;
+use dropslot::prelude::*;
+use std::sync::Arc;
+
+const PRICE: TopicKey<f64> = TopicKey::new("price");
+const STATUS: TopicKey<String> = TopicKey::new("status");
+const PAYLOAD: TopicKey<Bytes> = TopicKey::new("payload");
+const PRICE_TEXT: TopicKey<String> = TopicKey::new("price");
+
+#[test]
+fn test_topics_with_different_types() {
+    let bus = TypedBus::new();
+    let mut prices = bus.subscribe(&PRICE).unwrap();
+    let mut status = bus.subscribe(&STATUS).unwrap();
+    let mut payload = bus.subscribe(&PAYLOAD).unwrap();
+
+    bus.publish(&PRICE, 42.5).unwrap();
+    bus.publish(&STATUS, "open".to_string()).unwrap();
+    bus.publish(&PAYLOAD, Bytes::from_static(b"raw")).unwrap();
+
+    assert_eq!(prices.try_get_message().unwrap(), Some(42.5));
+    assert_eq!(status.try_get_message().unwrap(), Some("open".to_string()));
+    assert_eq!(
+        payload.try_get_message().unwrap(),
+        Some(Bytes::from_static(b"raw"))
+    );
+    assert_eq!(bus.topic_count(), 3);
+}
+
+#[test]
+fn test_same_key_returns_same_topic() {
+    let bus = TypedBus::new();
+    let first = bus.topic(&PRICE).unwrap();
+    let second = bus.topic(&PRICE).unwrap();
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(first.name(), "price");
+}
+
+#[test]
+fn test_type_mismatch_is_reported() {
+    let bus = TypedBus::new();
+    bus.publish(&PRICE, 1.0).unwrap();
+
+    let error = bus.topic(&PRICE_TEXT).unwrap_err();
+    assert!(error.is_type_mismatch());
+    assert!(!error.is_empty());
+    let string_type = std::any::type_name::<String>();
+    assert_eq!(error, BusError::type_mismatch("price", string_type, "f64"));
+    assert_eq!(
+        error.to_string(),
+        format!("Type error: Topic 'price' carries `f64`, not `{string_type}`")
+    );
+
+    assert!(
+        bus.publish(&PRICE_TEXT, "1.0".to_string())
+            .unwrap_err()
+            .is_type_mismatch()
+    );
+    assert!(bus.subscribe(&PRICE_TEXT).unwrap_err().is_type_mismatch());
+
+    // The original topic is untouched.
+    assert_eq!(
+        bus.topic(&PRICE).unwrap().subscribe().get_latest(),
+        Some(1.0)
+    );
+}
+
+#[test]
+fn test_remove_topic_frees_name_for_other_type() {
+    let bus = TypedBus::new();
+    let _subscriber = bus.subscribe(&PRICE).unwrap();
+    assert_eq!(bus.topic_type_name("price"), Some("f64"));
+
+    assert_eq!(bus.remove_topic("price"), Some(1));
+    assert_eq!(bus.remove_topic("price"), None);
+
+    bus.publish(&PRICE_TEXT, "free".to_string()).unwrap();
+    assert_eq!(
+        bus.topic_type_name("price"),
+        Some(std::any::type_name::<String>())
+    );
+}
+
+#[test]
+fn test_cleanup_and_topic_names() {
+    let bus = TypedBus::new();
+    let _status = bus.subscribe(&STATUS).unwrap();
+    bus.topic(&PRICE).unwrap();
+
+    let mut names = bus.topic_names();
+    names.sort();
+    assert_eq!(names, ["price", "status"]);
+
+    assert_eq!(bus.cleanup_unused_topics(), 1);
+    assert_eq!(bus.topic_names(), ["status"]);
+}
+
+#[test]
+fn test_cleanup_keeps_pinned_topics() {
+    let bus = TypedBus::new();
+    bus.topic(&PRICE).unwrap().set_pinned(true);
+    bus.topic(&PAYLOAD).unwrap();
+
+    assert_eq!(bus.cleanup_unused_topics(), 1);
+    assert_eq!(bus.topic_names(), ["price"]);
+}
+
+#[test]
+fn test_typed_bus_across_threads() {
+    let bus = Arc::new(TypedBus::new());
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let bus = bus.clone();
+            std::thread::spawn(move || {
+                for j in 0..100 {
+                    bus.publish(&PRICE, f64::from(i * 100 + j)).unwrap();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(bus.topic_count(), 1);
+    assert!(bus.topic(&PRICE).unwrap().subscribe().has_latest());
+}
@@ -0,0 +1,274 @@
+mod common;
+
+use bytes::Bytes;
+use common::*;
+use dropslot::Bus;
+use dropslot::wal::{BytesCodec, Codec, LogOptions, TopicLog};
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+struct StringCodec;
+
+impl Codec<String> for StringCodec {
+    fn encode(&self, message: &String, buf: &mut Vec<u8>) -> io::Result<()> {
+        buf.extend_from_slice(message.as_bytes());
+        Ok(())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> io::Result<String> {
+        String::from_utf8(bytes.to_vec())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
+
+fn log_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("dropslot-wal-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn test_replay_rebuilds_values_and_versions() {
+    let dir = log_dir("replay");
+    {
+        let log = Arc::new(TopicLog::open(&dir, StringCodec).unwrap());
+        let bus = create_string_bus();
+        let orders = bus.durable_topic("orders", &log);
+        for i in 0..3 {
+            orders.publish(format!("order {i}"));
+        }
+        bus.durable_topic("status", &log)
+            .publish("open".to_string());
+        bus.publish("volatile", "lost".to_string());
+        log.flush().unwrap();
+    }
+
+    let log = Arc::new(TopicLog::open(&dir, StringCodec).unwrap());
+    let bus = create_string_bus();
+    assert_eq!(bus.replay(&log).unwrap(), 2);
+    assert_topic_counts(&bus, 2, &["orders", "status"]);
+
+    let orders = bus.topic("orders");
+    assert!(orders.is_durable());
+    let mut subscriber = orders.subscribe();
+    assert_eq!(subscriber.get_latest(), Some("order 2".to_string()));
+
+    orders.publish("order 3".to_string());
+    let delivery = subscriber.try_get_delivery().unwrap().unwrap();
+    assert_eq!(delivery.version, 4);
+
+    log.flush().unwrap();
+    let snapshot = log.snapshot().unwrap();
+    assert_eq!(snapshot.get("orders").unwrap().value, "order 3");
+    assert_eq!(snapshot.get("orders").unwrap().version, 4);
+
+    drop(log);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_durable_topic_logs_existing_value() {
+    let dir = log_dir("existing");
+    let log = Arc::new(TopicLog::open(&dir, BytesCodec).unwrap());
+    let bus = Bus::<Bytes>::new();
+    bus.publish("config", Bytes::from("v1"));
+    bus.publish("config", Bytes::from("v2"));
+
+    let topic = bus.durable_topic("config", &log);
+    assert!(topic.is_durable());
+    assert!(Arc::ptr_eq(&topic, &bus.durable_topic("config", &log)));
+
+    let snapshot = log.snapshot().unwrap();
+    let config = snapshot.get("config").unwrap();
+    assert_eq!((config.value.as_ref(), config.version), (&b"v2"[..], 2));
+
+    drop(log);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_recreated_topic_continues_logged_versions() {
+    let dir = log_dir("recreate");
+    let log = Arc::new(TopicLog::open(&dir, StringCodec).unwrap());
+    let bus = create_string_bus();
+    let orders = bus.durable_topic("orders", &log);
+    for i in 0..3 {
+        orders.publish(format!("old {i}"));
+    }
+    drop(orders);
+    bus.remove_topic("orders");
+
+    let orders = bus.durable_topic("orders", &log);
+    assert_eq!(orders.version(), 3);
+    orders.publish("new".to_string());
+    assert_eq!(orders.version(), 4);
+    log.flush().unwrap();
+    drop((bus, log));
+
+    // A reopened log continues from its records as well.
+    let log = Arc::new(TopicLog::open(&dir, StringCodec).unwrap());
+    let bus = create_string_bus();
+    bus.publish("orders", "unlogged".to_string());
+    let orders = bus.durable_topic("orders", &log);
+    assert_eq!(orders.version(), 5);
+    log.flush().unwrap();
+    drop(bus);
+
+    let bus = create_string_bus();
+    assert_eq!(bus.replay(&log).unwrap(), 1);
+    let orders = bus.topic("orders");
+    assert_eq!(orders.version(), 5);
+    assert_eq!(
+        orders.subscribe().get_latest(),
+        Some("unlogged".to_string())
+    );
+
+    drop(log);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_segments_roll_and_compact() {
+    let dir = log_dir("compact");
+    let options = LogOptions {
+        max_segment_bytes: 128,
+        ..LogOptions::default()
+    };
+    let log = Arc::new(TopicLog::open_with(&dir, StringCodec, options).unwrap());
+    let bus = create_string_bus();
+    let a = bus.durable_topic("a", &log);
+    let b = bus.durable_topic("b", &log);
+    for i in 0..20 {
+        a.publish(format!("a{i}"));
+        b.publish(format!("b{i}"));
+    }
+    log.flush().unwrap();
+    assert!(log.segment_count().unwrap() > 1);
+
+    assert_eq!(log.compact().unwrap(), 2);
+    assert_eq!(log.segment_count().unwrap(), 1);
+
+    a.publish("a20".to_string());
+    log.flush().unwrap();
+    drop(log);
+
+    let log = Arc::new(TopicLog::open_with(&dir, StringCodec, options).unwrap());
+    let snapshot = log.snapshot().unwrap();
+    assert_eq!(snapshot.len(), 2);
+    let a = snapshot.get("a").unwrap();
+    assert_eq!((a.value.as_str(), a.version), ("a20", 21));
+    let b = snapshot.get("b").unwrap();
+    assert_eq!((b.value.as_str(), b.version), ("b19", 20));
+
+    drop(log);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_open_truncates_torn_record() {
+    let dir = log_dir("torn");
+    {
+        let log = Arc::new(TopicLog::open(&dir, StringCodec).unwrap());
+        let bus = create_string_bus();
+        bus.durable_topic("t", &log).publish("complete".to_string());
+        log.flush().unwrap();
+    }
+
+    let segment = std::fs::read_dir(&dir)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let mut data = std::fs::read(&segment).unwrap();
+    let complete = data.len();
+    data.extend_from_slice(&[42, 0, 0, 0, 1, 2]);
+    std::fs::write(&segment, &data).unwrap();
+
+    let log = Arc::new(TopicLog::open(&dir, StringCodec).unwrap());
+    assert_eq!(std::fs::metadata(&segment).unwrap().len(), complete as u64);
+
+    let bus = create_string_bus();
+    bus.replay(&log).unwrap();
+    bus.topic("t").publish("after crash".to_string());
+    log.flush().unwrap();
+
+    let snapshot = log.snapshot().unwrap();
+    let t = snapshot.get("t").unwrap();
+    assert_eq!((t.value.as_str(), t.version), ("after crash", 2));
+
+    drop(log);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_append_errors_surface_on_flush() {
+    struct FailingCodec;
+
+    impl Codec<String> for FailingCodec {
+        fn encode(&self, _: &String, _: &mut Vec<u8>) -> io::Result<()> {
+            Err(io::Error::other("cannot encode"))
+        }
+
+        fn decode(&self, _: &[u8]) -> io::Result<String> {
+            unreachable!()
+        }
+    }
+
+    let dir = log_dir("errors");
+    let log = Arc::new(TopicLog::open(&dir, FailingCodec).unwrap());
+    let bus = create_string_bus();
+    bus.durable_topic("t", &log).publish("x".to_string());
+
+    assert_eq!(log.flush().unwrap_err().to_string(), "cannot encode");
+    assert!(log.flush().is_ok());
+    assert!(log.snapshot().unwrap().is_empty());
+
+    drop(log);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_publish_durable_returns_append_errors() {
+    struct PickyCodec;
+
+    impl Codec<String> for PickyCodec {
+        fn encode(&self, message: &String, buf: &mut Vec<u8>) -> io::Result<()> {
+            if message == "bad" {
+                return Err(io::Error::other("cannot encode"));
+            }
+            StringCodec.encode(message, buf)
+        }
+
+        fn decode(&self, bytes: &[u8]) -> io::Result<String> {
+            StringCodec.decode(bytes)
+        }
+    }
+
+    let dir = log_dir("publish-durable");
+    let log = Arc::new(TopicLog::open(&dir, PickyCodec).unwrap());
+    let bus = create_string_bus();
+    let topic = bus.durable_topic("t", &log);
+    let mut subscriber = topic.subscribe();
+
+    assert_eq!(topic.publish_durable("good".to_string()).unwrap(), 1);
+    assert_eq!(
+        subscriber.try_get_message().unwrap(),
+        Some("good".to_string())
+    );
+
+    let error = topic.publish_durable("bad".to_string()).unwrap_err();
+    assert_eq!(error.to_string(), "cannot encode");
+    assert!(subscriber.try_get_message().unwrap_err().is_empty());
+    assert_eq!(topic.version(), 1);
+    assert!(log.flush().is_ok());
+
+    let plain = bus.topic("plain");
+    let error = plain.publish_durable("x".to_string()).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+    assert!(plain.subscribe().get_latest().is_none());
+
+    drop(log);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
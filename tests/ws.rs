@@ -0,0 +1,160 @@
+#![cfg(feature = "ws")]
+
+mod common;
+
+use common::*;
+use dropslot::Bus;
+use dropslot::ws::WsGateway;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{Value, json};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+const WAIT: Duration = Duration::from_secs(5);
+
+async fn start_gateway<T>(bus: Arc<Bus<T>>) -> SocketAddr
+where
+    T: Clone + Send + Sync + serde::Serialize + 'static,
+{
+    let gateway = WsGateway::bind("127.0.0.1:0", bus).await.unwrap();
+    let addr = gateway.local_addr().unwrap();
+    tokio::spawn(gateway.serve());
+    addr
+}
+
+async fn connect(addr: SocketAddr) -> Client {
+    let (client, _) = tokio_tungstenite::connect_async(format!("ws://{addr}"))
+        .await
+        .unwrap();
+    client
+}
+
+async fn send(client: &mut Client, frame: Value) {
+    client.send(Message::text(frame.to_string())).await.unwrap();
+}
+
+async fn next_frame(client: &mut Client) -> Value {
+    loop {
+        let message = timeout(WAIT, client.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        if let Message::Text(text) = message {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_subscribe_receives_current_value_and_updates() {
+    let bus = create_string_bus_arc();
+    bus.publish("status", "online".to_string());
+    let addr = start_gateway(bus.clone()).await;
+
+    let mut client = connect(addr).await;
+    send(&mut client, json!({"type": "subscribe", "topic": "status"})).await;
+    let frame = next_frame(&mut client).await;
+    assert_eq!(
+        frame,
+        json!({"type": "update", "topic": "status", "version": 1, "value": "online"})
+    );
+
+    bus.publish("status", "busy".to_string());
+    let frame = next_frame(&mut client).await;
+    assert_eq!(
+        (&frame["value"], &frame["version"]),
+        (&json!("busy"), &json!(2))
+    );
+}
+
+#[tokio::test]
+async fn test_wildcards_and_unsubscribe() {
+    let bus = Arc::new(Bus::<f64>::new());
+    let addr = start_gateway(bus.clone()).await;
+
+    let mut client = connect(addr).await;
+    send(
+        &mut client,
+        json!({"type": "subscribe", "topic": "sensors/+/temp"}),
+    )
+    .await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    bus.publish("sensors/kitchen/humidity", 40.0);
+    bus.publish("sensors/kitchen/temp", 21.5);
+    let frame = next_frame(&mut client).await;
+    assert_eq!(frame["topic"], json!("sensors/kitchen/temp"));
+    assert_eq!(frame["value"], json!(21.5));
+
+    send(
+        &mut client,
+        json!({"type": "unsubscribe", "topic": "sensors/+/temp"}),
+    )
+    .await;
+    send(&mut client, json!({"type": "subscribe", "topic": "alarms"})).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    bus.publish("sensors/kitchen/temp", 22.0);
+    bus.publish("alarms", 1.0);
+    let frame = next_frame(&mut client).await;
+    assert_eq!(frame["topic"], json!("alarms"));
+}
+
+#[tokio::test]
+async fn test_errors_keep_connection_open() {
+    let bus = create_string_bus_arc();
+    bus.publish("ok", "fine".to_string());
+    let addr = start_gateway(bus).await;
+
+    let mut client = connect(addr).await;
+    send(&mut client, json!({"type": "subscribe", "topic": "a/#/b"})).await;
+    let frame = next_frame(&mut client).await;
+    assert_eq!(frame["type"], json!("error"));
+
+    client.send(Message::text("not json")).await.unwrap();
+    let frame = next_frame(&mut client).await;
+    assert_eq!(frame["type"], json!("error"));
+    assert!(
+        frame["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid frame")
+    );
+
+    send(&mut client, json!({"type": "subscribe", "topic": "ok"})).await;
+    let frame = next_frame(&mut client).await;
+    assert_eq!(frame["value"], json!("fine"));
+}
+
+#[tokio::test]
+async fn test_slow_client_is_conflated() {
+    let bus = Arc::new(Bus::<u64>::new());
+    let addr = start_gateway(bus.clone()).await;
+
+    let mut client = connect(addr).await;
+    send(&mut client, json!({"type": "subscribe", "topic": "ticks"})).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    for i in 1..=1_000 {
+        bus.publish("ticks", i);
+        if i % 100 == 0 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    let mut received = 0;
+    loop {
+        let frame = next_frame(&mut client).await;
+        received += 1;
+        if frame["value"] == json!(1_000) {
+            assert_eq!(frame["version"], json!(1_000));
+            break;
+        }
+    }
+    assert!(received < 1_000, "received {received} frames");
+}